@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use actix_web::{web, HttpResponse, Responder};
+use carbonable_domain::infrastructure::postgres::failed_event::count_failed_events;
+use serde::Serialize;
+
+use crate::{
+    common::{ApiError, ServerResponse},
+    AppDependencies,
+};
+
+#[derive(Serialize)]
+struct FailedEventsCount {
+    total: i64,
+    projections: BTreeMap<String, i64>,
+}
+
+/// Number of events projections failed to consume, a non zero value means read models have gaps
+pub async fn get_failed_events_count(
+    data: web::Data<AppDependencies>,
+) -> Result<impl Responder, ApiError> {
+    let client = data.db_client_pool.get().await?;
+    let projections = count_failed_events(&client)
+        .await?
+        .into_iter()
+        .collect::<BTreeMap<String, i64>>();
+
+    Ok(HttpResponse::Ok().json(ServerResponse::Data {
+        data: FailedEventsCount {
+            total: projections.values().sum(),
+            projections,
+        },
+    }))
+}
@@ -1,2 +1,3 @@
 pub mod details;
 pub mod list;
+pub mod sales;
@@ -0,0 +1,20 @@
+use actix_web::{web, HttpResponse, Responder};
+use carbonable_domain::infrastructure::postgres::minter::get_launchpad_sales;
+
+use crate::{
+    common::{ApiError, ServerResponse},
+    AppDependencies,
+};
+
+/// Sales volume, number of buyers and daily mints of project, from its minters events
+pub async fn launchpad_sales(
+    slug_param: web::Path<String>,
+    data: web::Data<AppDependencies>,
+) -> Result<impl Responder, ApiError> {
+    let slug = slug_param.into_inner();
+    let client = data.db_client_pool.get().await?;
+    match get_launchpad_sales(&client, &slug).await? {
+        Some(sales) => Ok(HttpResponse::Ok().json(ServerResponse::Data { data: sales })),
+        None => Ok(HttpResponse::NotFound().into()),
+    }
+}
@@ -11,11 +11,13 @@ use tracing::info;
 use crate::{common::ApiError, latest::get_latest_block};
 
 pub mod common;
+pub mod failed_events;
 pub mod farming;
 pub mod latest;
 pub mod launchpad;
 pub mod portfolio;
 pub mod project;
+pub mod unknown_events;
 
 #[get("/ping")]
 async fn ping() -> impl Responder {
@@ -60,13 +62,32 @@ async fn main() -> std::io::Result<()> {
             .service(ping)
             .service(get_config)
             .service(web::scope("/latest").route("/block", web::get().to(get_latest_block)))
-            .service(web::scope("/portfolio").route(
-                "/{wallet}",
-                web::get().to(portfolio::get_by_wallet::get_by_wallet),
+            .service(web::scope("/failed-events").route(
+                "/count",
+                web::get().to(failed_events::get_failed_events_count),
+            ))
+            .service(web::scope("/unknown-events").route(
+                "/count",
+                web::get().to(unknown_events::get_unknown_events_count),
             ))
+            .service(
+                web::scope("/portfolio")
+                    .route(
+                        "/{wallet}",
+                        web::get().to(portfolio::get_by_wallet::get_by_wallet),
+                    )
+                    .route(
+                        "/{wallet}/approvals",
+                        web::get().to(portfolio::approvals::get_approvals),
+                    ),
+            )
             .service(
                 web::scope("/projects")
-                    .route("/{slug}", web::get().to(project::get_by_slug::get_by_slug)),
+                    .route("/{slug}", web::get().to(project::get_by_slug::get_by_slug))
+                    .route(
+                        "/{slug}/absorption-curves",
+                        web::get().to(project::absorption_curves::absorption_curves),
+                    ),
             )
             .service(
                 web::scope("/farming")
@@ -98,6 +119,10 @@ async fn main() -> std::io::Result<()> {
                     .route(
                         "/details/{slug}",
                         web::get().to(launchpad::details::launchpad_details),
+                    )
+                    .route(
+                        "/sales/{slug}",
+                        web::get().to(launchpad::sales::launchpad_sales),
                     ),
             )
     })
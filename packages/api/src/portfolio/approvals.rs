@@ -0,0 +1,23 @@
+use actix_web::{web, HttpResponse, Responder};
+use carbonable_domain::infrastructure::{
+    postgres::customer::PostgresCustomer, starknet::ensure_starknet_wallet,
+};
+
+use crate::{
+    common::{ApiError, ServerResponse},
+    AppDependencies,
+};
+
+/// Operators allowed to move wallet tokens, from project approval events
+pub async fn get_approvals(
+    data: web::Data<AppDependencies>,
+    wallet_param: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let mut wallet = wallet_param.into_inner();
+    ensure_starknet_wallet(&mut wallet);
+
+    let customer_model = PostgresCustomer::new(data.db_client_pool.clone());
+    let approvals = customer_model.get_customer_approvals(&wallet).await?;
+
+    Ok(HttpResponse::Ok().json(ServerResponse::Data { data: approvals }))
+}
@@ -1 +1,2 @@
+pub mod approvals;
 pub mod get_by_wallet;
@@ -0,0 +1,18 @@
+use actix_web::{web, HttpResponse, Responder};
+use carbonable_domain::infrastructure::postgres::project::get_absorption_curves;
+
+use crate::{
+    common::{ApiError, ServerResponse},
+    AppDependencies,
+};
+
+/// Revisions of project absorption curve, from its `AbsorptionUpdate` events
+pub async fn absorption_curves(
+    slug_param: web::Path<String>,
+    data: web::Data<AppDependencies>,
+) -> Result<impl Responder, ApiError> {
+    let slug = slug_param.into_inner();
+    let client = data.db_client_pool.get().await?;
+    let revisions = get_absorption_curves(&client, &slug).await?;
+    Ok(HttpResponse::Ok().json(ServerResponse::Data { data: revisions }))
+}
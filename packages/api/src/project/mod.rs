@@ -1 +1,2 @@
+pub mod absorption_curves;
 pub mod get_by_slug;
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use actix_web::{web, HttpResponse, Responder};
+use carbonable_domain::infrastructure::postgres::{
+    event_store::count_unknown_events, skipped_event::count_skipped_events,
+};
+use serde::Serialize;
+
+use crate::{
+    common::{ApiError, ServerResponse},
+    AppDependencies,
+};
+
+#[derive(Serialize)]
+struct UnknownEventsCount {
+    total: i64,
+    types: BTreeMap<String, i64>,
+    skipped: BTreeMap<String, BTreeMap<String, i64>>,
+}
+
+/// Number of stored events no filter recognised, and how many of them each projection skipped.
+/// They are resolved when projections start once their type is supported
+pub async fn get_unknown_events_count(
+    data: web::Data<AppDependencies>,
+) -> Result<impl Responder, ApiError> {
+    let client = data.db_client_pool.get().await?;
+    let types = count_unknown_events(&client)
+        .await?
+        .into_iter()
+        .collect::<BTreeMap<String, i64>>();
+    let mut skipped: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+    for (projection, r#type, count) in count_skipped_events(&client).await? {
+        skipped.entry(projection).or_default().insert(r#type, count);
+    }
+
+    Ok(HttpResponse::Ok().json(ServerResponse::Data {
+        data: UnknownEventsCount {
+            total: types.values().sum(),
+            types,
+            skipped,
+        },
+    }))
+}
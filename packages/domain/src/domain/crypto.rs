@@ -91,6 +91,11 @@ impl From<&U256> for sea_query::Value {
         sea_query::Value::Bytes(Some(Box::new(value.0.to_be_bytes().to_vec())))
     }
 }
+impl sea_query::Nullable for U256 {
+    fn null() -> sea_query::Value {
+        sea_query::Value::Bytes(None)
+    }
+}
 impl From<u64> for U256 {
     fn from(value: u64) -> Self {
         U256(crypto_bigint::U256::from_u64(value))
@@ -1,12 +1,24 @@
+use apibara_core::node::v1alpha2::Cursor;
 use deadpool_postgres::{Pool, Transaction};
 use thiserror::Error;
-use tracing::{debug, error};
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
 
-use crate::infrastructure::postgres::event_source::{
-    event_was_processed, insert_last_domain_event,
+use crate::{
+    domain::Ulid,
+    infrastructure::postgres::{
+        checkpoint::store_checkpoint,
+        event_source::insert_domain_events,
+        failed_event::{delete_failed_event, store_failed_event, DeadLetter},
+        projection::store_projection_checkpoint,
+        skipped_event::store_skipped_event,
+    },
 };
 
-use super::{transaction::TransactionManager, BlockMetadata, DomainError, DomainEvent, Event};
+use super::{
+    projection::Projection, retry::RetryPolicy, transaction::TransactionManager,
+    upcaster::UpcasterChain, BlockMetadata, DomainError, DomainEvent, Event,
+};
 use std::{fmt::Debug, sync::Arc};
 
 #[derive(Debug, Error)]
@@ -36,7 +48,9 @@ where
 #[derive(Debug)]
 pub struct EventBus<Store, Consumer> {
     pub(crate) client_pool: Arc<Store>,
-    pub(crate) consumers: Vec<Consumer>,
+    pub(crate) consumers: Vec<(Projection, Consumer)>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) upcasters: UpcasterChain,
 }
 
 impl EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>> {
@@ -44,37 +58,66 @@ impl EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>> {
         Self {
             client_pool,
             consumers: vec![],
+            retry_policy: RetryPolicy::default(),
+            upcasters: UpcasterChain::default(),
         }
     }
 
+    /// Retry policy applied when a consumer fails with a transient error
+    /// * `retry_policy` - [`RetryPolicy`]
+    ///
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Add event consumer
+    /// * `projection` - [`Projection`] - Projection the consumer builds
     /// * `consumer` - [`Consumer`]
     ///
-    pub fn add_consumer(&mut self, consumer: Box<dyn for<'a> Consumer<Transaction<'a>>>) {
-        self.consumers.push(consumer);
+    pub fn add_consumer(
+        &mut self,
+        projection: Projection,
+        consumer: Box<dyn for<'a> Consumer<Transaction<'a>>>,
+    ) {
+        self.consumers.push((projection, consumer));
+    }
+
+    /// Projections having at least one consumer
+    pub fn projections(&self) -> Vec<Projection> {
+        Projection::all()
+            .into_iter()
+            .filter(|p| self.consumers.iter().any(|(projection, _)| projection == p))
+            .collect()
     }
 
-    /// Forward event to consumers.
-    /// Add logic for pre.event and post.event
+    /// Store every domain event of a stream batch.
+    /// Events and stream checkpoint are persisted within the same db.tx
+    /// so that indexing can resume exactly after last stored batch.
     ///
-    /// Create db.tx commit if success
-    /// * `event` - [`DomainEvent`]
-    /// * `event` - [`BlockMetadata`]
-    pub async fn register(
+    /// * `events` - [`&[(DomainEvent, BlockMetadata)]`]
+    /// * `stream` - [`&str`] - Checkpoint stream name
+    /// * `cursor` - [`&Cursor`] - Batch end cursor
+    pub async fn register_batch(
         &self,
-        event: &DomainEvent,
-        metadata: &BlockMetadata,
+        events: &[(DomainEvent, BlockMetadata)],
+        stream: &str,
+        cursor: &Cursor,
     ) -> Result<(), DomainError> {
         let mut client = self.client_pool.clone().get().await?;
-        if event_was_processed(&client, event.id.as_str()).await {
-            return Ok(());
-        }
         let tx = client.transaction().await?;
 
-        // Rollback transaction if storing domain event fails
-        match insert_last_domain_event(&tx, event, metadata).await {
-            Ok(_) => match tx.commit().await {
-                Ok(_) => Ok(()),
+        // Rollback transaction if storing domain events fails
+        let stored = match insert_domain_events(&tx, events, stream).await {
+            Ok(stored) => store_checkpoint(&tx, stream, cursor).await.map(|_| stored),
+            Err(err) => Err(err),
+        };
+        match stored {
+            Ok(stored) => match tx.commit().await {
+                Ok(_) => {
+                    debug!("event_store.batch: {} new events", stored);
+                    Ok(())
+                }
                 Err(_) => Err(DomainError::FailedToPersistEvent),
             },
             Err(err) => {
@@ -85,29 +128,137 @@ impl EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>> {
         }
     }
 
-    pub async fn consume_event_store(
+    /// Dispatch an event store event to consumers of given projection.
+    /// Projection checkpoint is moved to `key` within the same db.tx. If a consumer fails,
+    /// its changes are rolled back. Transient failures are retried following [`RetryPolicy`],
+    /// then the event is stored into `failed_event` so that the projection moves on without it.
+    ///
+    /// * `projection` - [`Projection`]
+    /// * `event` - [`&DomainEvent`]
+    /// * `metadata` - [`&BlockMetadata`]
+    /// * `previous` - [`Option<Ulid>`] - Projection checkpoint read before consuming the event
+    /// * `key` - [`Ulid`] - event_store id of the event
+    pub async fn consume_projection(
         &self,
+        projection: Projection,
+        event: &DomainEvent,
+        metadata: &BlockMetadata,
+        previous: Option<Ulid>,
+        key: Ulid,
+    ) -> Result<(), DomainError> {
+        let mut attempts = 0;
+        loop {
+            let mut client = self.client_pool.clone().get().await?;
+            let mut tx = client.transaction().await?;
+            if let Err((consumer, e)) = self.dispatch(projection, event, metadata, &mut tx).await {
+                let _ = tx.rollback().await;
+                attempts += 1;
+
+                if let Some(delay) = self.retry_policy.backoff(attempts, &e) {
+                    warn!(
+                        "{} failed on event {} ({}), retrying in {:?}",
+                        consumer, event.id, e, delay
+                    );
+                    // client goes back to the pool while waiting, a new one is used on retry
+                    drop(client);
+                    sleep(delay).await;
+                    continue;
+                }
+                tx = client.transaction().await?;
+                store_failed_event(&tx, projection, &consumer, key, &event.id, &e.to_string())
+                    .await?;
+            }
+
+            if !store_projection_checkpoint(&tx, projection, previous, key).await? {
+                let _ = tx.rollback().await;
+                return Err(DomainError::ProjectionCheckpointMoved(
+                    projection.to_string(),
+                ));
+            }
+
+            return match tx.commit().await {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    error!("event_store.{}.committing.error: {:#?}", projection, e);
+                    Err(DomainError::FailedToPersistEvent)
+                }
+            };
+        }
+    }
+
+    /// Consume a failed event again into its projection.
+    /// Failed event is removed on success, its attempts are incremented otherwise.
+    ///
+    /// * `dead_letter` - [`&DeadLetter`]
+    /// * `event` - [`&DomainEvent`]
+    /// * `metadata` - [`&BlockMetadata`]
+    pub async fn retry_failed_event(
+        &self,
+        dead_letter: &DeadLetter,
         event: &DomainEvent,
         metadata: &BlockMetadata,
     ) -> Result<(), DomainError> {
         let mut client = self.client_pool.clone().get().await?;
         let mut tx = client.transaction().await?;
-        for consumer in &self.consumers {
+        match self
+            .dispatch(dead_letter.projection, event, metadata, &mut tx)
+            .await
+        {
+            Ok(_) => {
+                delete_failed_event(&tx, dead_letter.id).await?;
+                tx.commit().await?;
+                Ok(())
+            }
+            Err((consumer, e)) => {
+                let _ = tx.rollback().await;
+                let tx = client.transaction().await?;
+                store_failed_event(
+                    &tx,
+                    dead_letter.projection,
+                    &consumer,
+                    dead_letter.event_store_id,
+                    &event.id,
+                    &e.to_string(),
+                )
+                .await?;
+                tx.commit().await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Run consumers of given projection on event upcasted to its current schema version,
+    /// returns failing consumer name on error
+    async fn dispatch<'a>(
+        &self,
+        projection: Projection,
+        event: &DomainEvent,
+        metadata: &BlockMetadata,
+        tx: &mut Transaction<'a>,
+    ) -> Result<(), (String, DomainError)> {
+        // skipped unknown events are counted within the db.tx moving projection checkpoint
+        if let Event::Unknown { contract, selector } = &event.r#type {
+            return store_skipped_event(tx, projection, contract, selector, &event.id)
+                .await
+                .map_err(|e| ("EventBus".to_owned(), DomainError::from(e)));
+        }
+        let event = &self
+            .upcasters
+            .upcast(event.clone())
+            .map_err(|e| ("UpcasterChain".to_owned(), e))?;
+        for (_, consumer) in self.consumers.iter().filter(|(p, _)| p == &projection) {
             if consumer.can_consume(&event.r#type) {
                 debug!(
-                    "Dispatching event: {:?} with id : {:?}",
-                    &event.r#type, &event.id
+                    "Dispatching event: {:?} with id : {:?} to {}",
+                    &event.r#type, &event.id, projection
                 );
-                consumer.consume(event, metadata, &mut tx).await?;
+                consumer
+                    .consume(event, metadata, tx)
+                    .await
+                    .map_err(|e| (format!("{:?}", consumer), e))?;
             }
         }
 
-        match tx.commit().await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                error!("event_store.domain_event.committing.error: {:#?}", e);
-                Ok(())
-            }
-        }
+        Ok(())
     }
 }
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use time::OffsetDateTime;
+
+use crate::infrastructure::in_memory::{InMemoryProjectionStore, InMemoryTransaction};
+
+use super::{
+    event_bus::Consumer, transaction::TransactionManager, BlockMetadata, DomainError, DomainEvent,
+    Event,
+};
+
+/// Event emitted by `from_address` with positional payload `data`
+pub(crate) fn event(id: &str, r#type: Event, from_address: &str, data: &[&str]) -> DomainEvent {
+    DomainEvent {
+        id: id.to_owned(),
+        metadata: HashMap::from([("from_address".to_owned(), from_address.to_owned())]),
+        payload: data
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (i.to_string(), d.to_string()))
+            .collect(),
+        keys: vec![],
+        r#type,
+        schema_version: 1,
+    }
+}
+
+pub(crate) fn metadata() -> BlockMetadata {
+    BlockMetadata {
+        hash: "0x0abc".to_owned(),
+        timestamp: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+        number: 612_000,
+    }
+}
+
+/// Consume `events` within a single transaction of `store`, committed once all of them are
+/// consumed as projection runners do. Nothing is committed when a consumer fails
+pub(crate) async fn consume_all<C>(
+    store: &InMemoryProjectionStore,
+    consumer: &C,
+    events: &[DomainEvent],
+) -> Result<(), DomainError>
+where
+    C: Consumer<InMemoryTransaction>,
+{
+    let mut txn = store.transaction();
+    for event in events {
+        consumer.consume(event, &metadata(), &mut txn).await?;
+    }
+    txn.commit()
+        .await
+        .expect("in memory transaction should commit");
+    Ok(())
+}
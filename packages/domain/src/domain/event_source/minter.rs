@@ -1,14 +1,15 @@
 use std::{collections::HashMap, sync::Arc};
 
 use apibara_core::starknet::v1alpha2::FieldElement;
-use deadpool_postgres::Transaction;
 use serde::{Deserialize, Serialize};
 use starknet::macros::selector;
+use time::PrimitiveDateTime;
+use tracing::error;
 
 use crate::{
-    domain::crypto::U256,
+    domain::{crypto::U256, Ulid},
     infrastructure::{
-        postgres::event_source::{migrate_customer_token, update_project_total_value},
+        postgres::entity::{Airdrop, Buy},
         starknet::{
             get_starknet_rpc_from_env,
             model::{parallelize_blockchain_rpc_calls, u256_to_felt},
@@ -16,9 +17,19 @@ use crate::{
     },
 };
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{
-    event_bus::Consumer, get_event, to_filters, BlockMetadata, DomainError, DomainEvent, Event,
-    Filterable,
+    active_addresses, add_nested_paths,
+    event_bus::Consumer,
+    get_event,
+    payload::{self, Migration},
+    repository::{
+        CustomerTokenRepository, MinterSaleRepository, MinterStatusRepository, ProjectRepository,
+    },
+    to_filters,
+    transaction::TransactionManager,
+    BlockMetadata, DomainError, DomainEvent, Event, Filterable,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,7 +49,7 @@ pub enum MinterEvents {
 #[derive(Debug)]
 pub struct MinterFilters {
     contracts: Vec<String>,
-    filters: HashMap<String, Vec<(String, Event)>>,
+    filters: HashMap<String, Vec<(Vec<String>, Event)>>,
 }
 
 impl MinterFilters {
@@ -57,33 +68,86 @@ impl Default for MinterFilters {
 
 /// Add [`Filterable`] behaviour on [`Minter`]
 impl Filterable for MinterFilters {
-    fn to_filters(&self) -> Vec<(String, String)> {
+    fn to_filters(&self) -> Vec<(String, Vec<String>)> {
         to_filters(&self.filters)
     }
 
-    fn get_event(&mut self, contract_address: &str, event_key: &str) -> Option<Event> {
-        get_event(&mut self.filters, contract_address, event_key)
+    fn get_event(&mut self, contract_address: &str, keys: &[String]) -> Option<(Event, usize)> {
+        get_event(&mut self.filters, contract_address, keys)
     }
 
-    fn hydrate_from_file(&mut self, address_list: Vec<HashMap<String, String>>) {
-        let contracts = self.extract_addresses(address_list, &["minter"]);
-        self.contracts = contracts;
+    fn add_nested_paths(&mut self, contract_address: &str, paths: &[Vec<String>]) {
+        add_nested_paths(&mut self.filters, contract_address, paths)
+    }
+
+    fn hydrate(&mut self, contracts: &[RegisteredContract]) {
+        self.contracts = active_addresses(contracts, &[ContractType::Minter]);
+        // filters are rebuilt so that deactivated contracts are dropped
+        self.filters.clear();
         for contract in self.contracts.iter() {
             self.filters.insert(
                 contract.to_string(),
                 [
                     (
-                        FieldElement::from_bytes(&selector!("Migration").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Upgraded").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Minter(MinterEvents::Upgraded),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("Migration").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Minter(MinterEvents::Migration),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Airdrop").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Airdrop").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Minter(MinterEvents::Airdrop),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Buy").to_bytes_be()).to_string(),
+                        vec![FieldElement::from_bytes(&selector!("Buy").to_bytes_be()).to_string()],
                         Event::Minter(MinterEvents::Buy),
                     ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("PreSaleOpen").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Minter(MinterEvents::PreSaleOpen),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("PreSaleClosed").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Minter(MinterEvents::PreSaleClosed),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("PublicSaleOpen").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Minter(MinterEvents::PublicSaleOpen),
+                    ),
+                    (
+                        vec![FieldElement::from_bytes(
+                            &selector!("PublicSaleClosed").to_bytes_be(),
+                        )
+                        .to_string()],
+                        Event::Minter(MinterEvents::PublicSaleClosed),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("SoldOut").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Minter(MinterEvents::SoldOut),
+                    ),
                 ]
                 .to_vec(),
             );
@@ -101,7 +165,10 @@ impl MinterMigrationEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for MinterMigrationEventConsumer {
+impl<Txn> Consumer<Txn> for MinterMigrationEventConsumer
+where
+    Txn: TransactionManager + CustomerTokenRepository + ProjectRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Minter(MinterEvents::Migration))
     }
@@ -110,28 +177,17 @@ impl Consumer<Transaction<'_>> for MinterMigrationEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        // Migration(address: felt, tokenId: u256, newTokenId: u256, slot: u256, value: u256);
         // Catch migration to update total_supply on project if
-        let minter_721 = event
-            .metadata
-            .get("from_address")
-            .expect("should have from address");
-        let customer_address = event.payload.get("0").expect("should have slot");
-        let token_id = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have token_id")).unwrap(),
-        );
-        let new_token_id = U256::from(
-            FieldElement::from_hex(event.payload.get("3").expect("should have new_token_id"))
-                .unwrap(),
-        );
-        let slot = U256::from(
-            FieldElement::from_hex(event.payload.get("5").expect("should have slot")).unwrap(),
-        );
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("7").expect("should have value")).unwrap(),
-        );
+        let minter_721 = event.from_address()?;
+        let Migration {
+            customer_address,
+            token_id,
+            new_token_id,
+            slot,
+            value,
+        } = Migration::try_from(event)?;
 
         let provider = Arc::new(get_starknet_rpc_from_env()?);
 
@@ -167,20 +223,21 @@ impl Consumer<Transaction<'_>> for MinterMigrationEventConsumer {
             &data[0].clone().first().unwrap().to_bytes_be(),
         ));
 
-        let _ =
-            update_project_total_value(txn, &project_address.to_hex(), &slot, &total_value).await?;
+        let _ = txn
+            .update_project_total_value(&project_address.to_hex(), &slot, &total_value)
+            .await?;
 
-        let _ = migrate_customer_token(
-            txn,
-            &project_address.to_hex(),
-            &from_project_address.to_hex(),
-            customer_address,
-            &token_id,
-            &new_token_id,
-            &slot,
-            &value,
-        )
-        .await?;
+        let _ = txn
+            .migrate_customer_token(
+                &project_address.to_hex(),
+                &from_project_address.to_hex(),
+                &customer_address,
+                &token_id,
+                &new_token_id,
+                &slot,
+                &value,
+            )
+            .await?;
 
         // event not handled at the moment but it will be stored in database later on.
         Ok(())
@@ -197,20 +254,44 @@ impl MinterAirdropEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for MinterAirdropEventConsumer {
+impl<Txn> Consumer<Txn> for MinterAirdropEventConsumer
+where
+    Txn: TransactionManager + MinterSaleRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Minter(MinterEvents::Airdrop))
     }
 
     async fn consume(
         &self,
-        _event: &DomainEvent,
-        _metadata: &BlockMetadata,
-        _txn: &mut Transaction,
+        event: &DomainEvent,
+        metadata: &BlockMetadata,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        // Airdrop(address: felt, quantity: felt, time: felt)
-        // event not handled at the moment but it will be stored in database later on.
-        Ok(())
+        let minter_address = event.from_address()?;
+        let payload = payload::Airdrop::try_from(event)?;
+        let minter_id = match txn.get_minter_id_from_address(minter_address).await? {
+            Some(id) => id,
+            None => {
+                error!(
+                    "minter.airdrop => did not find minter matching address: {}",
+                    minter_address
+                );
+                return Err(DomainError::ContractNotFound(minter_address.to_string()));
+            }
+        };
+
+        let airdrop = Airdrop {
+            id: Ulid::new(),
+            hash: event.tx_hash().to_owned(),
+            event_id: event.id().to_owned(),
+            address: payload.customer_address,
+            quantity: payload.quantity,
+            time: PrimitiveDateTime::new(payload.time.date(), payload.time.time()),
+            block_id: U256::from(metadata.get_block()),
+            minter_id: Some(minter_id),
+        };
+        txn.add_airdrop_to_minter(&airdrop).await
     }
 }
 
@@ -224,19 +305,228 @@ impl MinterBuyEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for MinterBuyEventConsumer {
+impl<Txn> Consumer<Txn> for MinterBuyEventConsumer
+where
+    Txn: TransactionManager + MinterSaleRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Minter(MinterEvents::Buy))
     }
 
     async fn consume(
         &self,
-        _event: &DomainEvent,
+        event: &DomainEvent,
+        metadata: &BlockMetadata,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let minter_address = event.from_address()?;
+        let payload = payload::Buy::try_from(event)?;
+        let minter_id = match txn.get_minter_id_from_address(minter_address).await? {
+            Some(id) => id,
+            None => {
+                error!(
+                    "minter.buy => did not find minter matching address: {}",
+                    minter_address
+                );
+                return Err(DomainError::ContractNotFound(minter_address.to_string()));
+            }
+        };
+
+        let buy = Buy {
+            id: Ulid::new(),
+            hash: event.tx_hash().to_owned(),
+            event_id: event.id().to_owned(),
+            address: payload.customer_address,
+            quantity: payload.quantity,
+            amount: Some(payload.amount),
+            time: PrimitiveDateTime::new(payload.time.date(), payload.time.time()),
+            block_id: U256::from(metadata.get_block()),
+            minter_id: Some(minter_id),
+        };
+        txn.add_buy_to_minter(&buy).await
+    }
+}
+
+/// Consuming [`PreSaleOpen`], [`PreSaleClosed`], [`PublicSaleOpen`], [`PublicSaleClosed`] and
+/// [`SoldOut`] events emitted from [`Minter`] on chain, each one carries its `time: felt` only
+#[derive(Default, Debug)]
+pub struct MinterSaleStatusEventConsumer {}
+impl MinterSaleStatusEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for MinterSaleStatusEventConsumer
+where
+    Txn: TransactionManager + MinterStatusRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(
+            event,
+            Event::Minter(
+                MinterEvents::PreSaleOpen
+                    | MinterEvents::PreSaleClosed
+                    | MinterEvents::PublicSaleOpen
+                    | MinterEvents::PublicSaleClosed
+                    | MinterEvents::SoldOut
+            )
+        )
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
         _metadata: &BlockMetadata,
-        _txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        // Airdrop(address: felt, amount: u256, quantity: felt, time: felt)
-        // event not handled at the moment but it will be stored in database later on.
-        Ok(())
+        let minter_address = event.from_address()?;
+        match event.r#type {
+            Event::Minter(MinterEvents::PreSaleOpen) => {
+                txn.update_minter_pre_sale_open(minter_address, true).await
+            }
+            Event::Minter(MinterEvents::PreSaleClosed) => {
+                txn.update_minter_pre_sale_open(minter_address, false).await
+            }
+            Event::Minter(MinterEvents::PublicSaleOpen) => {
+                txn.update_minter_public_sale_open(minter_address, true)
+                    .await
+            }
+            Event::Minter(MinterEvents::PublicSaleClosed) => {
+                txn.update_minter_public_sale_open(minter_address, false)
+                    .await
+            }
+            Event::Minter(MinterEvents::SoldOut) => {
+                txn.update_minter_sold_out(minter_address, true).await
+            }
+            _ => Err(DomainError::InvalidEventPayload(
+                event.id().to_owned(),
+                format!("unexpected event type {:?}", event.r#type),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            crypto::U256,
+            event_source::{
+                fixtures::{consume_all, event},
+                Event,
+            },
+        },
+        infrastructure::in_memory::InMemoryProjectionStore,
+    };
+
+    use super::{
+        MinterAirdropEventConsumer, MinterBuyEventConsumer, MinterEvents,
+        MinterSaleStatusEventConsumer,
+    };
+
+    const MINTER: &str = "0x0789";
+
+    #[tokio::test]
+    async fn test_buy_and_airdrop_are_added_to_minter_once_per_event() {
+        let store = InMemoryProjectionStore::new().with_minter(MINTER);
+        // identical buys of a single transaction only differ by their event id
+        let buys = ["0x01_0", "0x01_1"].map(|id| {
+            let mut buy = event(
+                id,
+                Event::Minter(MinterEvents::Buy),
+                MINTER,
+                &["0x0ABC", "0x1e8480", "0x0", "0x2", "0x6553f100"],
+            );
+            buy.metadata.insert("tx_hash".to_owned(), "0x01".to_owned());
+            buy
+        });
+        let airdrop = event(
+            "0x02_0",
+            Event::Minter(MinterEvents::Airdrop),
+            MINTER,
+            &["0x0def", "0x1", "0x6553f200"],
+        );
+
+        consume_all(&store, &MinterBuyEventConsumer::new(), &buys)
+            .await
+            .unwrap();
+        consume_all(&store, &MinterBuyEventConsumer::new(), &buys)
+            .await
+            .unwrap();
+        consume_all(&store, &MinterAirdropEventConsumer::new(), &[airdrop])
+            .await
+            .unwrap();
+
+        let projections = store.projections();
+        let minter_id = Some(projections.minters[0].id);
+        assert_eq!(2, projections.buys.len());
+        assert_eq!("0x01", projections.buys[0].hash);
+        assert_eq!("0x01_1", projections.buys[1].event_id);
+        assert_eq!("0x0abc", projections.buys[0].address);
+        assert_eq!(Some(U256::from(2_000_000u64)), projections.buys[0].amount);
+        assert_eq!(U256::from(2u64), projections.buys[0].quantity);
+        assert_eq!(minter_id, projections.buys[0].minter_id);
+        assert_eq!(1, projections.airdrops.len());
+        assert_eq!(U256::from(1u64), projections.airdrops[0].quantity);
+        assert_eq!(minter_id, projections.airdrops[0].minter_id);
+    }
+
+    #[tokio::test]
+    async fn test_sale_status_follows_last_event() {
+        let store = InMemoryProjectionStore::new().with_minter(MINTER);
+        let events = [
+            (MinterEvents::PreSaleOpen, "0x01_0"),
+            (MinterEvents::PreSaleClosed, "0x02_0"),
+            (MinterEvents::PublicSaleOpen, "0x03_0"),
+            (MinterEvents::SoldOut, "0x04_0"),
+        ]
+        .map(|(r#type, id)| event(id, Event::Minter(r#type), MINTER, &["0x6553f100"]));
+
+        consume_all(&store, &MinterSaleStatusEventConsumer::new(), &events)
+            .await
+            .unwrap();
+
+        let minter = &store.projections().minters[0];
+        assert!(!minter.pre_sale_open);
+        assert!(minter.public_sale_open);
+        assert!(minter.sold_out);
+    }
+
+    #[tokio::test]
+    async fn test_sale_status_toggles_on_open_and_close_events() {
+        let store = InMemoryProjectionStore::new().with_minter(MINTER);
+        // pre sale open, public sale open, sold out once event is consumed
+        let steps = [
+            (MinterEvents::PreSaleOpen, (true, false, false)),
+            (MinterEvents::PreSaleClosed, (false, false, false)),
+            (MinterEvents::PublicSaleOpen, (false, true, false)),
+            (MinterEvents::PublicSaleClosed, (false, false, false)),
+            (MinterEvents::PreSaleOpen, (true, false, false)),
+            (MinterEvents::PublicSaleOpen, (true, true, false)),
+            (MinterEvents::SoldOut, (true, true, true)),
+        ];
+
+        for (i, (r#type, expected)) in steps.into_iter().enumerate() {
+            let id = format!("0x{:02x}_0", i + 1);
+            consume_all(
+                &store,
+                &MinterSaleStatusEventConsumer::new(),
+                &[event(&id, Event::Minter(r#type), MINTER, &["0x6553f100"])],
+            )
+            .await
+            .unwrap();
+
+            let minter = &store.projections().minters[0];
+            assert_eq!(
+                expected,
+                (
+                    minter.pre_sale_open,
+                    minter.public_sale_open,
+                    minter.sold_out
+                )
+            );
+        }
     }
 }
@@ -1,23 +1,33 @@
 pub mod event_bus;
+#[cfg(test)]
+pub(crate) mod fixtures;
 pub mod minter;
 pub mod offseter;
+pub mod payload;
 pub mod project;
+pub mod projection;
+pub mod repository;
+pub mod retry;
 pub mod transaction;
+pub mod upcaster;
 pub mod yielder;
 
 use std::{
     collections::{hash_map::Entry, HashMap},
-    fmt::Debug,
+    fmt::{Debug, Display},
 };
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::OffsetDateTime;
 
-use crate::infrastructure::{
-    postgres::PostgresError,
-    starknet::{model::ModelError, SequencerError},
-    view_model::DomainEventViewModel,
+use crate::{
+    domain::registry::{ContractType, RegisteredContract},
+    infrastructure::{
+        postgres::{is_transient_tokio_postgres_error, PostgresError},
+        starknet::{model::ModelError, SequencerError},
+        view_model::DomainEventViewModel,
+    },
 };
 
 use self::{
@@ -28,10 +38,55 @@ use self::{
 pub struct DomainEvent {
     pub(crate) id: String,
     pub(crate) metadata: HashMap<String, String>,
+    /// Storage format of event data, decode it with [`payload`] typed structs
     pub(crate) payload: HashMap<String, String>,
+    /// Every event key, selector first
+    #[serde(default)]
+    pub(crate) keys: Vec<String>,
     pub(crate) r#type: Event,
+    /// Payload layout version, older events are upcasted on replay, see [`UpcasterChain`]
+    ///
+    /// [`UpcasterChain`]: upcaster::UpcasterChain
+    #[serde(default = "first_schema_version")]
+    pub(crate) schema_version: u32,
+}
+
+fn first_schema_version() -> u32 {
+    1
 }
 impl DomainEvent {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Every event key, selector first. Cairo 1 indexed members follow it,
+    /// after the component selector when emitted through a nested component.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Address of the contract which emitted the event
+    pub fn from_address(&self) -> Result<&str, DomainError> {
+        self.metadata
+            .get("from_address")
+            .map(|a| a.as_str())
+            .ok_or_else(|| {
+                DomainError::InvalidEventPayload(self.id.to_owned(), "missing from_address".into())
+            })
+    }
+
+    /// Hash of the transaction which emitted the event, event id when unknown
+    pub fn tx_hash(&self) -> &str {
+        self.metadata
+            .get("tx_hash")
+            .map(|h| h.as_str())
+            .unwrap_or(&self.id)
+    }
+
     pub fn with_metadata(mut self, metadata: &BlockMetadata) -> Self {
         self.metadata
             .insert("block_hash".to_owned(), metadata.hash.to_string());
@@ -41,6 +96,15 @@ impl DomainEvent {
             .insert("timestamp".to_owned(), metadata.timestamp.to_string());
         self
     }
+
+    /// Store ABI decoded named fields next to positional ones, see [`AbiRegistry`]
+    /// * fields - [`HashMap<String, String>`] - Flattened named fields
+    ///
+    /// [`AbiRegistry`]: crate::infrastructure::starknet::abi::AbiRegistry
+    pub fn with_decoded_payload(mut self, fields: HashMap<String, String>) -> Self {
+        self.payload.extend(fields);
+        self
+    }
 }
 
 impl From<&DomainEventViewModel> for DomainEvent {
@@ -49,7 +113,9 @@ impl From<&DomainEventViewModel> for DomainEvent {
             id: value.event_id.to_owned(),
             metadata: value.metadata.to_owned(),
             payload: value.payload.to_owned(),
+            keys: value.keys.to_owned(),
             r#type: value.r#type.to_owned(),
+            schema_version: value.schema_version,
         }
     }
 }
@@ -85,11 +151,39 @@ pub enum Event {
     Minter(MinterEvents),
     Offseter(OffseterEvents),
     Yielder(YielderEvents),
+    /// Event emitted by an indexed contract but not matched by any filter,
+    /// stored so that it can be consumed once supported
+    Unknown {
+        contract: String,
+        selector: String,
+    },
+}
+
+impl Event {
+    /// Every `Upgraded` event, contracts emit one each time they switch implementation
+    pub fn upgraded() -> [Event; 4] {
+        [
+            Event::Project(ProjectEvents::Upgraded),
+            Event::Minter(MinterEvents::Upgraded),
+            Event::Offseter(OffseterEvents::Upgraded),
+            Event::Yielder(YielderEvents::Upgraded),
+        ]
+    }
+
+    pub fn is_upgraded(&self) -> bool {
+        matches!(
+            self,
+            Event::Project(ProjectEvents::Upgraded)
+                | Event::Minter(MinterEvents::Upgraded)
+                | Event::Offseter(OffseterEvents::Upgraded)
+                | Event::Yielder(YielderEvents::Upgraded)
+        )
+    }
 }
 
-impl From<Event> for &str {
-    fn from(value: Event) -> Self {
-        match value {
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
             Event::Project(v) => match v {
                 ProjectEvents::Upgraded => "project.upgraded",
                 ProjectEvents::AbsorptionUpdate => "project.absorption_update",
@@ -128,12 +222,28 @@ impl From<Event> for &str {
                 YielderEvents::Snapshot => "yielder.snapshot",
                 YielderEvents::Provision => "yielder.provision",
             },
-        }
+            Event::Unknown { contract, selector } => {
+                return write!(f, "unknown.{contract}.{selector}");
+            }
+        };
+        write!(f, "{name}")
     }
 }
-impl From<&str> for Event {
-    fn from(value: &str) -> Self {
-        match value {
+
+impl TryFrom<&str> for Event {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if let Some((contract, selector)) = value
+            .strip_prefix("unknown.")
+            .and_then(|unknown| unknown.split_once('.'))
+        {
+            return Ok(Event::Unknown {
+                contract: contract.to_owned(),
+                selector: selector.to_owned(),
+            });
+        }
+        Ok(match value {
             "project.upgraded" => Event::Project(ProjectEvents::Upgraded),
             "project.absorption_update" => Event::Project(ProjectEvents::AbsorptionUpdate),
             "project.transfer" => Event::Project(ProjectEvents::Transfer),
@@ -164,8 +274,8 @@ impl From<&str> for Event {
             "yielder.withdraw" => Event::Yielder(YielderEvents::Withdraw),
             "yielder.snapshot" => Event::Yielder(YielderEvents::Snapshot),
             "yielder.provision" => Event::Yielder(YielderEvents::Provision),
-            &_ => panic!("Unknown event {value}"),
-        }
+            &_ => return Err(DomainError::UnknownEvent(value.to_owned())),
+        })
     }
 }
 
@@ -181,6 +291,8 @@ pub enum DomainError {
     NotAvailable,
     #[error("contract with address {0} not found inside db")]
     ContractNotFound(String),
+    #[error("token {1} of contract {0} not found inside db")]
+    TokenNotFound(String, String),
     #[error(transparent)]
     SequencerError(#[from] SequencerError),
     #[error(transparent)]
@@ -189,6 +301,33 @@ pub enum DomainError {
     FailedToPersistEvent,
     #[error("failed to rollback")]
     FailedToRollback,
+    #[error("unknown projection {0}")]
+    UnknownProjection(String),
+    #[error("unknown projection status {0}")]
+    UnknownProjectionStatus(String),
+    #[error("checkpoint of projection {0} moved while consuming event")]
+    ProjectionCheckpointMoved(String),
+    #[error("invalid payload of event {0}: {1}")]
+    InvalidEventPayload(String, String),
+    #[error("unknown event type {0}")]
+    UnknownEvent(String),
+    #[error("event {0} schema version {1} cannot be upcasted")]
+    UnsupportedSchemaVersion(String, u32),
+}
+
+impl DomainError {
+    /// Whether consuming the event again later may succeed,
+    /// e.g. rate limited node or lost database connection
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DomainError::PoolError(_) => true,
+            DomainError::TokioError(e) => is_transient_tokio_postgres_error(e),
+            DomainError::PostgresError(e) => e.is_transient(),
+            DomainError::SequencerError(e) => e.is_transient(),
+            DomainError::ModelError(e) => e.is_transient(),
+            _ => false,
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -201,67 +340,164 @@ pub trait StorageClientPool {
 }
 
 /// Implement this trait to enable specific filtering.
+/// Events are matched on a key path: their selector, or the component selector followed by
+/// their selector when emitted through a nested Cairo 1 component.
 pub trait Filterable: Debug {
-    /// Maps a single `contract_address` to `selector_hash`
-    fn to_filters(&self) -> Vec<(String, String)>;
-
-    /// Tries to find event in current filter.
-    fn get_event(&mut self, contract_address: &str, event_key: &str) -> Option<Event>;
-
-    /// Build filter item from configuration filter
-    fn hydrate_from_file(&mut self, address_list: Vec<HashMap<String, String>>);
-
-    /// Extract from file data
-    fn extract_addresses(
-        &self,
-        contract_addresses: Vec<HashMap<String, String>>,
-        keys: &[&str],
-    ) -> Vec<String> {
-        let mut addresses = Vec::new();
-        for list in contract_addresses {
-            for (k, addr) in list.iter() {
-                if keys.contains(&k.as_str()) {
-                    let parts = addr.split("//").collect::<Vec<&str>>();
-                    let addr = parts[0];
-                    addresses.push(addr.to_string());
-                }
-            }
-        }
-        addresses
-    }
+    /// Maps a single `contract_address` to event key path
+    fn to_filters(&self) -> Vec<(String, Vec<String>)>;
+
+    /// Tries to find event in current filter from every key of the event.
+    /// Returns the event with the length of its matched key path, keys after it are event members.
+    fn get_event(&mut self, contract_address: &str, keys: &[String]) -> Option<(Event, usize)>;
+
+    /// Route events emitted through nested components of a contract.
+    /// Every `[component_selector, event_selector]` path whose event is already filtered for
+    /// the contract is filtered as well.
+    /// * `contract_address` - [`&str`]
+    /// * `paths` - [`&[Vec<String>]`] - Nested key paths found in contract ABI
+    ///
+    fn add_nested_paths(&mut self, contract_address: &str, paths: &[Vec<String>]);
+
+    /// Build filter items from active contract registry entries, replacing previous ones
+    /// * `contracts` - [`&[RegisteredContract]`]
+    ///
+    fn hydrate(&mut self, contracts: &[RegisteredContract]);
+}
+
+/// Common function for [`Filterable::hydrate`] trait implementation,
+/// addresses of active registry contracts of given types
+/// * contracts: &[RegisteredContract]
+/// * contract_types: &[ContractType]
+///
+pub(crate) fn active_addresses(
+    contracts: &[RegisteredContract],
+    contract_types: &[ContractType],
+) -> Vec<String> {
+    contracts
+        .iter()
+        .filter(|c| c.active && contract_types.contains(&c.contract_type))
+        .map(|c| c.address.to_owned())
+        .collect()
 }
 
 /// Common function for [`Filterable::to_filters`] trait implementation
-/// * filters: &HashMap<contract_address, Vec<(selector_hash, Event)>>
+/// * filters: &HashMap<contract_address, Vec<(key_path, Event)>>
 ///
-pub(crate) fn to_filters(filters: &HashMap<String, Vec<(String, Event)>>) -> Vec<(String, String)> {
+pub(crate) fn to_filters(
+    filters: &HashMap<String, Vec<(Vec<String>, Event)>>,
+) -> Vec<(String, Vec<String>)> {
     filters
         .iter()
-        .flat_map(|(k, v)| {
-            v.iter()
-                .map(|(selector_hash, _)| (k.to_owned(), selector_hash.to_owned()))
-        })
+        .flat_map(|(k, v)| v.iter().map(|(path, _)| (k.to_owned(), path.to_owned())))
         .collect()
 }
 
-/// Common function for [`Filterable::get_event`] trait implementation
-/// * filters: &mut HashMap<contract_address, Vec<(selector_hash, Event)>>
+/// Common function for [`Filterable::get_event`] trait implementation,
+/// longest key path matching event keys wins and is returned with its length
+/// * filters: &mut HashMap<contract_address, Vec<(key_path, Event)>>
 /// * contract_address: &str
-/// * event_key: &str
+/// * keys: &[String]
 ///
 pub(crate) fn get_event(
-    filters: &mut HashMap<String, Vec<(String, Event)>>,
+    filters: &mut HashMap<String, Vec<(Vec<String>, Event)>>,
     contract_address: &str,
-    event_key: &str,
-) -> Option<Event> {
+    keys: &[String],
+) -> Option<(Event, usize)> {
     let parts = contract_address.split("//").collect::<Vec<&str>>();
     let addr = parts[0];
     match filters.entry(addr.to_string()) {
         Entry::Occupied(e) => e
             .get()
             .iter()
-            .find(|(k, _)| &event_key.to_string() == k)
-            .map(|(_, ev)| ev.clone()),
+            .filter(|(path, _)| keys.starts_with(path))
+            .max_by_key(|(path, _)| path.len())
+            .map(|(path, ev)| (ev.clone(), path.len())),
         Entry::Vacant(_) => None,
     }
 }
+
+/// Common function for [`Filterable::add_nested_paths`] trait implementation
+/// * filters: &mut HashMap<contract_address, Vec<(key_path, Event)>>
+/// * contract_address: &str
+/// * paths: &[Vec<String>]
+///
+pub(crate) fn add_nested_paths(
+    filters: &mut HashMap<String, Vec<(Vec<String>, Event)>>,
+    contract_address: &str,
+    paths: &[Vec<String>],
+) {
+    let parts = contract_address.split("//").collect::<Vec<&str>>();
+    let entries = match filters.get_mut(parts[0]) {
+        Some(entries) => entries,
+        None => return,
+    };
+    for path in paths {
+        let event = match path.last().and_then(|selector| {
+            entries
+                .iter()
+                .find(|(p, _)| p.len() == 1 && &p[0] == selector)
+                .map(|(_, ev)| ev.clone())
+        }) {
+            Some(event) => event,
+            None => continue,
+        };
+        if !entries.iter().any(|(p, _)| p == path) {
+            entries.push((path.to_owned(), event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{add_nested_paths, get_event, project::ProjectEvents, DomainError, Event};
+
+    #[test]
+    fn test_event_type_round_trip() {
+        let transfer = Event::try_from("project.transfer").unwrap();
+        assert!(matches!(transfer, Event::Project(ProjectEvents::Transfer)));
+        assert_eq!("project.transfer", transfer.to_string());
+
+        let unknown = Event::try_from("unknown.0x0123.0x099cd8").unwrap();
+        assert!(matches!(
+            &unknown,
+            Event::Unknown { contract, selector } if contract == "0x0123" && selector == "0x099cd8"
+        ));
+        assert_eq!("unknown.0x0123.0x099cd8", unknown.to_string());
+
+        assert!(matches!(
+            Event::try_from("project.not_yet_supported"),
+            Err(DomainError::UnknownEvent(_))
+        ));
+    }
+
+    #[test]
+    fn test_nested_component_events_are_routed() {
+        let mut filters = HashMap::from([(
+            "0x01".to_owned(),
+            vec![(
+                vec!["0xtransfer".to_owned()],
+                Event::Project(ProjectEvents::Transfer),
+            )],
+        )]);
+        let nested = vec!["0xcomponent".to_owned(), "0xtransfer".to_owned()];
+        add_nested_paths(
+            &mut filters,
+            "0x01",
+            &[
+                nested.clone(),
+                vec!["0xcomponent".to_owned(), "0xunknown".to_owned()],
+            ],
+        );
+
+        assert_eq!(2, filters["0x01"].len());
+        let keys = [nested, vec!["0x07".to_owned()]].concat();
+        assert!(matches!(
+            get_event(&mut filters, "0x01", &keys),
+            Some((Event::Project(ProjectEvents::Transfer), 2))
+        ));
+        assert!(get_event(&mut filters, "0x01", &["0xcomponent".to_owned()]).is_none());
+        assert!(get_event(&mut filters, "0x02", &["0xtransfer".to_owned()]).is_none());
+    }
+}
@@ -1,21 +1,17 @@
 use std::collections::HashMap;
 
 use apibara_core::starknet::v1alpha2::FieldElement;
-use deadpool_postgres::Transaction;
 use serde::{Deserialize, Serialize};
 use starknet::macros::selector;
 
-use crate::{
-    domain::crypto::U256,
-    infrastructure::postgres::{
-        entity::{ActionType, FarmType},
-        event_source::{append_customer_action, find_related_project_address_and_slot},
-    },
-};
+use crate::infrastructure::postgres::entity::{ActionType, FarmType};
+
+use crate::domain::registry::{ContractType, RegisteredContract};
 
 use super::{
-    event_bus::Consumer, get_event, to_filters, BlockMetadata, DomainError, DomainEvent, Event,
-    Filterable,
+    active_addresses, add_nested_paths, event_bus::Consumer, get_event, payload::FarmAction,
+    repository::CustomerActionRepository, to_filters, transaction::TransactionManager,
+    BlockMetadata, DomainError, DomainEvent, Event, Filterable,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,7 +26,7 @@ pub enum OffseterEvents {
 #[derive(Debug)]
 pub struct OffsetFilters {
     contracts: Vec<String>,
-    filters: HashMap<String, Vec<(String, Event)>>,
+    filters: HashMap<String, Vec<(Vec<String>, Event)>>,
 }
 
 impl OffsetFilters {
@@ -49,35 +45,54 @@ impl Default for OffsetFilters {
 
 /// Add [`Filterable`] behaviour on [`Yielder`]
 impl Filterable for OffsetFilters {
-    fn to_filters(&self) -> Vec<(String, String)> {
+    fn to_filters(&self) -> Vec<(String, Vec<String>)> {
         to_filters(&self.filters)
     }
 
-    fn get_event(&mut self, contract_address: &str, event_key: &str) -> Option<Event> {
-        get_event(&mut self.filters, contract_address, event_key)
+    fn get_event(&mut self, contract_address: &str, keys: &[String]) -> Option<(Event, usize)> {
+        get_event(&mut self.filters, contract_address, keys)
+    }
+
+    fn add_nested_paths(&mut self, contract_address: &str, paths: &[Vec<String>]) {
+        add_nested_paths(&mut self.filters, contract_address, paths)
     }
 
-    fn hydrate_from_file(&mut self, address_list: Vec<HashMap<String, String>>) {
-        let contracts = self.extract_addresses(address_list, &["offseter", "offseter_3525"]);
-        self.contracts = contracts;
+    fn hydrate(&mut self, contracts: &[RegisteredContract]) {
+        self.contracts = active_addresses(
+            contracts,
+            &[ContractType::Offseter, ContractType::Offseter3525],
+        );
+        // filters are rebuilt so that deactivated contracts are dropped
+        self.filters.clear();
         for contract in self.contracts.iter() {
             self.filters.insert(
                 contract.to_string(),
                 [
                     (
-                        FieldElement::from_bytes(&selector!("Upgraded").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Upgraded").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Offseter(OffseterEvents::Upgraded),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Deposit").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Deposit").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Offseter(OffseterEvents::Deposit),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Withdraw").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Withdraw").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Offseter(OffseterEvents::Withdraw),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Claim").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Claim").to_bytes_be()).to_string()
+                        ],
                         Event::Offseter(OffseterEvents::Claim),
                     ),
                 ]
@@ -97,7 +112,10 @@ impl OffseterUpgradedEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for OffseterUpgradedEventConsumer {
+impl<Txn> Consumer<Txn> for OffseterUpgradedEventConsumer
+where
+    Txn: TransactionManager,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Offseter(OffseterEvents::Upgraded))
     }
@@ -106,7 +124,7 @@ impl Consumer<Transaction<'_>> for OffseterUpgradedEventConsumer {
         &self,
         _event: &DomainEvent,
         _metadata: &BlockMetadata,
-        _txn: &mut Transaction,
+        _txn: &mut Txn,
     ) -> Result<(), DomainError> {
         // event not handled at the moment but it will be stored in database later on.
         Ok(())
@@ -123,7 +141,10 @@ impl OffseterDepositEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for OffseterDepositEventConsumer {
+impl<Txn> Consumer<Txn> for OffseterDepositEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Offseter(OffseterEvents::Deposit))
     }
@@ -132,30 +153,21 @@ impl Consumer<Transaction<'_>> for OffseterDepositEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Offset).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Offset)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Offset,
             ActionType::Deposit,
         )
@@ -175,7 +187,10 @@ impl OffseterWithdrawEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for OffseterWithdrawEventConsumer {
+impl<Txn> Consumer<Txn> for OffseterWithdrawEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Offseter(OffseterEvents::Withdraw))
     }
@@ -184,30 +199,21 @@ impl Consumer<Transaction<'_>> for OffseterWithdrawEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Offset).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Offset)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Offset,
             ActionType::Withdraw,
         )
@@ -227,7 +233,10 @@ impl OffseterClaimEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for OffseterClaimEventConsumer {
+impl<Txn> Consumer<Txn> for OffseterClaimEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Offseter(OffseterEvents::Claim))
     }
@@ -236,30 +245,21 @@ impl Consumer<Transaction<'_>> for OffseterClaimEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Offset).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Offset)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Offset,
             ActionType::Claim,
         )
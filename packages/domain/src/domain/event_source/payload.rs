@@ -0,0 +1,560 @@
+use apibara_core::starknet::v1alpha2::FieldElement;
+use time::OffsetDateTime;
+
+use crate::domain::crypto::U256;
+
+use super::{
+    minter::MinterEvents, offseter::OffseterEvents, project::ProjectEvents, yielder::YielderEvents,
+    DomainError, DomainEvent, Event,
+};
+
+/// Reads [`DomainEvent`] payload, stored as a map of event data position to felt hex string.
+/// Cairo `Uint256` spans two felts (low, high) of 128 bits each.
+struct PayloadReader<'a> {
+    event: &'a DomainEvent,
+}
+
+impl<'a> PayloadReader<'a> {
+    /// Ensures event is of expected type before reading its payload
+    /// * `event` - [`&DomainEvent`]
+    /// * `expected` - [`fn(&Event) -> bool`]
+    ///
+    fn new(event: &'a DomainEvent, expected: fn(&Event) -> bool) -> Result<Self, DomainError> {
+        if !expected(&event.r#type) {
+            return Err(DomainError::InvalidEventPayload(
+                event.id.to_owned(),
+                format!("unexpected event type {:?}", event.r#type),
+            ));
+        }
+        Ok(Self { event })
+    }
+
+    fn invalid(&self, field: &str, reason: &str) -> DomainError {
+        DomainError::InvalidEventPayload(self.event.id.to_owned(), format!("{field} {reason}"))
+    }
+
+    fn raw(&self, position: usize, field: &str) -> Result<&'a str, DomainError> {
+        self.event
+            .payload
+            .get(&position.to_string())
+            .map(|v| v.as_str())
+            .ok_or_else(|| self.invalid(field, &format!("missing at position {position}")))
+    }
+
+    fn felt(&self, position: usize, field: &str) -> Result<FieldElement, DomainError> {
+        let raw = self.raw(position, field)?;
+        FieldElement::from_hex(raw)
+            .map_err(|_| self.invalid(field, &format!("is not a felt {raw}")))
+    }
+
+    /// Address is kept as emitted as it is stored that way in view models
+    fn address(&self, position: usize, field: &str) -> Result<String, DomainError> {
+        self.felt(position, field)?;
+        Ok(self.raw(position, field)?.to_owned())
+    }
+
+    /// Felt value, e.g. a quantity or a timestamp
+    fn felt_value(&self, position: usize, field: &str) -> Result<U256, DomainError> {
+        Ok(U256::from(self.felt(position, field)?))
+    }
+
+    /// `Uint256` value, low limb at `position` and high limb right after it
+    fn u256(&self, position: usize, field: &str) -> Result<U256, DomainError> {
+        let limb_max = crypto_bigint::U256::from_u128(u128::MAX);
+        let low = crypto_bigint::U256::from(self.felt_value(position, field)?);
+        let high = crypto_bigint::U256::from(self.felt_value(position + 1, field)?);
+        if low > limb_max || high > limb_max {
+            return Err(self.invalid(field, "is not a Uint256"));
+        }
+        Ok(U256::from(low.wrapping_add(&high.shl_vartime(128))))
+    }
+
+    fn bool(&self, position: usize, field: &str) -> Result<bool, DomainError> {
+        Ok(FieldElement::from_u64(0) != self.felt(position, field)?)
+    }
+
+    fn time(&self, position: usize, field: &str) -> Result<OffsetDateTime, DomainError> {
+        let timestamp = self.felt_value(position, field)?;
+        if timestamp > U256::from(i64::MAX as u64) {
+            return Err(self.invalid(field, "is not a timestamp"));
+        }
+        OffsetDateTime::from_unix_timestamp(u64::from(timestamp) as i64)
+            .map_err(|_| self.invalid(field, "is not a timestamp"))
+    }
+}
+
+/// `Transfer(from: felt, to: felt, tokenId: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: String,
+    pub to: String,
+    pub token_id: U256,
+}
+
+impl Transfer {
+    /// Token is minted when transferred from address 0
+    pub fn is_mint(&self) -> bool {
+        FieldElement::from_hex(&self.from)
+            .map(|from| FieldElement::from_u64(0) == from)
+            .unwrap_or(false)
+    }
+}
+
+impl TryFrom<&DomainEvent> for Transfer {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::Transfer))
+        })?;
+        Ok(Self {
+            from: reader.address(0, "from")?,
+            to: reader.address(1, "to")?,
+            token_id: reader.u256(2, "token_id")?,
+        })
+    }
+}
+
+/// `TransferValue(fromTokenId: Uint256, toTokenId: Uint256, value: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferValue {
+    pub from_token_id: U256,
+    pub to_token_id: U256,
+    pub value: U256,
+}
+
+impl TryFrom<&DomainEvent> for TransferValue {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::TransferValue))
+        })?;
+        Ok(Self {
+            from_token_id: reader.u256(0, "from_token_id")?,
+            to_token_id: reader.u256(2, "to_token_id")?,
+            value: reader.u256(4, "value")?,
+        })
+    }
+}
+
+/// `SlotChanged(tokenId: Uint256, oldSlot: Uint256, newSlot: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotChanged {
+    pub token_id: U256,
+    pub old_slot: U256,
+    pub new_slot: U256,
+}
+
+impl TryFrom<&DomainEvent> for SlotChanged {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::SlotChanged))
+        })?;
+        Ok(Self {
+            token_id: reader.u256(0, "token_id")?,
+            old_slot: reader.u256(2, "old_slot")?,
+            new_slot: reader.u256(4, "new_slot")?,
+        })
+    }
+}
+
+/// `ProjectValueUpdate(slot: Uint256, projectValue: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectValueUpdate {
+    pub slot: U256,
+    pub project_value: U256,
+}
+
+impl TryFrom<&DomainEvent> for ProjectValueUpdate {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::ProjectValueUpdate))
+        })?;
+        Ok(Self {
+            slot: reader.u256(0, "slot")?,
+            project_value: reader.u256(2, "project_value")?,
+        })
+    }
+}
+
+/// `MetadataUpdate(tokenId: Uint256)` and `BatchMetadataUpdate(fromTokenId: Uint256, toTokenId: Uint256)`
+/// emitted from project, a single token update is a range of one token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataUpdate {
+    pub from_token_id: U256,
+    pub to_token_id: U256,
+}
+
+impl TryFrom<&DomainEvent> for MetadataUpdate {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(
+                e,
+                Event::Project(ProjectEvents::MetadataUpdate | ProjectEvents::BatchMetadataUpdate)
+            )
+        })?;
+        let from_token_id = reader.u256(0, "from_token_id")?;
+        if matches!(event.r#type, Event::Project(ProjectEvents::MetadataUpdate)) {
+            return Ok(Self {
+                from_token_id,
+                to_token_id: from_token_id,
+            });
+        }
+        Ok(Self {
+            from_token_id,
+            to_token_id: reader.u256(2, "to_token_id")?,
+        })
+    }
+}
+
+/// `Approval(owner: felt, approved: felt, tokenId: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Approval {
+    pub owner: String,
+    pub approved: String,
+    pub token_id: U256,
+}
+
+impl Approval {
+    /// Token approval is cleared when approved address is 0, e.g. on transfer
+    pub fn is_cleared(&self) -> bool {
+        FieldElement::from_hex(&self.approved)
+            .map(|approved| FieldElement::from_u64(0) == approved)
+            .unwrap_or(false)
+    }
+}
+
+impl TryFrom<&DomainEvent> for Approval {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::Approval))
+        })?;
+        Ok(Self {
+            owner: reader.address(0, "owner")?,
+            approved: reader.address(1, "approved")?,
+            token_id: reader.u256(2, "token_id")?,
+        })
+    }
+}
+
+/// `ApprovalValue(tokenId: Uint256, operator: felt, value: Uint256)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalValue {
+    pub token_id: U256,
+    pub operator: String,
+    pub value: U256,
+}
+
+impl TryFrom<&DomainEvent> for ApprovalValue {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::ApprovalValue))
+        })?;
+        Ok(Self {
+            token_id: reader.u256(0, "token_id")?,
+            operator: reader.address(2, "operator")?,
+            value: reader.u256(3, "value")?,
+        })
+    }
+}
+
+/// `ApprovalForAll(owner: felt, operator: felt, approved: felt)` emitted from project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalForAll {
+    pub owner: String,
+    pub operator: String,
+    pub approved: bool,
+}
+
+impl TryFrom<&DomainEvent> for ApprovalForAll {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Project(ProjectEvents::ApprovalForAll))
+        })?;
+        Ok(Self {
+            owner: reader.address(0, "owner")?,
+            operator: reader.address(1, "operator")?,
+            approved: reader.bool(2, "approved")?,
+        })
+    }
+}
+
+/// `Migration(address: felt, tokenId: Uint256, newTokenId: Uint256, slot: Uint256, value: Uint256)`
+/// emitted from minter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub customer_address: String,
+    pub token_id: U256,
+    pub new_token_id: U256,
+    pub slot: U256,
+    pub value: U256,
+}
+
+impl TryFrom<&DomainEvent> for Migration {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Minter(MinterEvents::Migration))
+        })?;
+        Ok(Self {
+            customer_address: reader.address(0, "address")?,
+            token_id: reader.u256(1, "token_id")?,
+            new_token_id: reader.u256(3, "new_token_id")?,
+            slot: reader.u256(5, "slot")?,
+            value: reader.u256(7, "value")?,
+        })
+    }
+}
+
+/// `Buy(address: felt, amount: Uint256, quantity: felt, time: felt)` emitted from minter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buy {
+    pub customer_address: String,
+    pub amount: U256,
+    pub quantity: U256,
+    pub time: OffsetDateTime,
+}
+
+impl TryFrom<&DomainEvent> for Buy {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| matches!(e, Event::Minter(MinterEvents::Buy)))?;
+        Ok(Self {
+            customer_address: reader.address(0, "address")?,
+            amount: reader.u256(1, "amount")?,
+            quantity: reader.felt_value(3, "quantity")?,
+            time: reader.time(4, "time")?,
+        })
+    }
+}
+
+/// `Airdrop(address: felt, quantity: felt, time: felt)` emitted from minter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Airdrop {
+    pub customer_address: String,
+    pub quantity: U256,
+    pub time: OffsetDateTime,
+}
+
+impl TryFrom<&DomainEvent> for Airdrop {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader =
+            PayloadReader::new(event, |e| matches!(e, Event::Minter(MinterEvents::Airdrop)))?;
+        Ok(Self {
+            customer_address: reader.address(0, "address")?,
+            quantity: reader.felt_value(1, "quantity")?,
+            time: reader.time(2, "time")?,
+        })
+    }
+}
+
+/// `Deposit`, `Withdraw` and `Claim` `(address: felt, value: Uint256)` emitted from offseter
+/// and yielder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FarmAction {
+    pub customer_address: String,
+    pub value: U256,
+}
+
+impl TryFrom<&DomainEvent> for FarmAction {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(
+                e,
+                Event::Offseter(
+                    OffseterEvents::Deposit | OffseterEvents::Withdraw | OffseterEvents::Claim
+                ) | Event::Yielder(
+                    YielderEvents::Deposit | YielderEvents::Withdraw | YielderEvents::Claim
+                )
+            )
+        })?;
+        Ok(Self {
+            customer_address: reader.address(0, "address")?,
+            value: reader.u256(1, "value")?,
+        })
+    }
+}
+
+/// `Provision(project: felt, amount: felt, time: felt)` emitted from yielder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provision {
+    pub amount: U256,
+    pub time: OffsetDateTime,
+}
+
+impl TryFrom<&DomainEvent> for Provision {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Yielder(YielderEvents::Provision))
+        })?;
+        Ok(Self {
+            amount: reader.felt_value(1, "amount")?,
+            time: reader.time(2, "time")?,
+        })
+    }
+}
+
+/// `Snapshot(project: felt, previousTime: felt, previousProjectAbsorption: felt, ...)`
+/// emitted from yielder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub previous_time: OffsetDateTime,
+    pub previous_project_absorption: U256,
+    pub previous_offseter_absorption: U256,
+    pub previous_yielder_absorption: U256,
+    pub current_time: OffsetDateTime,
+    pub current_project_absorption: U256,
+    pub current_offseter_absorption: U256,
+    pub current_yielder_absorption: U256,
+    pub project_absorption: U256,
+    pub offseter_absorption: U256,
+    pub yielder_absorption: U256,
+}
+
+impl TryFrom<&DomainEvent> for Snapshot {
+    type Error = DomainError;
+
+    fn try_from(event: &DomainEvent) -> Result<Self, Self::Error> {
+        let reader = PayloadReader::new(event, |e| {
+            matches!(e, Event::Yielder(YielderEvents::Snapshot))
+        })?;
+        Ok(Self {
+            previous_time: reader.time(1, "previous_time")?,
+            previous_project_absorption: reader.felt_value(2, "previous_project_absorption")?,
+            previous_offseter_absorption: reader.felt_value(3, "previous_offseter_absorption")?,
+            previous_yielder_absorption: reader.felt_value(4, "previous_yielder_absorption")?,
+            current_time: reader.time(5, "current_time")?,
+            current_project_absorption: reader.felt_value(6, "current_project_absorption")?,
+            current_offseter_absorption: reader.felt_value(7, "current_offseter_absorption")?,
+            current_yielder_absorption: reader.felt_value(8, "current_yielder_absorption")?,
+            project_absorption: reader.felt_value(9, "project_absorption")?,
+            offseter_absorption: reader.felt_value(10, "offseter_absorption")?,
+            yielder_absorption: reader.felt_value(11, "yielder_absorption")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        crypto::U256,
+        event_source::{fixtures::event, project::ProjectEvents, DomainError, Event},
+    };
+
+    use super::{Transfer, TransferValue};
+
+    const PROJECT: &str = "0x0456";
+
+    #[test]
+    fn test_decode_transfer_value() {
+        let event = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::TransferValue),
+            PROJECT,
+            &["0x1", "0x0", "0x2", "0x0", "0x64", "0x0"],
+        );
+
+        assert_eq!(
+            TransferValue {
+                from_token_id: U256::from(1u64),
+                to_token_id: U256::from(2u64),
+                value: U256::from(100u64),
+            },
+            TransferValue::try_from(&event).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_mint() {
+        let event = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            PROJECT,
+            &["0x0", "0x123", "0x7", "0x0"],
+        );
+        let transfer = Transfer::try_from(&event).unwrap();
+
+        assert!(transfer.is_mint());
+        assert_eq!(U256::from(7u64), transfer.token_id);
+    }
+
+    #[test]
+    fn test_malformed_payload_is_an_error() {
+        let missing = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            PROJECT,
+            &["0x0", "0x123"],
+        );
+        let not_a_felt = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            PROJECT,
+            &["0x0", "0x123", "token", "0x0"],
+        );
+        let wrong_type = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::SlotChanged),
+            PROJECT,
+            &["0x0", "0x123", "0x7", "0x0"],
+        );
+
+        for event in [missing, not_a_felt, wrong_type] {
+            assert!(matches!(
+                Transfer::try_from(&event),
+                Err(DomainError::InvalidEventPayload(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decode_u256_high_limb() {
+        let event = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            PROJECT,
+            &["0x0", "0x123", "0x7", "0x1"],
+        );
+        let transfer = Transfer::try_from(&event).unwrap();
+
+        let expected = crypto_bigint::U256::from_u64(7)
+            .wrapping_add(&crypto_bigint::U256::from_u64(1).shl_vartime(128));
+        assert_eq!(U256::from(expected), transfer.token_id);
+    }
+
+    #[test]
+    fn test_u256_limb_overflow_is_an_error() {
+        let event = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            PROJECT,
+            &["0x0", "0x123", "0x7", "0x100000000000000000000000000000000"],
+        );
+
+        assert!(matches!(
+            Transfer::try_from(&event),
+            Err(DomainError::InvalidEventPayload(_, _))
+        ));
+    }
+}
@@ -1,21 +1,38 @@
 use crate::{
-    domain::crypto::U256,
-    infrastructure::postgres::event_source::{
-        create_token_for_customer, decrease_token_value, update_project_project_value,
-        update_token_owner, update_token_slot, update_token_value,
+    domain::{crypto::U256, Ulid},
+    infrastructure::{
+        postgres::entity::AbsorptionCurve,
+        starknet::{
+            get_starknet_rpc_from_env,
+            project::{load_project_absorption_curve, load_project_uri_metadata},
+        },
     },
 };
 use apibara_core::starknet::v1alpha2::FieldElement;
-use crypto_bigint::Encoding;
-use deadpool_postgres::Transaction;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use starknet::macros::selector;
-use std::{collections::HashMap, sync::Mutex};
+use std::{collections::HashMap, sync::Arc};
+use time::PrimitiveDateTime;
 use tracing::{error, info};
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{
-    event_bus::Consumer, get_event, to_filters, BlockMetadata, DomainError, DomainEvent, Event,
-    Filterable,
+    active_addresses, add_nested_paths,
+    event_bus::Consumer,
+    get_event,
+    payload::{
+        Approval, ApprovalForAll, ApprovalValue, MetadataUpdate, ProjectValueUpdate, SlotChanged,
+        Transfer, TransferValue,
+    },
+    repository::{
+        AbsorptionCurveRepository, CustomerApprovalRepository, CustomerTokenRepository,
+        ProjectMetadataRepository, ProjectRepository,
+    },
+    to_filters,
+    transaction::TransactionManager,
+    BlockMetadata, DomainError, DomainEvent, Event, Filterable,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,7 +54,7 @@ pub enum ProjectEvents {
 #[derive(Debug)]
 pub struct ProjectFilters {
     contracts: Vec<String>,
-    filters: HashMap<String, Vec<(String, Event)>>,
+    filters: HashMap<String, Vec<(Vec<String>, Event)>>,
 }
 
 impl ProjectFilters {
@@ -57,42 +74,106 @@ impl Default for ProjectFilters {
 
 /// Add [`Filterable`] behaviour on [`Project`]
 impl Filterable for ProjectFilters {
-    fn to_filters(&self) -> Vec<(String, String)> {
+    fn to_filters(&self) -> Vec<(String, Vec<String>)> {
         to_filters(&self.filters)
     }
 
-    fn get_event(&mut self, contract_address: &str, event_key: &str) -> Option<Event> {
-        get_event(&mut self.filters, contract_address, event_key)
+    fn get_event(&mut self, contract_address: &str, keys: &[String]) -> Option<(Event, usize)> {
+        get_event(&mut self.filters, contract_address, keys)
     }
 
-    fn hydrate_from_file(&mut self, address_list: Vec<HashMap<String, String>>) {
-        let contracts = self.extract_addresses(address_list, &["project", "project_3525"]);
-        self.contracts = contracts;
+    fn add_nested_paths(&mut self, contract_address: &str, paths: &[Vec<String>]) {
+        add_nested_paths(&mut self.filters, contract_address, paths)
+    }
+
+    fn hydrate(&mut self, contracts: &[RegisteredContract]) {
+        self.contracts = active_addresses(
+            contracts,
+            &[ContractType::Project, ContractType::Project3525],
+        );
+        // filters are rebuilt so that deactivated contracts are dropped
+        self.filters.clear();
         for contract in self.contracts.iter() {
-            let parts = contract.split("//").collect::<Vec<&str>>();
-            let addr = parts[0];
             self.filters.insert(
-                addr.to_string(),
+                contract.to_string(),
                 [
                     (
-                        FieldElement::from_bytes(&selector!("Transfer").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Upgraded").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Project(ProjectEvents::Upgraded),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("Transfer").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Project(ProjectEvents::Transfer),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("TransferValue").to_bytes_be())
-                            .to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("TransferValue").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Project(ProjectEvents::TransferValue),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("SlotChanged").to_bytes_be())
-                            .to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("SlotChanged").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Project(ProjectEvents::SlotChanged),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("ProjectValueUpdate").to_bytes_be())
-                            .to_string(),
+                        vec![FieldElement::from_bytes(
+                            &selector!("ProjectValueUpdate").to_bytes_be(),
+                        )
+                        .to_string()],
                         Event::Project(ProjectEvents::ProjectValueUpdate),
                     ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("Approval").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Project(ProjectEvents::Approval),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("ApprovalValue").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Project(ProjectEvents::ApprovalValue),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("ApprovalForAll").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Project(ProjectEvents::ApprovalForAll),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("MetadataUpdate").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Project(ProjectEvents::MetadataUpdate),
+                    ),
+                    (
+                        vec![FieldElement::from_bytes(
+                            &selector!("BatchMetadataUpdate").to_bytes_be(),
+                        )
+                        .to_string()],
+                        Event::Project(ProjectEvents::BatchMetadataUpdate),
+                    ),
+                    (
+                        vec![FieldElement::from_bytes(
+                            &selector!("AbsorptionUpdate").to_bytes_be(),
+                        )
+                        .to_string()],
+                        Event::Project(ProjectEvents::AbsorptionUpdate),
+                    ),
                 ]
                 .to_vec(),
             );
@@ -110,7 +191,10 @@ impl ProjectTransferEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for ProjectTransferEventConsumer {
+impl<Txn> Consumer<Txn> for ProjectTransferEventConsumer
+where
+    Txn: TransactionManager + CustomerTokenRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Project(ProjectEvents::Transfer))
     }
@@ -119,54 +203,28 @@ impl Consumer<Transaction<'_>> for ProjectTransferEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
         // if transfer `from`=0 this means token_id is created.
         // when transfer event is emitted in blockchain, it only has data about transfer from one
         // address to an other.
         // We may encounter duplicated data due to this. Be careful when you handle event.
-        let from = event.payload.get("0").expect("should have from value set");
-        let to = event.payload.get("1").expect("should have to value set");
-        let token_id_felt = FieldElement::from_hex(
-            event
-                .payload
-                .get("2")
-                .expect("should have token_id value set"),
-        )
-        .expect("should be able to convert to felt");
-        let token_id = U256(crypto_bigint::U256::from_be_bytes(token_id_felt.to_bytes()));
-        let contract_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-
-        if FieldElement::from_u64(0) == FieldElement::from_hex(from).unwrap() {
-            return Ok(create_token_for_customer(txn, contract_address, to, &token_id).await?);
-        }
-
-        Ok(update_token_owner(txn, from, contract_address, to, &token_id).await?)
-    }
-}
+        let transfer = Transfer::try_from(event)?;
+        let contract_address = event.from_address()?;
 
-#[async_trait::async_trait]
-impl Consumer<Mutex<HashMap<String, Vec<DomainEvent>>>> for ProjectTransferEventConsumer {
-    fn can_consume(&self, event: &Event) -> bool {
-        matches!(event, Event::Project(ProjectEvents::Transfer))
-    }
-
-    async fn consume(
-        &self,
-        event: &DomainEvent,
-        _metadata: &BlockMetadata,
-        tx: &mut Mutex<HashMap<String, Vec<DomainEvent>>>,
-    ) -> Result<(), DomainError> {
-        let lock = tx.get_mut().unwrap();
-
-        lock.entry("project-transfer".to_string())
-            .or_insert(vec![])
-            .push(event.clone());
+        if transfer.is_mint() {
+            return txn
+                .create_token_for_customer(contract_address, &transfer.to, &transfer.token_id)
+                .await;
+        }
 
-        Ok(())
+        txn.update_token_owner(
+            &transfer.from,
+            contract_address,
+            &transfer.to,
+            &transfer.token_id,
+        )
+        .await
     }
 }
 
@@ -180,7 +238,10 @@ impl ProjectTransferValueEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for ProjectTransferValueEventConsumer {
+impl<Txn> Consumer<Txn> for ProjectTransferValueEventConsumer
+where
+    Txn: TransactionManager + CustomerTokenRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Project(ProjectEvents::TransferValue))
     }
@@ -189,16 +250,19 @@ impl Consumer<Transaction<'_>> for ProjectTransferValueEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event.metadata.get("from_address").unwrap();
-        let from_token_id =
-            U256::from(FieldElement::from_hex(event.payload.get("0").unwrap()).unwrap());
-        let to_token_id =
-            U256::from(FieldElement::from_hex(event.payload.get("2").unwrap()).unwrap());
-        let value = U256::from(FieldElement::from_hex(event.payload.get("4").unwrap()).unwrap());
-
-        match update_token_value(txn, from_address, &to_token_id, value.clone()).await {
+        let from_address = event.from_address()?;
+        let TransferValue {
+            from_token_id,
+            to_token_id,
+            value,
+        } = TransferValue::try_from(event)?;
+
+        match txn
+            .update_token_value(from_address, &to_token_id, value)
+            .await
+        {
             Ok(_) => info!("project.transfer_value.update: success"),
             Err(e) => error!(
                 "project.transfer_value.update: transfer_value_event_consumer {:#?}",
@@ -206,7 +270,10 @@ impl Consumer<Transaction<'_>> for ProjectTransferValueEventConsumer {
             ),
         }
 
-        match decrease_token_value(txn, from_address, &from_token_id, value.clone()).await {
+        match txn
+            .decrease_token_value(from_address, &from_token_id, value)
+            .await
+        {
             Ok(_) => info!("project.transfer_value.decrease: success"),
             Err(e) => error!("project.transfer_value.decrease: failed {:#?}", e),
         }
@@ -224,7 +291,10 @@ impl ProjectSlotChangedEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for ProjectSlotChangedEventConsumer {
+impl<Txn> Consumer<Txn> for ProjectSlotChangedEventConsumer
+where
+    Txn: TransactionManager + CustomerTokenRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Project(ProjectEvents::SlotChanged))
     }
@@ -233,19 +303,19 @@ impl Consumer<Transaction<'_>> for ProjectSlotChangedEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
         // token_id is unique per contract AND per slot.
-        let from_address = event.metadata.get("from_address").unwrap();
-        let token_id = U256::from(FieldElement::from_hex(event.payload.get("0").unwrap()).unwrap());
-        let old_slot = U256::from(FieldElement::from_hex(event.payload.get("2").unwrap()).unwrap());
-        let slot = U256::from(FieldElement::from_hex(event.payload.get("4").unwrap()).unwrap());
+        let from_address = event.from_address()?;
+        let slot_changed = SlotChanged::try_from(event)?;
 
         // token created from slot 0
         // if token is moved from a slot to another, it means to us that slot is moved from
         // a project to another one which is not possible at the moment.
-        if U256::from(0u64) == old_slot {
-            return Ok(update_token_slot(txn, from_address, &token_id, &slot).await?);
+        if U256::zero() == slot_changed.old_slot {
+            return txn
+                .update_token_slot(from_address, &slot_changed.token_id, &slot_changed.new_slot)
+                .await;
         }
         Ok(())
     }
@@ -261,7 +331,10 @@ impl ProjectProjectValueUpdateEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for ProjectProjectValueUpdateEventConsumer {
+impl<Txn> Consumer<Txn> for ProjectProjectValueUpdateEventConsumer
+where
+    Txn: TransactionManager + ProjectRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Project(ProjectEvents::ProjectValueUpdate))
     }
@@ -270,13 +343,636 @@ impl Consumer<Transaction<'_>> for ProjectProjectValueUpdateEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let from_address = event.from_address()?;
+        let update = ProjectValueUpdate::try_from(event)?;
+
+        txn.update_project_project_value(from_address, &update.slot, &update.project_value)
+            .await
+    }
+}
+
+/// Consuming [`Approval`] event emitted from [`Project`] on chain
+#[derive(Default, Debug)]
+pub struct ProjectApprovalEventConsumer {}
+impl ProjectApprovalEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for ProjectApprovalEventConsumer
+where
+    Txn: TransactionManager + CustomerApprovalRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(event, Event::Project(ProjectEvents::Approval))
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
+        _metadata: &BlockMetadata,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let from_address = event.from_address()?;
+        let approval = Approval::try_from(event)?;
+
+        // a single address is approved on whole token, approving another one replaces it
+        txn.clear_token_approval(from_address, &approval.token_id)
+            .await?;
+        if approval.is_cleared() {
+            return Ok(());
+        }
+        txn.approve_token(from_address, &approval.token_id, &approval.approved)
+            .await
+    }
+}
+
+/// Consuming [`ApprovalValue`] event emitted from [`Project`] on chain
+#[derive(Default, Debug)]
+pub struct ProjectApprovalValueEventConsumer {}
+impl ProjectApprovalValueEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for ProjectApprovalValueEventConsumer
+where
+    Txn: TransactionManager + CustomerApprovalRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(event, Event::Project(ProjectEvents::ApprovalValue))
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
+        _metadata: &BlockMetadata,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let from_address = event.from_address()?;
+        let approval = ApprovalValue::try_from(event)?;
+
+        txn.approve_token_value(
+            from_address,
+            &approval.token_id,
+            &approval.operator,
+            &approval.value,
+        )
+        .await
+    }
+}
+
+/// Consuming [`ApprovalForAll`] event emitted from [`Project`] on chain
+#[derive(Default, Debug)]
+pub struct ProjectApprovalForAllEventConsumer {}
+impl ProjectApprovalForAllEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for ProjectApprovalForAllEventConsumer
+where
+    Txn: TransactionManager + CustomerApprovalRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(event, Event::Project(ProjectEvents::ApprovalForAll))
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
+        _metadata: &BlockMetadata,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let from_address = event.from_address()?;
+        let approval = ApprovalForAll::try_from(event)?;
+
+        txn.set_approval_for_all(
+            from_address,
+            &approval.owner,
+            &approval.operator,
+            approval.approved,
+        )
+        .await
+    }
+}
+
+/// Consuming [`MetadataUpdate`] and [`BatchMetadataUpdate`] events emitted from [`Project`] on chain
+#[derive(Default, Debug)]
+pub struct ProjectMetadataUpdateEventConsumer {}
+impl ProjectMetadataUpdateEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for ProjectMetadataUpdateEventConsumer
+where
+    Txn: TransactionManager + ProjectRepository + ProjectMetadataRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(
+            event,
+            Event::Project(ProjectEvents::MetadataUpdate | ProjectEvents::BatchMetadataUpdate)
+        )
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
+        _metadata: &BlockMetadata,
+        txn: &mut Txn,
+    ) -> Result<(), DomainError> {
+        let from_address = event.from_address()?;
+        let update = MetadataUpdate::try_from(event)?;
+        let slots = get_updated_project_slots(txn, from_address, &update).await?;
+
+        let provider = Arc::new(get_starknet_rpc_from_env()?);
+        for slot in slots.iter() {
+            let (uri, metadata) =
+                load_project_uri_metadata(provider.clone(), from_address, slot.as_ref()).await?;
+            txn.refresh_project_uri(from_address, slot.as_ref(), &uri, &metadata)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Get project slots holding tokens of metadata update, `None` for an ERC-721 project
+/// which has a single uri
+/// * txn - [`Txn`]
+/// * project_address - [`&str`]
+/// * update - [`&MetadataUpdate`]
+///
+async fn get_updated_project_slots<Txn>(
+    txn: &mut Txn,
+    project_address: &str,
+    update: &MetadataUpdate,
+) -> Result<Vec<Option<U256>>, DomainError>
+where
+    Txn: ProjectRepository + ProjectMetadataRepository,
+{
+    let slots = txn.get_project_slots(project_address).await?;
+    if slots.is_empty() {
+        return Err(DomainError::ContractNotFound(project_address.to_string()));
+    }
+    if slots.contains(&None) {
+        return Ok(vec![None]);
+    }
+
+    let token_slots = txn
+        .get_project_token_slots(project_address, &update.from_token_id, &update.to_token_id)
+        .await?;
+    if token_slots.is_empty() {
+        // tokens not projected yet, e.g. while customer_token projection is rebuilt
+        return Ok(slots);
+    }
+    Ok(slots
+        .into_iter()
+        .filter(|slot| matches!(slot, Some(slot) if token_slots.contains(slot)))
+        .collect())
+}
+
+/// Consuming [`AbsorptionUpdate`] event emitted from [`Project`] on chain
+#[derive(Default, Debug)]
+pub struct ProjectAbsorptionUpdateEventConsumer {}
+impl ProjectAbsorptionUpdateEventConsumer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl<Txn> Consumer<Txn> for ProjectAbsorptionUpdateEventConsumer
+where
+    Txn: TransactionManager + ProjectRepository + AbsorptionCurveRepository,
+{
+    fn can_consume(&self, event: &Event) -> bool {
+        matches!(event, Event::Project(ProjectEvents::AbsorptionUpdate))
+    }
+
+    async fn consume(
+        &self,
+        event: &DomainEvent,
+        metadata: &BlockMetadata,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        // slot: Uint256, projectValue: Uint256
-        let from_address = event.metadata.get("from_address").unwrap();
-        let slot = U256::from(FieldElement::from_hex(event.payload.get("0").unwrap()).unwrap());
-        let value = U256::from(FieldElement::from_hex(event.payload.get("2").unwrap()).unwrap());
+        let from_address = event.from_address()?;
+        let slots = txn.get_project_slots(from_address).await?;
+        if slots.is_empty() {
+            return Err(DomainError::ContractNotFound(from_address.to_string()));
+        }
+
+        // curve is not part of the event, it is read at the event block so replays
+        // rebuild every revision
+        let provider = Arc::new(get_starknet_rpc_from_env()?);
+        for slot in slots.into_iter() {
+            let (times, absorptions) = load_project_absorption_curve(
+                provider.clone(),
+                from_address,
+                slot.as_ref(),
+                metadata.get_block(),
+            )
+            .await?;
+            let time = PrimitiveDateTime::new(metadata.timestamp.date(), metadata.timestamp.time());
+            let mut current = txn
+                .get_current_absorption_curve(from_address, slot.as_ref())
+                .await?;
+            if current.is_none() {
+                // project seeded after absorption_curve migration, its seeded curve becomes
+                // version 0 before first update is recorded
+                if let Some(seeded) = txn
+                    .get_seeded_absorption_curve(from_address, slot.as_ref())
+                    .await?
+                {
+                    txn.add_absorption_curve(&seeded).await?;
+                    current = Some(seeded);
+                }
+            }
+            if matches!(&current, Some(c) if c.times == times && c.absorptions == absorptions) {
+                continue;
+            }
+
+            txn.add_absorption_curve(&AbsorptionCurve {
+                id: Ulid::new(),
+                project_address: from_address.to_string(),
+                slot,
+                version: current.map_or(1, |c| c.version + 1),
+                times,
+                absorptions,
+                event_id: event.id.to_string(),
+                time,
+            })
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Merge metadata fetched from project uri into stored project metadata,
+/// keys only set in database (e.g `milestones`, `rating`) are kept
+/// * stored - [`Option<Value>`]
+/// * fetched - [`&Value`]
+///
+pub fn merge_project_metadata(stored: Option<Value>, fetched: &Value) -> Value {
+    match (stored, fetched) {
+        (Some(Value::Object(mut stored)), Value::Object(fetched)) => {
+            stored.extend(fetched.clone());
+            Value::Object(stored)
+        }
+        _ => fetched.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            crypto::U256,
+            event_source::{
+                event_bus::Consumer,
+                fixtures::{consume_all, event, metadata},
+                repository::AbsorptionCurveRepository,
+                transaction::TransactionManager,
+                DomainError, Event,
+            },
+            Ulid,
+        },
+        infrastructure::{in_memory::InMemoryProjectionStore, postgres::entity::AbsorptionCurve},
+    };
+    use serde_json::json;
+    use time::macros::datetime;
+
+    use super::{
+        get_updated_project_slots, merge_project_metadata, MetadataUpdate,
+        ProjectApprovalEventConsumer, ProjectApprovalForAllEventConsumer,
+        ProjectApprovalValueEventConsumer, ProjectEvents, ProjectProjectValueUpdateEventConsumer,
+        ProjectSlotChangedEventConsumer, ProjectTransferEventConsumer,
+        ProjectTransferValueEventConsumer,
+    };
+
+    const PROJECT: &str = "0x0456";
+
+    #[tokio::test]
+    async fn test_minted_token_is_moved_to_new_owner() {
+        let store = InMemoryProjectionStore::new();
+        let mut txn = store.transaction();
+        let transfer = Event::Project(ProjectEvents::Transfer);
+        let mint = event(
+            "0x01_0",
+            transfer.clone(),
+            PROJECT,
+            &["0x0", "0x123", "0x7", "0x0"],
+        );
+        let transfer = event(
+            "0x02_0",
+            transfer,
+            PROJECT,
+            &["0x123", "0x789", "0x7", "0x0"],
+        );
+        let slot_changed = event(
+            "0x01_1",
+            Event::Project(ProjectEvents::SlotChanged),
+            PROJECT,
+            &["0x7", "0x0", "0x0", "0x0", "0x1", "0x0"],
+        );
+
+        ProjectTransferEventConsumer::new()
+            .consume(&mint, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        ProjectSlotChangedEventConsumer::new()
+            .consume(&slot_changed, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        ProjectTransferEventConsumer::new()
+            .consume(&transfer, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+
+        let tokens = store.projections().customer_tokens;
+        assert_eq!(1, tokens.len());
+        assert_eq!("0x789", tokens[0].address);
+        assert_eq!(U256::from(7u64), tokens[0].token_id);
+        assert_eq!(Some(U256::from(1u64)), tokens[0].slot);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_value_moves_value_between_tokens() {
+        let store = InMemoryProjectionStore::new();
+        let mut txn = store.transaction();
+        let transfer = Event::Project(ProjectEvents::Transfer);
+        let transfer_value = Event::Project(ProjectEvents::TransferValue);
+        let fixtures = [
+            event(
+                "0x01_0",
+                transfer.clone(),
+                PROJECT,
+                &["0x0", "0x123", "0x1", "0x0"],
+            ),
+            event("0x02_0", transfer, PROJECT, &["0x0", "0x789", "0x2", "0x0"]),
+            event(
+                "0x01_1",
+                transfer_value.clone(),
+                PROJECT,
+                &["0x0", "0x0", "0x1", "0x0", "0x64", "0x0"],
+            ),
+            event(
+                "0x03_0",
+                transfer_value,
+                PROJECT,
+                &["0x1", "0x0", "0x2", "0x0", "0x28", "0x0"],
+            ),
+        ];
+
+        for fixture in fixtures.iter() {
+            if matches!(fixture.r#type, Event::Project(ProjectEvents::Transfer)) {
+                ProjectTransferEventConsumer::new()
+                    .consume(fixture, &metadata(), &mut txn)
+                    .await
+                    .unwrap();
+            } else {
+                ProjectTransferValueEventConsumer::new()
+                    .consume(fixture, &metadata(), &mut txn)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let tokens = &txn.projections().customer_tokens;
+        assert_eq!(Some(U256::from(60u64)), tokens[0].value);
+        assert_eq!(Some(U256::from(40u64)), tokens[1].value);
+
+        txn.rollback().await.unwrap();
+        assert!(store.projections().customer_tokens.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_project_value_is_updated() {
+        let store = InMemoryProjectionStore::new().with_project(PROJECT, U256::from(1u64));
+        let mut txn = store.transaction();
+        let update = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::ProjectValueUpdate),
+            PROJECT,
+            &["0x1", "0x0", "0x3e8", "0x0"],
+        );
+
+        ProjectProjectValueUpdateEventConsumer::new()
+            .consume(&update, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+
+        assert_eq!(
+            Some(U256::from(1000u64)),
+            store.projections().projects[0].project_value
+        );
+    }
+
+    #[tokio::test]
+    async fn test_approvals_track_current_operators() {
+        let store = InMemoryProjectionStore::new();
+        let approvals = [
+            ("0x01_0", ["0x123", "0x0aaa", "0x7", "0x0"]),
+            ("0x02_0", ["0x123", "0x0bbb", "0x7", "0x0"]),
+        ]
+        .map(|(id, data)| event(id, Event::Project(ProjectEvents::Approval), PROJECT, &data));
+        let approval_values = [
+            ("0x03_0", ["0x7", "0x0", "0x0ccc", "0x64", "0x0"]),
+            ("0x04_0", ["0x7", "0x0", "0x0ddd", "0x28", "0x0"]),
+            ("0x05_0", ["0x7", "0x0", "0x0ddd", "0x0", "0x0"]),
+        ]
+        .map(|(id, data)| {
+            event(
+                id,
+                Event::Project(ProjectEvents::ApprovalValue),
+                PROJECT,
+                &data,
+            )
+        });
+        let approvals_for_all = [
+            ("0x06_0", ["0x123", "0x0eee", "0x1"]),
+            ("0x07_0", ["0x123", "0x0fff", "0x1"]),
+            ("0x08_0", ["0x123", "0x0fff", "0x0"]),
+        ]
+        .map(|(id, data)| {
+            event(
+                id,
+                Event::Project(ProjectEvents::ApprovalForAll),
+                PROJECT,
+                &data,
+            )
+        });
+
+        consume_all(&store, &ProjectApprovalEventConsumer::new(), &approvals)
+            .await
+            .unwrap();
+        consume_all(
+            &store,
+            &ProjectApprovalValueEventConsumer::new(),
+            &approval_values,
+        )
+        .await
+        .unwrap();
+        consume_all(
+            &store,
+            &ProjectApprovalForAllEventConsumer::new(),
+            &approvals_for_all,
+        )
+        .await
+        .unwrap();
+
+        let projections = store.projections();
+        let tokens = projections
+            .token_approvals
+            .iter()
+            .map(|a| (a.operator_address.as_str(), a.approved, a.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("0x0bbb", true, None),
+                ("0x0ccc", false, Some(U256::from(100u64)))
+            ],
+            tokens
+        );
+        assert_eq!(1, projections.operator_approvals.len());
+        assert_eq!("0x0eee", projections.operator_approvals[0].operator_address);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_update_refreshes_slots_of_updated_tokens() {
+        let store = [1u64, 2, 3]
+            .into_iter()
+            .fold(InMemoryProjectionStore::new(), |store, slot| {
+                store.with_project(PROJECT, U256::from(slot))
+            })
+            .with_erc721_project("0x0789");
+        let mints = ["0x1", "0x2", "0x5"].map(|token_id| {
+            event(
+                &format!("{token_id}_0"),
+                Event::Project(ProjectEvents::Transfer),
+                PROJECT,
+                &["0x0", "0x123", token_id, "0x0"],
+            )
+        });
+        let slot_changes =
+            [("0x1", "0x1"), ("0x2", "0x2"), ("0x5", "0x3")].map(|(token_id, slot)| {
+                event(
+                    &format!("{token_id}_1"),
+                    Event::Project(ProjectEvents::SlotChanged),
+                    PROJECT,
+                    &[token_id, "0x0", "0x0", "0x0", slot, "0x0"],
+                )
+            });
+        consume_all(&store, &ProjectTransferEventConsumer::new(), &mints)
+            .await
+            .unwrap();
+        consume_all(
+            &store,
+            &ProjectSlotChangedEventConsumer::new(),
+            &slot_changes,
+        )
+        .await
+        .unwrap();
+
+        let updated_slots = |address: &str, r#type: ProjectEvents, data: &[&str]| {
+            let update =
+                MetadataUpdate::try_from(&event("0x0a_0", Event::Project(r#type), address, data))
+                    .unwrap();
+            let address = address.to_owned();
+            let mut txn = store.transaction();
+            async move {
+                get_updated_project_slots(&mut txn, &address, &update)
+                    .await
+                    .unwrap()
+            }
+        };
+        let slot = |slot: u64| Some(U256::from(slot));
+
+        assert_eq!(
+            vec![slot(2)],
+            updated_slots(PROJECT, ProjectEvents::MetadataUpdate, &["0x2", "0x0"]).await
+        );
+        assert_eq!(
+            vec![slot(1), slot(2)],
+            updated_slots(
+                PROJECT,
+                ProjectEvents::BatchMetadataUpdate,
+                &["0x1", "0x0", "0x4", "0x0"]
+            )
+            .await
+        );
+        // tokens not projected yet
+        assert_eq!(
+            vec![slot(1), slot(2), slot(3)],
+            updated_slots(PROJECT, ProjectEvents::MetadataUpdate, &["0x9", "0x0"]).await
+        );
+        assert_eq!(
+            vec![None],
+            updated_slots("0x0789", ProjectEvents::MetadataUpdate, &["0x1", "0x0"]).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_absorption_curve_revision_is_rejected() {
+        let store = InMemoryProjectionStore::new().with_erc721_project(PROJECT);
+        let mut txn = store.transaction();
+        let revision = |slot: Option<U256>, version: i32| AbsorptionCurve {
+            id: Ulid::new(),
+            project_address: PROJECT.to_owned(),
+            slot,
+            version,
+            times: vec![datetime!(2023-11-14 10:00)],
+            absorptions: vec![U256::from(1000u64 * version as u64)],
+            event_id: format!("0x0{version}_0"),
+            time: datetime!(2023-11-14 10:00),
+        };
+
+        // ERC-721 curves have no slot, their revisions are unique all the same
+        txn.add_absorption_curve(&revision(None, 1)).await.unwrap();
+        assert!(matches!(
+            txn.add_absorption_curve(&revision(None, 1)).await,
+            Err(DomainError::FailedToPersistEvent)
+        ));
+        txn.add_absorption_curve(&revision(None, 2)).await.unwrap();
+        txn.add_absorption_curve(&revision(Some(U256::from(1u64)), 1))
+            .await
+            .unwrap();
+
+        let current = txn
+            .get_current_absorption_curve(PROJECT, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, current.version);
+        assert_eq!(3, txn.projections().absorption_curves.len());
+    }
+
+    #[test]
+    fn test_fetched_metadata_keeps_database_only_keys() {
+        let stored = json!({"name": "Banegas Farm", "rating": "B", "milestones": [1, 2]});
+        let fetched = json!({"name": "Banegas Farm II", "external_url": "https://carbonable.io"});
 
-        Ok(update_project_project_value(txn, from_address, &slot, &value).await?)
+        assert_eq!(
+            json!({
+                "name": "Banegas Farm II",
+                "rating": "B",
+                "milestones": [1, 2],
+                "external_url": "https://carbonable.io"
+            }),
+            merge_project_metadata(Some(stored), &fetched)
+        );
+        assert_eq!(fetched, merge_project_metadata(None, &fetched));
     }
 }
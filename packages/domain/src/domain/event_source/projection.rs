@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+use super::DomainError;
+
+/// Read model built out of the event store.
+/// Each projection keeps its own checkpoint so it can run, pause or be rebuilt
+/// independently of the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Projection {
+    CustomerToken,
+    CustomerFarm,
+    YielderSnapshot,
+    YielderProvision,
+    ProjectValue,
+    MinterSale,
+    MinterStatus,
+    CustomerApproval,
+    ProjectMetadata,
+    ProjectAbsorption,
+}
+
+impl Projection {
+    pub fn all() -> [Projection; 10] {
+        [
+            Projection::CustomerToken,
+            Projection::CustomerFarm,
+            Projection::YielderSnapshot,
+            Projection::YielderProvision,
+            Projection::ProjectValue,
+            Projection::MinterSale,
+            Projection::MinterStatus,
+            Projection::CustomerApproval,
+            Projection::ProjectMetadata,
+            Projection::ProjectAbsorption,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Projection::CustomerToken => "customer_token",
+            Projection::CustomerFarm => "customer_farm",
+            Projection::YielderSnapshot => "yielder_snapshot",
+            Projection::YielderProvision => "yielder_provision",
+            Projection::ProjectValue => "project_value",
+            Projection::MinterSale => "minter_sale",
+            Projection::MinterStatus => "minter_status",
+            Projection::CustomerApproval => "customer_approval",
+            Projection::ProjectMetadata => "project_metadata",
+            Projection::ProjectAbsorption => "project_absorption",
+        }
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for Projection {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Projection::all()
+            .into_iter()
+            .find(|p| p.as_str() == value)
+            .ok_or(DomainError::UnknownProjection(value.to_owned()))
+    }
+}
+
+/// Whether a projection runner should consume events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionStatus {
+    Running,
+    Paused,
+}
+
+impl ProjectionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectionStatus::Running => "running",
+            ProjectionStatus::Paused => "paused",
+        }
+    }
+}
+
+impl Display for ProjectionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ProjectionStatus {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "running" => Ok(ProjectionStatus::Running),
+            "paused" => Ok(ProjectionStatus::Paused),
+            _ => Err(DomainError::UnknownProjectionStatus(value.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Projection;
+
+    #[test]
+    fn test_projection_names_round_trip() {
+        for projection in Projection::all() {
+            assert_eq!(
+                projection,
+                Projection::try_from(projection.as_str()).unwrap()
+            );
+        }
+        assert!(Projection::try_from("customer_tokens").is_err());
+    }
+}
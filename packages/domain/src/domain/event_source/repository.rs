@@ -0,0 +1,375 @@
+use time::OffsetDateTime;
+
+use crate::{
+    domain::{crypto::U256, Ulid},
+    infrastructure::postgres::entity::{
+        AbsorptionCurve, ActionType, Airdrop, Buy, FarmType, Snapshot,
+    },
+};
+
+use super::DomainError;
+
+/// `customer_token` projection, tokens owned by customers and their value
+/// Every projection repository is implemented on postgres [`Transaction`], and on
+/// [`InMemoryTransaction`] to test [`Consumer`] without database.
+///
+/// [`Consumer`]: super::event_bus::Consumer
+/// [`Transaction`]: deadpool_postgres::Transaction
+/// [`InMemoryTransaction`]: crate::infrastructure::in_memory::InMemoryTransaction
+#[async_trait::async_trait]
+pub trait CustomerTokenRepository: Send {
+    /// Create token minted to customer
+    /// * contract_address - [`&str`]
+    /// * to - [`&str`] - Customer address
+    /// * token_id - [`&U256`]
+    ///
+    async fn create_token_for_customer(
+        &mut self,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Move customer token to its new owner
+    /// * from - [`&str`]
+    /// * contract_address - [`&str`]
+    /// * to - [`&str`]
+    /// * token_id - [`&U256`]
+    ///
+    async fn update_token_owner(
+        &mut self,
+        from: &str,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Move customer token from migration source project to target project
+    /// * project_address - [`&str`]
+    /// * from_project_address - [`&str`]
+    /// * customer_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * new_token_id - [`&U256`]
+    /// * slot - [`&U256`]
+    /// * value - [`&U256`]
+    ///
+    #[allow(clippy::too_many_arguments)]
+    async fn migrate_customer_token(
+        &mut self,
+        project_address: &str,
+        from_project_address: &str,
+        customer_address: &str,
+        token_id: &U256,
+        new_token_id: &U256,
+        slot: &U256,
+        value: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Add value to customer token
+    /// * contract_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * value - [`U256`]
+    ///
+    async fn update_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError>;
+
+    /// Remove value from customer token, down to zero.
+    /// Token may be unknown as value can be transferred to our own contracts.
+    /// * contract_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * value - [`U256`]
+    ///
+    async fn decrease_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError>;
+
+    /// Set customer token slot
+    /// * contract_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * slot - [`&U256`]
+    ///
+    async fn update_token_slot(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        slot: &U256,
+    ) -> Result<(), DomainError>;
+}
+
+/// `project_value` projection, values of project slots
+#[async_trait::async_trait]
+pub trait ProjectRepository: Send {
+    /// Get seeded slots of project, `None` for an ERC-721 project
+    /// * project_address - [`&str`]
+    ///
+    async fn get_project_slots(
+        &mut self,
+        project_address: &str,
+    ) -> Result<Vec<Option<U256>>, DomainError>;
+
+    /// Set project slot total value
+    /// * project_address - [`&str`]
+    /// * slot - [`&U256`]
+    /// * total_value - [`&U256`]
+    ///
+    async fn update_project_total_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        total_value: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Set project slot project value
+    /// * project_address - [`&str`]
+    /// * slot - [`&U256`]
+    /// * project_value - [`&U256`]
+    ///
+    async fn update_project_project_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        project_value: &U256,
+    ) -> Result<(), DomainError>;
+}
+
+/// `yielder_provision` and `yielder_snapshot` projections
+#[async_trait::async_trait]
+pub trait YielderRepository: Send {
+    /// Get yielder id from blockchain address
+    /// * yielder_address - [`&str`]
+    ///
+    async fn get_yielder_id_from_address(&mut self, yielder_address: &str) -> Option<Ulid>;
+
+    /// Add provision to yielder
+    /// * yielder_id - [`Ulid`]
+    /// * amount - [`U256`]
+    /// * time - [`OffsetDateTime`]
+    ///
+    async fn add_provision_to_yielder(
+        &mut self,
+        yielder_id: Ulid,
+        amount: U256,
+        time: OffsetDateTime,
+    ) -> Result<(), DomainError>;
+
+    /// Add snapshot to yielder
+    /// * snapshot - [`&Snapshot`]
+    ///
+    async fn add_snapshot_to_yielder(&mut self, snapshot: &Snapshot) -> Result<(), DomainError>;
+}
+
+/// `customer_farm` projection, customer actions on yielders and offseters
+#[async_trait::async_trait]
+pub trait CustomerActionRepository: Send {
+    /// Find project address and slot farmed by given contract
+    /// * address - [`&str`] - Yielder or offseter address
+    /// * farm_type - [`FarmType`]
+    ///
+    async fn find_related_project_address_and_slot(
+        &mut self,
+        address: &str,
+        farm_type: FarmType,
+    ) -> Result<(String, U256), DomainError>;
+
+    /// Append customer action on farms, replayed events are ignored
+    /// * event_id - [`&str`]
+    /// * event_timestamp - [`OffsetDateTime`]
+    /// * customer_address - [`&str`]
+    /// * project_address - [`&str`]
+    /// * slot - [`&U256`]
+    /// * value - [`&U256`]
+    /// * farm_type - [`FarmType`]
+    /// * action_type - [`ActionType`]
+    ///
+    #[allow(clippy::too_many_arguments)]
+    async fn append_customer_action(
+        &mut self,
+        event_id: &str,
+        event_timestamp: OffsetDateTime,
+        customer_address: &str,
+        project_address: &str,
+        slot: &U256,
+        value: &U256,
+        farm_type: FarmType,
+        action_type: ActionType,
+    ) -> Result<(), DomainError>;
+}
+
+/// `minter_sale` projection, tokens bought and airdropped through minters
+#[async_trait::async_trait]
+pub trait MinterSaleRepository: Send {
+    /// Get minter id from blockchain address, `None` when minter is unknown
+    /// * minter_address - [`&str`]
+    ///
+    async fn get_minter_id_from_address(
+        &mut self,
+        minter_address: &str,
+    ) -> Result<Option<Ulid>, DomainError>;
+
+    /// Add buy to minter, replayed events are ignored
+    /// * buy - [`&Buy`] - `event_id` is the id of the event it comes from
+    ///
+    async fn add_buy_to_minter(&mut self, buy: &Buy) -> Result<(), DomainError>;
+
+    /// Add airdrop to minter, replayed events are ignored
+    /// * airdrop - [`&Airdrop`] - `event_id` is the id of the event it comes from
+    ///
+    async fn add_airdrop_to_minter(&mut self, airdrop: &Airdrop) -> Result<(), DomainError>;
+}
+
+/// `minter_status` projection, sale status of minters
+#[async_trait::async_trait]
+pub trait MinterStatusRepository: Send {
+    /// Open or close minter whitelisted pre sale
+    /// * minter_address - [`&str`]
+    /// * open - [`bool`]
+    ///
+    async fn update_minter_pre_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError>;
+
+    /// Open or close minter public sale
+    /// * minter_address - [`&str`]
+    /// * open - [`bool`]
+    ///
+    async fn update_minter_public_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError>;
+
+    /// Set minter as sold out
+    /// * minter_address - [`&str`]
+    /// * sold_out - [`bool`]
+    ///
+    async fn update_minter_sold_out(
+        &mut self,
+        minter_address: &str,
+        sold_out: bool,
+    ) -> Result<(), DomainError>;
+}
+
+/// `customer_approval` projection, operators allowed to move customer tokens
+#[async_trait::async_trait]
+pub trait CustomerApprovalRepository: Send {
+    /// Clear operator approved on whole token, only one can be approved at a time
+    /// * project_address - [`&str`]
+    /// * token_id - [`&U256`]
+    ///
+    async fn clear_token_approval(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Approve operator on whole token
+    /// * project_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * operator_address - [`&str`]
+    ///
+    async fn approve_token(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+    ) -> Result<(), DomainError>;
+
+    /// Set value operator is allowed to move out of token, zero revokes it
+    /// * project_address - [`&str`]
+    /// * token_id - [`&U256`]
+    /// * operator_address - [`&str`]
+    /// * value - [`&U256`]
+    ///
+    async fn approve_token_value(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+        value: &U256,
+    ) -> Result<(), DomainError>;
+
+    /// Approve or revoke operator on every token of owner
+    /// * project_address - [`&str`]
+    /// * owner_address - [`&str`]
+    /// * operator_address - [`&str`]
+    /// * approved - [`bool`]
+    ///
+    async fn set_approval_for_all(
+        &mut self,
+        project_address: &str,
+        owner_address: &str,
+        operator_address: &str,
+        approved: bool,
+    ) -> Result<(), DomainError>;
+}
+
+/// `project_metadata` projection, project uri and metadata fetched from it
+#[async_trait::async_trait]
+pub trait ProjectMetadataRepository: Send {
+    /// Replace project uri and metadata fetched from it, keys of project metadata which
+    /// are not part of fetched one are kept
+    /// * project_address - [`&str`]
+    /// * slot - [`Option<&U256>`]
+    /// * uri - [`&str`]
+    /// * metadata - [`&serde_json::Value`]
+    ///
+    async fn refresh_project_uri(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+        uri: &str,
+        metadata: &serde_json::Value,
+    ) -> Result<(), DomainError>;
+
+    /// Get distinct slots of project tokens within token id range, tokens unknown from
+    /// `customer_token` projection are left out
+    /// * project_address - [`&str`]
+    /// * from_token_id - [`&U256`]
+    /// * to_token_id - [`&U256`]
+    ///
+    async fn get_project_token_slots(
+        &mut self,
+        project_address: &str,
+        from_token_id: &U256,
+        to_token_id: &U256,
+    ) -> Result<Vec<U256>, DomainError>;
+}
+
+/// `project_absorption` projection, revisions of project absorption curves
+#[async_trait::async_trait]
+pub trait AbsorptionCurveRepository: Send {
+    /// Get current absorption curve of project
+    /// * project_address - [`&str`]
+    /// * slot - [`Option<&U256>`] - `None` for an ERC-721 project
+    ///
+    async fn get_current_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError>;
+
+    /// Get absorption curve stored on project when it was seeded as version 0 revision,
+    /// `None` if project has none
+    /// * project_address - [`&str`]
+    /// * slot - [`Option<&U256>`] - `None` for an ERC-721 project
+    ///
+    async fn get_seeded_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError>;
+
+    /// Store new revision of project absorption curve, it becomes project current curve
+    /// * curve - [`&AbsorptionCurve`]
+    ///
+    async fn add_absorption_curve(&mut self, curve: &AbsorptionCurve) -> Result<(), DomainError>;
+}
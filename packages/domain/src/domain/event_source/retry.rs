@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use super::DomainError;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How many times and how long to wait before consuming an event again
+/// when a consumer fails with a transient error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, first one included
+    pub max_attempts: u32,
+    /// Delay before second attempt, doubled on each following one
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// * `max_attempts` - [`u32`] - Total number of attempts, first one included
+    /// * `base_delay` - [`Duration`] - Delay before second attempt
+    /// * `max_delay` - [`Duration`] - Upper bound of exponential backoff
+    ///
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// Never retry
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO, Duration::ZERO)
+    }
+
+    /// Delay to wait before next attempt, `None` when event should not be retried
+    /// * `attempts` - [`u32`] - Number of failed attempts so far
+    /// * `error` - [`&DomainError`] - Error of last attempt
+    ///
+    pub fn backoff(&self, attempts: u32, error: &DomainError) -> Option<Duration> {
+        if !error.is_transient() || attempts >= self.max_attempts {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+
+        Some(
+            self.base_delay
+                .checked_mul(factor)
+                .unwrap_or(self.max_delay)
+                .min(self.max_delay),
+        )
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ATTEMPTS, DEFAULT_BASE_DELAY, DEFAULT_MAX_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{domain::event_source::DomainError, infrastructure::starknet::model::ModelError};
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_transient_errors_are_retried_with_exponential_backoff() {
+        let policy = RetryPolicy::new(4, Duration::from_millis(100), Duration::from_millis(300));
+        let error = DomainError::ModelError(ModelError::RateLimited);
+
+        assert_eq!(Some(Duration::from_millis(100)), policy.backoff(1, &error));
+        assert_eq!(Some(Duration::from_millis(200)), policy.backoff(2, &error));
+        assert_eq!(Some(Duration::from_millis(300)), policy.backoff(3, &error));
+        assert_eq!(None, policy.backoff(4, &error));
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(
+            None,
+            policy.backoff(1, &DomainError::ContractNotFound("0x01".to_owned()))
+        );
+        assert_eq!(
+            None,
+            RetryPolicy::none().backoff(1, &DomainError::ModelError(ModelError::RateLimited))
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use super::{DomainError, DomainEvent, Event};
+
+/// Normalizes a stored event of `from_version` to `from_version + 1`
+pub type UpcastFn = fn(DomainEvent) -> Result<DomainEvent, DomainError>;
+
+/// Upcasts events of a given type when their payload layout changed,
+/// e.g. after contract was upgraded.
+#[derive(Debug)]
+pub struct Upcaster {
+    /// Event type as stored in event store, e.g. `yielder.snapshot`
+    pub r#type: &'static str,
+    pub from_version: u32,
+    /// Class hash of the contract implementation which emits `from_version + 1` layout
+    pub implementation: &'static str,
+    pub upcast: UpcastFn,
+}
+
+/// Every upcaster, in order. Register one there when an event payload layout changes,
+/// events emitted once their contract was upgraded to its implementation are then stored with
+/// the next schema version.
+const UPCASTERS: &[Upcaster] = &[];
+
+/// Schema version of an event, from implementations its contract was upgraded to up to the
+/// event block. Events are stamped when stored so that blocks indexed after an upgrade, e.g.
+/// backfills, keep the layout they were emitted with.
+/// * `event` - [`&DomainEvent`]
+/// * `block` - [`u64`] - Block the event was emitted at
+/// * `history` - [`&ImplementationHistory`]
+///
+pub fn schema_version(event: &DomainEvent, block: u64, history: &ImplementationHistory) -> u32 {
+    let implementations = event
+        .from_address()
+        .map(|contract| history.implementations_at(contract, block))
+        .unwrap_or_default();
+    UpcasterChain::default().schema_version(&event.r#type, &implementations)
+}
+
+/// Implementations contracts were upgraded to, read from their `Upgraded` events.
+/// Proxies emit one when initialized as well, so the first implementation is known.
+#[derive(Debug, Default)]
+pub struct ImplementationHistory {
+    upgrades: HashMap<String, Vec<(u64, String)>>,
+}
+
+impl ImplementationHistory {
+    /// Record implementation carried by an `Upgraded` event, other events are ignored
+    /// * `event` - [`&DomainEvent`]
+    /// * `block` - [`u64`] - Block the event was emitted at
+    ///
+    pub fn record(&mut self, event: &DomainEvent, block: u64) {
+        if !event.r#type.is_upgraded() {
+            return;
+        }
+        if let (Ok(contract), Some(implementation)) = (event.from_address(), event.payload.get("0"))
+        {
+            self.add(contract, block, implementation);
+        }
+    }
+
+    /// Record contract upgrade
+    /// * `contract` - [`&str`]
+    /// * `block` - [`u64`]
+    /// * `implementation` - [`&str`] - Class hash of new implementation
+    ///
+    pub fn add(&mut self, contract: &str, block: u64, implementation: &str) {
+        self.upgrades
+            .entry(normalize_felt(contract))
+            .or_default()
+            .push((block, normalize_felt(implementation)));
+    }
+
+    /// Implementations contract was upgraded to up to given block, included
+    /// * `contract` - [`&str`]
+    /// * `block` - [`u64`]
+    ///
+    pub fn implementations_at(&self, contract: &str, block: u64) -> Vec<String> {
+        self.upgrades
+            .get(&normalize_felt(contract))
+            .map(|upgrades| {
+                upgrades
+                    .iter()
+                    .filter(|(upgraded_at, _)| *upgraded_at <= block)
+                    .map(|(_, implementation)| implementation.to_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Felts are compared without `0x` prefix and leading zeros
+fn normalize_felt(felt: &str) -> String {
+    felt.trim_start_matches("0x")
+        .trim_start_matches('0')
+        .to_lowercase()
+}
+
+/// Chain of [`Upcaster`] bringing replayed events to their current schema version
+#[derive(Debug)]
+pub struct UpcasterChain {
+    upcasters: &'static [Upcaster],
+}
+
+impl UpcasterChain {
+    pub fn new(upcasters: &'static [Upcaster]) -> Self {
+        Self { upcasters }
+    }
+
+    /// Schema version of events of given type emitted by a contract which was upgraded to
+    /// `implementations`, first one when none of them changed its layout
+    /// * `event` - [`&Event`]
+    /// * `implementations` - [`&[String]`] - Normalized class hashes
+    ///
+    pub fn schema_version(&self, event: &Event, implementations: &[String]) -> u32 {
+        let r#type = event.to_string();
+        self.upcasters
+            .iter()
+            .filter(|u| {
+                u.r#type == r#type && implementations.contains(&normalize_felt(u.implementation))
+            })
+            .map(|u| u.from_version + 1)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Current schema version of events of given type
+    /// * `event` - [`&Event`]
+    ///
+    pub fn current_version(&self, event: &Event) -> u32 {
+        let r#type = event.to_string();
+        1 + self.upcasters.iter().filter(|u| u.r#type == r#type).count() as u32
+    }
+
+    /// Upcast event one version at a time until its current schema version
+    /// * `event` - [`DomainEvent`]
+    ///
+    pub fn upcast(&self, mut event: DomainEvent) -> Result<DomainEvent, DomainError> {
+        let r#type = event.r#type.to_string();
+        while let Some(upcaster) = self
+            .upcasters
+            .iter()
+            .find(|u| u.r#type == r#type && u.from_version == event.schema_version)
+        {
+            event = (upcaster.upcast)(event)?;
+            event.schema_version = upcaster.from_version + 1;
+        }
+
+        if event.schema_version != self.current_version(&event.r#type) {
+            return Err(DomainError::UnsupportedSchemaVersion(
+                event.id,
+                event.schema_version,
+            ));
+        }
+        Ok(event)
+    }
+}
+
+impl Default for UpcasterChain {
+    fn default() -> Self {
+        Self::new(UPCASTERS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::event_source::{
+        fixtures::event, yielder::YielderEvents, DomainError, DomainEvent, Event,
+    };
+
+    use super::{ImplementationHistory, Upcaster, UpcasterChain};
+
+    /// Provision amount became a `Uint256`, time moves one felt further
+    fn provision_amount_to_u256(mut event: DomainEvent) -> Result<DomainEvent, DomainError> {
+        let time = event.payload.remove("2").unwrap_or_default();
+        event.payload.insert("2".to_owned(), "0x0".to_owned());
+        event.payload.insert("3".to_owned(), time);
+        Ok(event)
+    }
+
+    const UPCASTERS: &[Upcaster] = &[Upcaster {
+        r#type: "yielder.provision",
+        from_version: 1,
+        implementation: "0x0c1a55",
+        upcast: provision_amount_to_u256,
+    }];
+
+    #[test]
+    fn test_old_event_is_upcasted() {
+        let chain = UpcasterChain::new(UPCASTERS);
+        let provision = event(
+            "0x01_0",
+            Event::Yielder(YielderEvents::Provision),
+            "0x0123",
+            &["0x0abc", "0x64", "0x6553f100"],
+        );
+
+        let upcasted = chain.upcast(provision).unwrap();
+
+        assert_eq!(2, upcasted.schema_version);
+        assert_eq!(Some(&"0x6553f100".to_owned()), upcasted.payload.get("3"));
+        assert_eq!(
+            1,
+            chain.current_version(&Event::Yielder(YielderEvents::Snapshot))
+        );
+    }
+
+    #[test]
+    fn test_event_from_newer_schema_is_an_error() {
+        let mut provision = event(
+            "0x01_0",
+            Event::Yielder(YielderEvents::Provision),
+            "0x0123",
+            &["0x0abc", "0x64", "0x6553f100"],
+        );
+        provision.schema_version = 3;
+
+        assert!(matches!(
+            UpcasterChain::new(UPCASTERS).upcast(provision),
+            Err(DomainError::UnsupportedSchemaVersion(_, 3))
+        ));
+    }
+
+    #[test]
+    fn test_schema_version_follows_implementation_at_event_block() {
+        let chain = UpcasterChain::new(UPCASTERS);
+        let provision = Event::Yielder(YielderEvents::Provision);
+        let mut history = ImplementationHistory::default();
+        history.record(
+            &event(
+                "0x01_0",
+                Event::Yielder(YielderEvents::Upgraded),
+                "0x0123",
+                &["0x0a"],
+            ),
+            10,
+        );
+        history.record(
+            &event(
+                "0x02_0",
+                Event::Yielder(YielderEvents::Upgraded),
+                "0x0123",
+                &["0xc1a55"],
+            ),
+            20,
+        );
+
+        // events of blocks indexed after the upgrade keep their layout
+        assert_eq!(
+            1,
+            chain.schema_version(&provision, &history.implementations_at("0x0123", 15))
+        );
+        assert_eq!(
+            2,
+            chain.schema_version(&provision, &history.implementations_at("0x123", 20))
+        );
+        assert_eq!(
+            1,
+            chain.schema_version(&provision, &history.implementations_at("0x0456", 20))
+        );
+    }
+}
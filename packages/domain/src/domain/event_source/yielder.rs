@@ -2,28 +2,23 @@ use std::collections::HashMap;
 
 use crate::domain::Ulid;
 use apibara_core::starknet::v1alpha2::FieldElement;
-use deadpool_postgres::Transaction;
 use serde::{Deserialize, Serialize};
 use starknet::macros::selector;
 use tracing::error;
 
-use crate::{
-    domain::crypto::U256,
-    infrastructure::{
-        postgres::{
-            entity::{ActionType, FarmType, Snapshot},
-            event_source::{
-                add_provision_to_yielder, add_snapshot_to_yielder, append_customer_action,
-                find_related_project_address_and_slot, get_yielder_id_from_address,
-            },
-        },
-        starknet::model::felt_to_offset_datetime,
-    },
-};
+use crate::infrastructure::postgres::entity::{ActionType, FarmType, Snapshot};
+
+use crate::domain::registry::{ContractType, RegisteredContract};
 
 use super::{
-    event_bus::Consumer, get_event, to_filters, BlockMetadata, DomainError, DomainEvent, Event,
-    Filterable,
+    active_addresses, add_nested_paths,
+    event_bus::Consumer,
+    get_event,
+    payload::{self, FarmAction, Provision},
+    repository::{CustomerActionRepository, YielderRepository},
+    to_filters,
+    transaction::TransactionManager,
+    BlockMetadata, DomainError, DomainEvent, Event, Filterable,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,7 +35,7 @@ pub enum YielderEvents {
 #[derive(Debug)]
 pub struct YieldFilters {
     contracts: Vec<String>,
-    filters: HashMap<String, Vec<(String, Event)>>,
+    filters: HashMap<String, Vec<(Vec<String>, Event)>>,
 }
 
 impl YieldFilters {
@@ -59,39 +54,68 @@ impl Default for YieldFilters {
 
 /// Add [`Filterable`] behaviour on [`Yielder`]
 impl Filterable for YieldFilters {
-    fn to_filters(&self) -> Vec<(String, String)> {
+    fn to_filters(&self) -> Vec<(String, Vec<String>)> {
         to_filters(&self.filters)
     }
 
-    fn get_event(&mut self, contract_address: &str, event_key: &str) -> Option<Event> {
-        get_event(&mut self.filters, contract_address, event_key)
+    fn get_event(&mut self, contract_address: &str, keys: &[String]) -> Option<(Event, usize)> {
+        get_event(&mut self.filters, contract_address, keys)
     }
 
-    fn hydrate_from_file(&mut self, address_list: Vec<HashMap<String, String>>) {
-        let contracts = self.extract_addresses(address_list, &["yielder", "yielder_3525"]);
-        self.contracts = contracts;
+    fn add_nested_paths(&mut self, contract_address: &str, paths: &[Vec<String>]) {
+        add_nested_paths(&mut self.filters, contract_address, paths)
+    }
+
+    fn hydrate(&mut self, contracts: &[RegisteredContract]) {
+        self.contracts = active_addresses(
+            contracts,
+            &[ContractType::Yielder, ContractType::Yielder3525],
+        );
+        // filters are rebuilt so that deactivated contracts are dropped
+        self.filters.clear();
         for contract in self.contracts.iter() {
             self.filters.insert(
                 contract.to_string(),
                 [
                     (
-                        FieldElement::from_bytes(&selector!("Claim").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Upgraded").to_bytes_be())
+                                .to_string(),
+                        ],
+                        Event::Yielder(YielderEvents::Upgraded),
+                    ),
+                    (
+                        vec![
+                            FieldElement::from_bytes(&selector!("Claim").to_bytes_be()).to_string()
+                        ],
                         Event::Yielder(YielderEvents::Claim),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Deposit").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Deposit").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Yielder(YielderEvents::Deposit),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Withdraw").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Withdraw").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Yielder(YielderEvents::Withdraw),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Provision").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Provision").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Yielder(YielderEvents::Provision),
                     ),
                     (
-                        FieldElement::from_bytes(&selector!("Snapshot").to_bytes_be()).to_string(),
+                        vec![
+                            FieldElement::from_bytes(&selector!("Snapshot").to_bytes_be())
+                                .to_string(),
+                        ],
                         Event::Yielder(YielderEvents::Snapshot),
                     ),
                 ]
@@ -111,7 +135,10 @@ impl YielderClaimEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for YielderClaimEventConsumer {
+impl<Txn> Consumer<Txn> for YielderClaimEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Yielder(YielderEvents::Claim))
     }
@@ -120,30 +147,21 @@ impl Consumer<Transaction<'_>> for YielderClaimEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Yield).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Yield)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Yield,
             ActionType::Claim,
         )
@@ -163,7 +181,10 @@ impl YielderDepositEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for YielderDepositEventConsumer {
+impl<Txn> Consumer<Txn> for YielderDepositEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Yielder(YielderEvents::Deposit))
     }
@@ -172,30 +193,21 @@ impl Consumer<Transaction<'_>> for YielderDepositEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Yield).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Yield)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Yield,
             ActionType::Deposit,
         )
@@ -215,7 +227,10 @@ impl YielderProvisionEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for YielderProvisionEventConsumer {
+impl<Txn> Consumer<Txn> for YielderProvisionEventConsumer
+where
+    Txn: TransactionManager + YielderRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Yielder(YielderEvents::Provision))
     }
@@ -224,13 +239,11 @@ impl Consumer<Transaction<'_>> for YielderProvisionEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let yielder_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let yielder_id = match get_yielder_id_from_address(txn, yielder_address).await {
+        let yielder_address = event.from_address()?;
+        let provision = Provision::try_from(event)?;
+        let yielder_id = match txn.get_yielder_id_from_address(yielder_address).await {
             Some(id) => id,
             None => {
                 error!(
@@ -241,10 +254,8 @@ impl Consumer<Transaction<'_>> for YielderProvisionEventConsumer {
             }
         };
 
-        let amount = U256::from(FieldElement::from_hex(event.payload.get("1").unwrap()).unwrap());
-        let time = felt_to_offset_datetime(event.payload.get("2").expect("should have time"));
-
-        add_provision_to_yielder(txn, yielder_id, amount, time).await?;
+        txn.add_provision_to_yielder(yielder_id, provision.amount, provision.time)
+            .await?;
         Ok(())
     }
 }
@@ -259,7 +270,10 @@ impl YielderSnapshotEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for YielderSnapshotEventConsumer {
+impl<Txn> Consumer<Txn> for YielderSnapshotEventConsumer
+where
+    Txn: TransactionManager + YielderRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Yielder(YielderEvents::Snapshot))
     }
@@ -268,13 +282,11 @@ impl Consumer<Transaction<'_>> for YielderSnapshotEventConsumer {
         &self,
         event: &DomainEvent,
         _metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let yielder_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let yielder_id = match get_yielder_id_from_address(txn, yielder_address).await {
+        let yielder_address = event.from_address()?;
+        let payload = payload::Snapshot::try_from(event)?;
+        let yielder_id = match txn.get_yielder_id_from_address(yielder_address).await {
             Some(id) => id,
             None => {
                 error!(
@@ -285,90 +297,22 @@ impl Consumer<Transaction<'_>> for YielderSnapshotEventConsumer {
             }
         };
 
-        let previous_time =
-            felt_to_offset_datetime(event.payload.get("1").expect("should have previous_time"));
-        let current_time =
-            felt_to_offset_datetime(event.payload.get("5").expect("should have current_time"));
-
         let snapshot = Snapshot {
             id: Ulid::new(),
-            previous_time,
-            previous_project_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("2")
-                    .expect("should have previous_project_absorption"),
-            )
-            .unwrap()
-            .into(),
-            previous_offseter_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("3")
-                    .expect("should have previous_offseter_absorption"),
-            )
-            .unwrap()
-            .into(),
-            previous_yielder_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("4")
-                    .expect("should have previous_yielder_absorption"),
-            )
-            .unwrap()
-            .into(),
-            time: current_time,
-            current_project_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("6")
-                    .expect("should have current_project_absorption"),
-            )
-            .unwrap()
-            .into(),
-            current_offseter_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("7")
-                    .expect("should have current_offseter_absorption"),
-            )
-            .unwrap()
-            .into(),
-            current_yielder_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("8")
-                    .expect("should have current_yielder_absorption"),
-            )
-            .unwrap()
-            .into(),
-            project_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("9")
-                    .expect("should have project_absorption"),
-            )
-            .unwrap()
-            .into(),
-            offseter_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("10")
-                    .expect("should have offseter_absorption"),
-            )
-            .unwrap()
-            .into(),
-            yielder_absorption: FieldElement::from_hex(
-                event
-                    .payload
-                    .get("11")
-                    .expect("should have yielder_absorption"),
-            )
-            .unwrap()
-            .into(),
+            previous_time: payload.previous_time,
+            previous_project_absorption: payload.previous_project_absorption,
+            previous_offseter_absorption: payload.previous_offseter_absorption,
+            previous_yielder_absorption: payload.previous_yielder_absorption,
+            time: payload.current_time,
+            current_project_absorption: payload.current_project_absorption,
+            current_offseter_absorption: payload.current_offseter_absorption,
+            current_yielder_absorption: payload.current_yielder_absorption,
+            project_absorption: payload.project_absorption,
+            offseter_absorption: payload.offseter_absorption,
+            yielder_absorption: payload.yielder_absorption,
             yielder_id: Some(yielder_id),
         };
-        let _snapshot_added = add_snapshot_to_yielder(txn, &snapshot).await;
+        let _snapshot_added = txn.add_snapshot_to_yielder(&snapshot).await;
         Ok(())
     }
 }
@@ -383,7 +327,10 @@ impl YielderWithdrawEventConsumer {
 }
 
 #[async_trait::async_trait]
-impl Consumer<Transaction<'_>> for YielderWithdrawEventConsumer {
+impl<Txn> Consumer<Txn> for YielderWithdrawEventConsumer
+where
+    Txn: TransactionManager + CustomerActionRepository,
+{
     fn can_consume(&self, event: &Event) -> bool {
         matches!(event, Event::Yielder(YielderEvents::Withdraw))
     }
@@ -392,30 +339,21 @@ impl Consumer<Transaction<'_>> for YielderWithdrawEventConsumer {
         &self,
         event: &DomainEvent,
         metadata: &BlockMetadata,
-        txn: &mut Transaction,
+        txn: &mut Txn,
     ) -> Result<(), DomainError> {
-        let from_address = event
-            .metadata
-            .get("from_address")
-            .expect("should have from_address");
-        let (project_address, slot) =
-            find_related_project_address_and_slot(txn, &from_address, FarmType::Yield).await?;
-        let customer_address = event
-            .payload
-            .get("0")
-            .expect("should have customer_address");
-        let value = U256::from(
-            FieldElement::from_hex(event.payload.get("1").expect("should have value")).unwrap(),
-        );
+        let from_address = event.from_address()?;
+        let action = FarmAction::try_from(event)?;
+        let (project_address, slot) = txn
+            .find_related_project_address_and_slot(from_address, FarmType::Yield)
+            .await?;
 
-        append_customer_action(
-            txn,
+        txn.append_customer_action(
             event.id.as_str(),
             metadata.timestamp,
-            &customer_address,
+            &action.customer_address,
             &project_address,
             &slot,
-            &value,
+            &action.value,
             FarmType::Yield,
             ActionType::Withdraw,
         )
@@ -424,3 +362,134 @@ impl Consumer<Transaction<'_>> for YielderWithdrawEventConsumer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            crypto::U256,
+            event_source::{
+                event_bus::Consumer,
+                fixtures::{event, metadata},
+                transaction::TransactionManager,
+                DomainError, Event,
+            },
+        },
+        infrastructure::{in_memory::InMemoryProjectionStore, postgres::entity::FarmType},
+    };
+
+    use super::{
+        YielderDepositEventConsumer, YielderEvents, YielderProvisionEventConsumer,
+        YielderSnapshotEventConsumer,
+    };
+
+    const PROJECT: &str = "0x0456";
+    const YIELDER: &str = "0x0123";
+
+    fn store() -> InMemoryProjectionStore {
+        InMemoryProjectionStore::new().with_farm(
+            YIELDER,
+            FarmType::Yield,
+            PROJECT,
+            U256::from(1u64),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_deposit_is_appended_once() {
+        let store = store();
+        let mut txn = store.transaction();
+        let deposit = event(
+            "0x01_0",
+            Event::Yielder(YielderEvents::Deposit),
+            YIELDER,
+            &["0x0ABC", "0x64", "0x0"],
+        );
+
+        for _ in 0..2 {
+            YielderDepositEventConsumer::new()
+                .consume(&deposit, &metadata(), &mut txn)
+                .await
+                .unwrap();
+        }
+        txn.commit().await.unwrap();
+
+        let actions = store.projections().customer_actions;
+        assert_eq!(1, actions.len());
+        assert_eq!("0x0abc", actions[0].customer_address);
+        assert_eq!(PROJECT, actions[0].project_address);
+        assert_eq!("deposit", actions[0].action_type);
+        assert_eq!(U256::from(100u64), actions[0].value);
+    }
+
+    #[tokio::test]
+    async fn test_provision_and_snapshot_are_added_to_yielder() {
+        let store = store();
+        let mut txn = store.transaction();
+        let provision = event(
+            "0x01_0",
+            Event::Yielder(YielderEvents::Provision),
+            YIELDER,
+            &["0x0abc", "0x64", "0x6553f100"],
+        );
+        let snapshot = event(
+            "0x02_0",
+            Event::Yielder(YielderEvents::Snapshot),
+            YIELDER,
+            &[
+                "0x0abc",
+                "0x6553f100",
+                "0x1",
+                "0x2",
+                "0x3",
+                "0x6553f200",
+                "0x4",
+                "0x5",
+                "0x6",
+                "0x7",
+                "0x8",
+                "0x9",
+            ],
+        );
+
+        YielderProvisionEventConsumer::new()
+            .consume(&provision, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        YielderSnapshotEventConsumer::new()
+            .consume(&snapshot, &metadata(), &mut txn)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+
+        let projections = store.projections();
+        let yielder_id = projections.farms[0].id;
+        assert_eq!(1, projections.provisions.len());
+        assert_eq!(yielder_id, projections.provisions[0].yielder_id);
+        assert_eq!(U256::from(100u64), projections.provisions[0].amount);
+        assert_eq!(1, projections.snapshots.len());
+        assert_eq!(Some(yielder_id), projections.snapshots[0].yielder_id);
+        assert_eq!(
+            U256::from(9u64),
+            projections.snapshots[0].yielder_absorption
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_yielder_is_an_error() {
+        let store = InMemoryProjectionStore::new();
+        let mut txn = store.transaction();
+        let provision = event(
+            "0x01_0",
+            Event::Yielder(YielderEvents::Provision),
+            YIELDER,
+            &["0x0abc", "0x64", "0x6553f100"],
+        );
+
+        let result = YielderProvisionEventConsumer::new()
+            .consume(&provision, &metadata(), &mut txn)
+            .await;
+
+        assert!(matches!(result, Err(DomainError::ContractNotFound(_))));
+    }
+}
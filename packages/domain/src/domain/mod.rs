@@ -4,10 +4,12 @@ use self::crypto::U256;
 use postgres_types::FromSql;
 use sea_query::Nullable;
 use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime, PrimitiveDateTime};
 
 pub mod crypto;
 pub mod event_source;
 pub mod project;
+pub mod registry;
 
 pub trait Contract {}
 
@@ -21,12 +23,19 @@ impl Contract for Erc721 {}
 pub struct Erc3525;
 impl Contract for Erc3525 {}
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Ulid(ulid::Ulid);
 impl Ulid {
     pub fn new() -> Self {
         Self(ulid::Ulid::new())
     }
+
+    /// UTC time ulid was generated at, millisecond precision
+    pub fn created_at(&self) -> PrimitiveDateTime {
+        let created_at =
+            OffsetDateTime::UNIX_EPOCH + Duration::milliseconds(self.0.timestamp_ms() as i64);
+        PrimitiveDateTime::new(created_at.date(), created_at.time())
+    }
 }
 
 impl From<ulid::Ulid> for Ulid {
@@ -0,0 +1,166 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("unknown contract type {0}")]
+    UnknownContractType(String),
+}
+
+/// Kind of contract tracked by the indexer.
+/// Names are the keys historically used in `data/{network}.data.json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContractType {
+    Project,
+    Project3525,
+    Minter,
+    Minter3525,
+    Offseter,
+    Offseter3525,
+    Yielder,
+    Yielder3525,
+    Vester,
+    Badge,
+    Migrator,
+}
+
+impl ContractType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContractType::Project => "project",
+            ContractType::Project3525 => "project_3525",
+            ContractType::Minter => "minter",
+            ContractType::Minter3525 => "minter_3525",
+            ContractType::Offseter => "offseter",
+            ContractType::Offseter3525 => "offseter_3525",
+            ContractType::Yielder => "yielder",
+            ContractType::Yielder3525 => "yielder_3525",
+            ContractType::Vester => "vester",
+            ContractType::Badge => "badge",
+            ContractType::Migrator => "migrator",
+        }
+    }
+
+    pub fn is_project(&self) -> bool {
+        matches!(self, ContractType::Project | ContractType::Project3525)
+    }
+}
+
+impl Display for ContractType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ContractType {
+    type Error = RegistryError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "project" => Ok(ContractType::Project),
+            "project_3525" => Ok(ContractType::Project3525),
+            "minter" => Ok(ContractType::Minter),
+            "minter_3525" => Ok(ContractType::Minter3525),
+            "offseter" => Ok(ContractType::Offseter),
+            "offseter_3525" => Ok(ContractType::Offseter3525),
+            "yielder" => Ok(ContractType::Yielder),
+            "yielder_3525" => Ok(ContractType::Yielder3525),
+            "vester" => Ok(ContractType::Vester),
+            "badge" => Ok(ContractType::Badge),
+            "migrator" => Ok(ContractType::Migrator),
+            _ => Err(RegistryError::UnknownContractType(value.to_owned())),
+        }
+    }
+}
+
+/// Contract registry entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredContract {
+    pub address: String,
+    pub contract_type: ContractType,
+    pub network: String,
+    pub project_address: Option<String>,
+    pub slot: Option<u64>,
+    pub deployment_block: u64,
+    pub active: bool,
+}
+
+impl RegisteredContract {
+    /// Build registry entries out of legacy data file content.
+    /// Contracts listed within the same object as a project belong to that project.
+    /// * `network` - [`&str`]
+    /// * `content` - [`Vec<HashMap<String, String>>`] - Data file content
+    ///
+    pub fn from_data_content(
+        network: &str,
+        content: Vec<HashMap<String, String>>,
+    ) -> Result<Vec<Self>, RegistryError> {
+        let mut contracts = Vec::new();
+        for entry in content {
+            let mut group = Vec::new();
+            for (key, address) in entry.iter() {
+                // `address//suffix` annotations are not part of the address
+                let address = address.split("//").next().unwrap_or_default();
+                group.push((ContractType::try_from(key.as_str())?, address.to_owned()));
+            }
+            let project_address = group
+                .iter()
+                .find(|(contract_type, _)| contract_type.is_project())
+                .map(|(_, address)| address.to_owned());
+
+            for (contract_type, address) in group {
+                contracts.push(RegisteredContract {
+                    project_address: match contract_type.is_project() {
+                        true => None,
+                        false => project_address.clone(),
+                    },
+                    address,
+                    contract_type,
+                    network: network.to_owned(),
+                    slot: None,
+                    deployment_block: 0,
+                    active: true,
+                });
+            }
+        }
+        contracts.sort_by(|a, b| a.address.cmp(&b.address));
+
+        Ok(contracts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ContractType, RegisteredContract};
+
+    #[test]
+    fn test_contracts_are_linked_to_project_of_their_group() {
+        let content = vec![
+            HashMap::from([
+                ("project".to_owned(), "0x01".to_owned()),
+                ("minter".to_owned(), "0x02//v2".to_owned()),
+            ]),
+            HashMap::from([("yielder_3525".to_owned(), "0x03".to_owned())]),
+        ];
+
+        let contracts = RegisteredContract::from_data_content("mainnet", content).unwrap();
+
+        assert_eq!(3, contracts.len());
+        assert_eq!(None, contracts[0].project_address);
+        assert_eq!(ContractType::Minter, contracts[1].contract_type);
+        assert_eq!("0x02", contracts[1].address);
+        assert_eq!(Some("0x01".to_owned()), contracts[1].project_address);
+        assert_eq!(None, contracts[2].project_address);
+    }
+
+    #[test]
+    fn test_unknown_contract_type_is_rejected() {
+        let content = vec![HashMap::from([("vault".to_owned(), "0x01".to_owned())])];
+
+        assert!(RegisteredContract::from_data_content("mainnet", content).is_err());
+    }
+}
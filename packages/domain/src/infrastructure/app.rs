@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use apibara_core::node::v1alpha2::DataFinality;
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "carbonable-indexer")]
@@ -25,8 +26,20 @@ pub enum Commands {
         starting_block: Option<u64>,
         #[arg(long)]
         batch_size: Option<u64>,
+        #[arg(long, env = "APIBARA_FINALITY", value_enum, default_value_t = Finality::Accepted)]
+        finality: Finality,
         #[arg(long)]
         force: bool,
+        #[arg(long)]
+        record: Option<String>,
+    },
+    Replay {
+        #[arg(env = "NETWORK")]
+        network: String,
+        #[arg(env = "DATABASE_URL")]
+        database_uri: String,
+        #[arg(long)]
+        from: String,
     },
     Seed {
         #[arg(env = "NETWORK")]
@@ -36,6 +49,22 @@ pub enum Commands {
         #[arg(env = "DATABASE_URL")]
         database_uri: String,
     },
+    Registry {
+        #[arg(env = "NETWORK")]
+        network: String,
+        #[arg(env = "DATABASE_URL")]
+        database_uri: String,
+        #[command(subcommand)]
+        command: RegistryCommands,
+    },
+    FailedEvents {
+        #[arg(env = "NETWORK")]
+        network: String,
+        #[arg(env = "DATABASE_URL")]
+        database_uri: String,
+        #[command(subcommand)]
+        command: FailedEventCommands,
+    },
     EventStore {
         #[arg(env = "NETWORK")]
         network: String,
@@ -45,9 +74,99 @@ pub enum Commands {
         database_uri: String,
         #[arg(long)]
         flush: bool,
+        /// Only run given projections, every projection when omitted
+        #[arg(long)]
+        projection: Vec<String>,
+        /// Attempts of a consumer failing with a transient error before event is set aside
+        #[arg(long, env = "CONSUMER_RETRY_ATTEMPTS", default_value_t = 5)]
+        retry_attempts: u32,
+        /// Delay before first retry, doubled on each following one
+        #[arg(long, env = "CONSUMER_RETRY_DELAY_MS", default_value_t = 500)]
+        retry_delay_ms: u64,
+        #[command(subcommand)]
+        command: Option<ProjectionCommands>,
     },
 }
 
+/// Contract registry management
+#[derive(Debug, Subcommand, Clone)]
+pub enum RegistryCommands {
+    /// Register a contract or update its entry
+    Add {
+        address: String,
+        #[arg(long)]
+        contract_type: String,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long)]
+        slot: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        deployment_block: u64,
+    },
+    /// List registered contracts
+    List {
+        #[arg(long)]
+        all: bool,
+    },
+    /// Stop indexing a contract
+    Deactivate { address: String },
+    /// Import legacy `data/{network}.data.json` file
+    Import {
+        #[arg(long)]
+        from: Option<String>,
+    },
+}
+
+/// Dead-letter queue management
+#[derive(Debug, Subcommand, Clone)]
+pub enum FailedEventCommands {
+    /// List events projections failed to consume
+    List {
+        #[arg(long)]
+        projection: Option<String>,
+    },
+    /// Consume a failed event again
+    Retry {
+        id: Option<String>,
+        /// Retry every failed event
+        #[arg(long)]
+        all: bool,
+    },
+    /// Drop a failed event without consuming it
+    Discard { id: String },
+}
+
+/// Event store projections management
+#[derive(Debug, Subcommand, Clone)]
+pub enum ProjectionCommands {
+    /// List projections with their checkpoint and status
+    Projections,
+    /// Stop consuming events into a projection
+    Pause { projection: String },
+    /// Consume events into a paused projection again
+    Resume { projection: String },
+    /// Clear a projection and replay it from first event
+    Rebuild { projection: String },
+}
+
+/// Block finality the indexer streams data at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Finality {
+    Pending,
+    Accepted,
+    Finalized,
+}
+
+impl From<Finality> for DataFinality {
+    fn from(value: Finality) -> Self {
+        match value {
+            Finality::Pending => DataFinality::DataStatusPending,
+            Finality::Accepted => DataFinality::DataStatusAccepted,
+            Finality::Finalized => DataFinality::DataStatusFinalized,
+        }
+    }
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct Args {
     #[arg(env = "NETWORK")]
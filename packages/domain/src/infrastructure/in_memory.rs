@@ -1,6 +1,31 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use crate::domain::event_source::{DomainError, DomainEvent, StorageClientPool};
+use serde_json::Value;
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::{
+    domain::{
+        crypto::U256,
+        event_source::{
+            project::merge_project_metadata,
+            repository::{
+                AbsorptionCurveRepository, CustomerActionRepository, CustomerApprovalRepository,
+                CustomerTokenRepository, MinterSaleRepository, MinterStatusRepository,
+                ProjectMetadataRepository, ProjectRepository, YielderRepository,
+            },
+            transaction::{TransactionManager, TransactionManagerError},
+            DomainError, DomainEvent, StorageClientPool,
+        },
+        Ulid,
+    },
+    infrastructure::postgres::entity::{
+        AbsorptionCurve, ActionType, Airdrop, Buy, FarmType, OperatorApproval, Snapshot,
+        TokenApproval,
+    },
+};
 
 #[derive(Debug)]
 pub struct InMemoryDomainClientPool {
@@ -29,3 +54,736 @@ impl StorageClientPool for InMemoryDomainClientPool {
         Ok(self.client.lock().unwrap().clone())
     }
 }
+
+/// Customer token row of in memory `customer_token` projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryCustomerToken {
+    pub address: String,
+    pub project_address: String,
+    pub token_id: U256,
+    pub slot: Option<U256>,
+    pub value: Option<U256>,
+}
+
+/// Project slot row of in memory `project_value` and `project_metadata` projections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryProject {
+    pub id: Ulid,
+    pub address: String,
+    /// `None` for an ERC-721 project
+    pub slot: Option<U256>,
+    pub total_supply: Option<U256>,
+    pub project_value: Option<U256>,
+    pub slot_uri: Option<String>,
+    pub metadata: Option<Value>,
+    pub absorption_curve: Option<(Vec<PrimitiveDateTime>, Vec<U256>)>,
+}
+
+/// Yielder or offseter farming a project slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryFarm {
+    pub id: Ulid,
+    pub address: String,
+    pub farm_type: String,
+    pub project_address: String,
+    pub slot: U256,
+}
+
+/// Customer action row of in memory `customer_farm` projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryCustomerAction {
+    pub event_id: String,
+    pub event_timestamp: OffsetDateTime,
+    pub customer_address: String,
+    pub project_address: String,
+    pub slot: U256,
+    pub value: U256,
+    pub farm_type: String,
+    pub action_type: String,
+}
+
+/// Provision row of in memory `yielder_provision` projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryProvision {
+    pub yielder_id: Ulid,
+    pub amount: U256,
+    pub time: OffsetDateTime,
+}
+
+/// Minter selling a project, with its `minter_status` projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryMinter {
+    pub id: Ulid,
+    pub address: String,
+    pub pre_sale_open: bool,
+    pub public_sale_open: bool,
+    pub sold_out: bool,
+}
+
+/// Every projection built by consumers
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryProjections {
+    pub customer_tokens: Vec<InMemoryCustomerToken>,
+    pub projects: Vec<InMemoryProject>,
+    pub farms: Vec<InMemoryFarm>,
+    pub customer_actions: Vec<InMemoryCustomerAction>,
+    pub provisions: Vec<InMemoryProvision>,
+    pub snapshots: Vec<Snapshot>,
+    pub minters: Vec<InMemoryMinter>,
+    pub buys: Vec<Buy>,
+    pub airdrops: Vec<Airdrop>,
+    pub token_approvals: Vec<TokenApproval>,
+    pub operator_approvals: Vec<OperatorApproval>,
+    pub absorption_curves: Vec<AbsorptionCurve>,
+}
+
+impl InMemoryProjections {
+    fn customer_token_mut(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+    ) -> Option<&mut InMemoryCustomerToken> {
+        self.customer_tokens
+            .iter_mut()
+            .find(|t| t.project_address == contract_address && &t.token_id == token_id)
+    }
+
+    fn minter_mut(&mut self, minter_address: &str) -> Option<&mut InMemoryMinter> {
+        self.minters
+            .iter_mut()
+            .find(|m| m.address.eq_ignore_ascii_case(minter_address))
+    }
+
+    fn token_approval_mut(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+    ) -> &mut TokenApproval {
+        let position = self.token_approvals.iter().position(|a| {
+            a.project_address == project_address
+                && &a.token_id == token_id
+                && a.operator_address == operator_address
+        });
+        let position = position.unwrap_or_else(|| {
+            self.token_approvals.push(TokenApproval {
+                id: Ulid::new(),
+                project_address: project_address.to_owned(),
+                token_id: *token_id,
+                operator_address: operator_address.to_owned(),
+                approved: false,
+                value: None,
+            });
+            self.token_approvals.len() - 1
+        });
+        &mut self.token_approvals[position]
+    }
+
+    fn project_mut(&mut self, project_address: &str, slot: &U256) -> Option<&mut InMemoryProject> {
+        self.projects.iter_mut().find(|p| {
+            p.address.eq_ignore_ascii_case(project_address) && p.slot.as_ref() == Some(slot)
+        })
+    }
+}
+
+/// In memory projections storage, consumers write to it through [`InMemoryTransaction`]
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryProjectionStore {
+    projections: Arc<Mutex<InMemoryProjections>>,
+}
+
+impl InMemoryProjectionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register project slot, as seeding does
+    /// * address - [`&str`]
+    /// * slot - [`U256`]
+    ///
+    pub fn with_project(self, address: &str, slot: U256) -> Self {
+        self.with_seeded_project(address, Some(slot))
+    }
+
+    /// Register ERC-721 project, as seeding does
+    /// * address - [`&str`]
+    ///
+    pub fn with_erc721_project(self, address: &str) -> Self {
+        self.with_seeded_project(address, None)
+    }
+
+    fn with_seeded_project(self, address: &str, slot: Option<U256>) -> Self {
+        self.projections
+            .lock()
+            .unwrap()
+            .projects
+            .push(InMemoryProject {
+                id: Ulid::new(),
+                address: address.to_owned(),
+                slot,
+                total_supply: None,
+                project_value: None,
+                slot_uri: None,
+                metadata: None,
+                absorption_curve: None,
+            });
+        self
+    }
+
+    /// Register yielder or offseter farming project slot, as seeding does
+    /// * address - [`&str`]
+    /// * farm_type - [`FarmType`]
+    /// * project_address - [`&str`]
+    /// * slot - [`U256`]
+    ///
+    pub fn with_farm(
+        self,
+        address: &str,
+        farm_type: FarmType,
+        project_address: &str,
+        slot: U256,
+    ) -> Self {
+        self.projections.lock().unwrap().farms.push(InMemoryFarm {
+            id: Ulid::new(),
+            address: address.to_owned(),
+            farm_type: farm_type.to_string(),
+            project_address: project_address.to_owned(),
+            slot,
+        });
+        self
+    }
+
+    /// Register minter, as seeding does
+    /// * address - [`&str`]
+    ///
+    pub fn with_minter(self, address: &str) -> Self {
+        self.projections
+            .lock()
+            .unwrap()
+            .minters
+            .push(InMemoryMinter {
+                id: Ulid::new(),
+                address: address.to_owned(),
+                pre_sale_open: false,
+                public_sale_open: false,
+                sold_out: false,
+            });
+        self
+    }
+
+    /// Start a transaction on a copy of current projections
+    pub fn transaction(&self) -> InMemoryTransaction {
+        InMemoryTransaction {
+            projections: self.projections.clone(),
+            staged: self.projections(),
+        }
+    }
+
+    /// Copy of committed projections
+    pub fn projections(&self) -> InMemoryProjections {
+        self.projections.lock().unwrap().clone()
+    }
+}
+
+/// Writes to [`InMemoryProjectionStore`], visible to other transactions once committed.
+/// Last committed transaction wins.
+#[derive(Debug)]
+pub struct InMemoryTransaction {
+    projections: Arc<Mutex<InMemoryProjections>>,
+    staged: InMemoryProjections,
+}
+
+impl InMemoryTransaction {
+    /// Projections as seen by this transaction
+    pub fn projections(&self) -> &InMemoryProjections {
+        &self.staged
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionManager for InMemoryTransaction {
+    async fn commit(self) -> Result<(), TransactionManagerError> {
+        *self.projections.lock().unwrap() = self.staged;
+        Ok(())
+    }
+
+    async fn rollback(self) -> Result<(), TransactionManagerError> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CustomerTokenRepository for InMemoryTransaction {
+    async fn create_token_for_customer(
+        &mut self,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        self.staged.customer_tokens.push(InMemoryCustomerToken {
+            address: to.to_owned(),
+            project_address: contract_address.to_owned(),
+            token_id: *token_id,
+            slot: None,
+            value: None,
+        });
+        Ok(())
+    }
+
+    async fn update_token_owner(
+        &mut self,
+        from: &str,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        if let Some(token) = self
+            .staged
+            .customer_token_mut(contract_address, token_id)
+            .filter(|t| t.address == from)
+        {
+            token.address = to.to_owned();
+        }
+        Ok(())
+    }
+
+    async fn migrate_customer_token(
+        &mut self,
+        project_address: &str,
+        from_project_address: &str,
+        customer_address: &str,
+        token_id: &U256,
+        new_token_id: &U256,
+        slot: &U256,
+        value: &U256,
+    ) -> Result<(), DomainError> {
+        if let Some(token) = self
+            .staged
+            .customer_token_mut(from_project_address, token_id)
+            .filter(|t| t.address == customer_address)
+        {
+            token.project_address = project_address.to_owned();
+            token.token_id = *new_token_id;
+            token.slot = Some(*slot);
+            token.value = Some(*value);
+        }
+        Ok(())
+    }
+
+    async fn update_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError> {
+        match self.staged.customer_token_mut(contract_address, token_id) {
+            Some(token) => {
+                token.value = Some(token.value.unwrap_or_default() + value);
+                Ok(())
+            }
+            None => Err(DomainError::TokenNotFound(
+                contract_address.to_owned(),
+                token_id.to_string(),
+            )),
+        }
+    }
+
+    async fn decrease_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError> {
+        if let Some(token) = self.staged.customer_token_mut(contract_address, token_id) {
+            let old_value = token.value.unwrap_or_default();
+            token.value = Some(if old_value > value {
+                old_value - value
+            } else {
+                U256::zero()
+            });
+        }
+        Ok(())
+    }
+
+    async fn update_token_slot(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        slot: &U256,
+    ) -> Result<(), DomainError> {
+        if let Some(token) = self.staged.customer_token_mut(contract_address, token_id) {
+            token.slot = Some(*slot);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectRepository for InMemoryTransaction {
+    async fn get_project_slots(
+        &mut self,
+        project_address: &str,
+    ) -> Result<Vec<Option<U256>>, DomainError> {
+        Ok(self
+            .staged
+            .projects
+            .iter()
+            .filter(|p| p.address.eq_ignore_ascii_case(project_address))
+            .map(|p| p.slot)
+            .collect())
+    }
+
+    async fn update_project_total_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        total_value: &U256,
+    ) -> Result<(), DomainError> {
+        if let Some(project) = self.staged.project_mut(project_address, slot) {
+            project.total_supply = Some(*total_value);
+        }
+        Ok(())
+    }
+
+    async fn update_project_project_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        project_value: &U256,
+    ) -> Result<(), DomainError> {
+        if let Some(project) = self.staged.project_mut(project_address, slot) {
+            project.project_value = Some(*project_value);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl YielderRepository for InMemoryTransaction {
+    async fn get_yielder_id_from_address(&mut self, yielder_address: &str) -> Option<Ulid> {
+        let farm_type = FarmType::Yield.to_string();
+        self.staged
+            .farms
+            .iter()
+            .find(|f| f.address == yielder_address && f.farm_type == farm_type)
+            .map(|f| f.id)
+    }
+
+    async fn add_provision_to_yielder(
+        &mut self,
+        yielder_id: Ulid,
+        amount: U256,
+        time: OffsetDateTime,
+    ) -> Result<(), DomainError> {
+        self.staged.provisions.push(InMemoryProvision {
+            yielder_id,
+            amount,
+            time,
+        });
+        Ok(())
+    }
+
+    async fn add_snapshot_to_yielder(&mut self, snapshot: &Snapshot) -> Result<(), DomainError> {
+        self.staged.snapshots.push(snapshot.clone());
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CustomerActionRepository for InMemoryTransaction {
+    async fn find_related_project_address_and_slot(
+        &mut self,
+        address: &str,
+        farm_type: FarmType,
+    ) -> Result<(String, U256), DomainError> {
+        let farm_type = farm_type.to_string();
+        self.staged
+            .farms
+            .iter()
+            .find(|f| f.address == address && f.farm_type == farm_type)
+            .map(|f| (f.project_address.to_owned(), f.slot))
+            .ok_or_else(|| DomainError::ContractNotFound(address.to_owned()))
+    }
+
+    async fn append_customer_action(
+        &mut self,
+        event_id: &str,
+        event_timestamp: OffsetDateTime,
+        customer_address: &str,
+        project_address: &str,
+        slot: &U256,
+        value: &U256,
+        farm_type: FarmType,
+        action_type: ActionType,
+    ) -> Result<(), DomainError> {
+        if self
+            .staged
+            .customer_actions
+            .iter()
+            .any(|a| a.event_id == event_id)
+        {
+            return Ok(());
+        }
+        self.staged.customer_actions.push(InMemoryCustomerAction {
+            event_id: event_id.to_owned(),
+            event_timestamp,
+            customer_address: customer_address.to_lowercase(),
+            project_address: project_address.to_owned(),
+            slot: *slot,
+            value: *value,
+            farm_type: farm_type.to_string(),
+            action_type: action_type.to_string(),
+        });
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MinterSaleRepository for InMemoryTransaction {
+    async fn get_minter_id_from_address(
+        &mut self,
+        minter_address: &str,
+    ) -> Result<Option<Ulid>, DomainError> {
+        Ok(self
+            .staged
+            .minters
+            .iter()
+            .find(|m| m.address.eq_ignore_ascii_case(minter_address))
+            .map(|m| m.id))
+    }
+
+    async fn add_buy_to_minter(&mut self, buy: &Buy) -> Result<(), DomainError> {
+        if !self.staged.buys.iter().any(|b| b.event_id == buy.event_id) {
+            self.staged.buys.push(Buy {
+                address: buy.address.to_lowercase(),
+                ..buy.clone()
+            });
+        }
+        Ok(())
+    }
+
+    async fn add_airdrop_to_minter(&mut self, airdrop: &Airdrop) -> Result<(), DomainError> {
+        if !self
+            .staged
+            .airdrops
+            .iter()
+            .any(|a| a.event_id == airdrop.event_id)
+        {
+            self.staged.airdrops.push(Airdrop {
+                address: airdrop.address.to_lowercase(),
+                ..airdrop.clone()
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MinterStatusRepository for InMemoryTransaction {
+    async fn update_minter_pre_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError> {
+        let minter = self
+            .staged
+            .minter_mut(minter_address)
+            .ok_or_else(|| DomainError::ContractNotFound(minter_address.to_owned()))?;
+        minter.pre_sale_open = open;
+        Ok(())
+    }
+
+    async fn update_minter_public_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError> {
+        let minter = self
+            .staged
+            .minter_mut(minter_address)
+            .ok_or_else(|| DomainError::ContractNotFound(minter_address.to_owned()))?;
+        minter.public_sale_open = open;
+        Ok(())
+    }
+
+    async fn update_minter_sold_out(
+        &mut self,
+        minter_address: &str,
+        sold_out: bool,
+    ) -> Result<(), DomainError> {
+        let minter = self
+            .staged
+            .minter_mut(minter_address)
+            .ok_or_else(|| DomainError::ContractNotFound(minter_address.to_owned()))?;
+        minter.sold_out = sold_out;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CustomerApprovalRepository for InMemoryTransaction {
+    async fn clear_token_approval(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        self.staged
+            .token_approvals
+            .iter_mut()
+            .filter(|a| a.project_address == project_address && &a.token_id == token_id)
+            .for_each(|a| a.approved = false);
+        self.staged
+            .token_approvals
+            .retain(|a| a.approved || a.value.is_some());
+        Ok(())
+    }
+
+    async fn approve_token(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+    ) -> Result<(), DomainError> {
+        self.staged
+            .token_approval_mut(project_address, token_id, operator_address)
+            .approved = true;
+        Ok(())
+    }
+
+    async fn approve_token_value(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+        value: &U256,
+    ) -> Result<(), DomainError> {
+        self.staged
+            .token_approval_mut(project_address, token_id, operator_address)
+            .value = Some(*value).filter(|v| &U256::zero() != v);
+        self.staged
+            .token_approvals
+            .retain(|a| a.approved || a.value.is_some());
+        Ok(())
+    }
+
+    async fn set_approval_for_all(
+        &mut self,
+        project_address: &str,
+        owner_address: &str,
+        operator_address: &str,
+        approved: bool,
+    ) -> Result<(), DomainError> {
+        self.staged.operator_approvals.retain(|a| {
+            !(a.project_address == project_address
+                && a.owner_address == owner_address
+                && a.operator_address == operator_address)
+        });
+        if approved {
+            self.staged.operator_approvals.push(OperatorApproval {
+                id: Ulid::new(),
+                project_address: project_address.to_owned(),
+                owner_address: owner_address.to_owned(),
+                operator_address: operator_address.to_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectMetadataRepository for InMemoryTransaction {
+    async fn refresh_project_uri(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+        uri: &str,
+        metadata: &Value,
+    ) -> Result<(), DomainError> {
+        if let Some(project) =
+            self.staged.projects.iter_mut().find(|p| {
+                p.address.eq_ignore_ascii_case(project_address) && p.slot.as_ref() == slot
+            })
+        {
+            // ERC-3525 slots are named after their uri metadata
+            if slot.is_some() {
+                project.slot_uri = Some(uri.to_owned());
+            }
+            project.metadata = Some(merge_project_metadata(project.metadata.take(), metadata));
+        }
+        Ok(())
+    }
+
+    async fn get_project_token_slots(
+        &mut self,
+        project_address: &str,
+        from_token_id: &U256,
+        to_token_id: &U256,
+    ) -> Result<Vec<U256>, DomainError> {
+        let mut slots = vec![];
+        for token in self.staged.customer_tokens.iter().filter(|t| {
+            t.project_address.eq_ignore_ascii_case(project_address)
+                && *from_token_id <= t.token_id
+                && t.token_id <= *to_token_id
+        }) {
+            if let Some(slot) = token.slot.filter(|s| !slots.contains(s)) {
+                slots.push(slot);
+            }
+        }
+        Ok(slots)
+    }
+}
+
+#[async_trait::async_trait]
+impl AbsorptionCurveRepository for InMemoryTransaction {
+    async fn get_current_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError> {
+        Ok(self
+            .staged
+            .absorption_curves
+            .iter()
+            .filter(|c| {
+                c.project_address.eq_ignore_ascii_case(project_address) && c.slot.as_ref() == slot
+            })
+            .max_by_key(|c| c.version)
+            .cloned())
+    }
+
+    async fn get_seeded_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError> {
+        Ok(self
+            .staged
+            .projects
+            .iter()
+            .find(|p| p.address.eq_ignore_ascii_case(project_address) && p.slot.as_ref() == slot)
+            .and_then(|p| {
+                p.absorption_curve.clone().map(|(times, absorptions)| {
+                    AbsorptionCurve::seeded(p.id, &p.address, slot.copied(), times, absorptions)
+                })
+            }))
+    }
+
+    async fn add_absorption_curve(&mut self, curve: &AbsorptionCurve) -> Result<(), DomainError> {
+        // as `absorption_curve_project_idx` unique index does
+        if self.staged.absorption_curves.iter().any(|c| {
+            c.project_address
+                .eq_ignore_ascii_case(&curve.project_address)
+                && c.slot == curve.slot
+                && c.version == curve.version
+        }) {
+            return Err(DomainError::FailedToPersistEvent);
+        }
+        self.staged.absorption_curves.push(curve.clone());
+        if let Some(project) = self.staged.projects.iter_mut().find(|p| {
+            p.address.eq_ignore_ascii_case(&curve.project_address) && p.slot == curve.slot
+        }) {
+            project.absorption_curve = Some((curve.times.clone(), curve.absorptions.clone()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use deadpool_postgres::Object;
+use sea_query::{Expr, Order, PostgresQueryBuilder, Query};
+use sea_query_postgres::PostgresBinder;
+use time::OffsetDateTime;
+use tokio_postgres::Row;
+use tracing::{error, info};
+
+use crate::domain::{crypto::U256, Ulid};
+
+use super::{
+    checkpoint::delete_checkpoint,
+    entity::{EventStoreIden, IndexerBackfillIden},
+    PostgresError,
+};
+
+const BACKFILL_STREAM_INFIX: &str = "-backfill-";
+
+/// Whether checkpoint stream is the one of a backfill rather than a live stream
+/// * stream - [`&str`]
+///
+pub fn is_backfill_stream(stream: &str) -> bool {
+    stream.contains(BACKFILL_STREAM_INFIX)
+}
+
+/// Historical stream of contracts registered after the live stream went past their deployment block
+#[derive(Debug, Clone)]
+pub struct Backfill {
+    pub id: Ulid,
+    pub network: String,
+    pub contracts: Vec<String>,
+    pub start_block: u64,
+    /// Set once backfill caught up with live stream
+    pub end_block: Option<u64>,
+}
+
+impl Backfill {
+    /// Checkpoint stream name of the backfill
+    pub fn stream(&self) -> String {
+        format!("{}{}{}", self.network, BACKFILL_STREAM_INFIX, self.id)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.end_block.is_some()
+    }
+}
+
+impl TryFrom<&Row> for Backfill {
+    type Error = PostgresError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let start_block: U256 = row.get(3);
+        let end_block: Option<U256> = row.get(4);
+
+        Ok(Self {
+            id: row.get(0),
+            network: row.get(1),
+            contracts: serde_json::from_value(row.get(2))?,
+            start_block: start_block.into(),
+            end_block: end_block.map(|b| b.into()),
+        })
+    }
+}
+
+/// Schedule a backfill for given contracts
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+/// * contracts - [`&[String]`] - Contract addresses
+/// * start_block - [`u64`] - Lowest deployment block of contracts
+///
+pub async fn create_backfill(
+    client: &Object,
+    network: &str,
+    contracts: &[String],
+    start_block: u64,
+) -> Result<Backfill, PostgresError> {
+    let backfill = Backfill {
+        id: Ulid::new(),
+        network: network.to_owned(),
+        contracts: contracts.to_vec(),
+        start_block,
+        end_block: None,
+    };
+    let (sql, values) = Query::insert()
+        .into_table(IndexerBackfillIden::Table)
+        .columns([
+            IndexerBackfillIden::Id,
+            IndexerBackfillIden::Network,
+            IndexerBackfillIden::Contracts,
+            IndexerBackfillIden::StartBlock,
+            IndexerBackfillIden::CreatedAt,
+        ])
+        .values([
+            backfill.id.into(),
+            network.into(),
+            serde_json::to_value(contracts)?.into(),
+            U256::from(start_block).into(),
+            OffsetDateTime::now_utc().into(),
+        ])?
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => {
+            info!(
+                "Scheduled backfill {} from block {} for {:?}",
+                backfill.id, start_block, contracts
+            );
+            Ok(backfill)
+        }
+        Err(e) => {
+            error!("indexer_backfill.create: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Get every backfill of given network, oldest first
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+///
+pub async fn get_backfills(client: &Object, network: &str) -> Result<Vec<Backfill>, PostgresError> {
+    let (sql, values) = Query::select()
+        .columns([
+            IndexerBackfillIden::Id,
+            IndexerBackfillIden::Network,
+            IndexerBackfillIden::Contracts,
+            IndexerBackfillIden::StartBlock,
+            IndexerBackfillIden::EndBlock,
+        ])
+        .from(IndexerBackfillIden::Table)
+        .and_where(Expr::col(IndexerBackfillIden::Network).eq(network))
+        .order_by(IndexerBackfillIden::Id, Order::Asc)
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => rows.iter().map(Backfill::try_from).collect(),
+        Err(e) => {
+            error!("indexer_backfill.list: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Mark backfill as caught up with live stream and drop its checkpoint
+/// * client - [`&mut deadpool_postgres::Object`]
+/// * backfill - [`&Backfill`]
+/// * end_block - [`u64`] - Last block stored by backfill
+///
+pub async fn complete_backfill(
+    client: &mut Object,
+    backfill: &Backfill,
+    end_block: u64,
+) -> Result<(), PostgresError> {
+    let tx = client.transaction().await?;
+    let (sql, values) = Query::update()
+        .table(IndexerBackfillIden::Table)
+        .values([(IndexerBackfillIden::EndBlock, U256::from(end_block).into())])
+        .and_where(Expr::col(IndexerBackfillIden::Id).eq(backfill.id))
+        .build_postgres(PostgresQueryBuilder);
+    tx.execute(sql.as_str(), &values.as_params()).await?;
+    delete_checkpoint(&tx, &backfill.stream()).await?;
+    tx.commit().await?;
+
+    info!("Backfill {} caught up at block {}", backfill.id, end_block);
+    Ok(())
+}
+
+/// Remove a backfill once its contracts were merged into live stream.
+/// Events it stored are handed over to the live stream, which invalidates them from then on.
+/// * client - [`&mut deadpool_postgres::Object`]
+/// * backfill - [`&Backfill`]
+///
+pub async fn delete_backfill(
+    client: &mut Object,
+    backfill: &Backfill,
+) -> Result<(), PostgresError> {
+    let tx = client.transaction().await?;
+    let (sql, values) = Query::update()
+        .table(EventStoreIden::Table)
+        .values([(EventStoreIden::Stream, backfill.network.as_str().into())])
+        .and_where(Expr::col(EventStoreIden::Stream).eq(backfill.stream()))
+        .build_postgres(PostgresQueryBuilder);
+    tx.execute(sql.as_str(), &values.as_params()).await?;
+    let (sql, values) = Query::delete()
+        .from_table(IndexerBackfillIden::Table)
+        .and_where(Expr::col(IndexerBackfillIden::Id).eq(backfill.id))
+        .build_postgres(PostgresQueryBuilder);
+    tx.execute(sql.as_str(), &values.as_params()).await?;
+
+    match tx.commit().await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("indexer_backfill.delete: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use apibara_core::node::v1alpha2::Cursor;
+use deadpool_postgres::{Object, Transaction};
+use sea_query::{Expr, OnConflict, PostgresQueryBuilder, Query};
+use sea_query_postgres::PostgresBinder;
+use time::OffsetDateTime;
+use tracing::{debug, error};
+
+use crate::domain::crypto::U256;
+
+use super::{entity::IndexerCheckpointIden, PostgresError};
+
+/// Get stored apibara cursor for given stream
+/// * client - [`&deadpool_postgres::Object`]
+/// * stream - [`&str`] - Stream name, usually the network name
+///
+pub async fn get_checkpoint(
+    client: &Object,
+    stream: &str,
+) -> Result<Option<Cursor>, PostgresError> {
+    let (sql, values) = Query::select()
+        .columns([
+            IndexerCheckpointIden::OrderKey,
+            IndexerCheckpointIden::UniqueKey,
+        ])
+        .from(IndexerCheckpointIden::Table)
+        .and_where(Expr::col(IndexerCheckpointIden::Stream).eq(stream))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.query_opt(sql.as_str(), &values.as_params()).await {
+        Ok(Some(row)) => {
+            let order_key: U256 = row.get(0);
+            Ok(Some(Cursor {
+                order_key: order_key.into(),
+                unique_key: row.get(1),
+            }))
+        }
+        Ok(None) => Ok(None),
+        Err(e) => {
+            error!("indexer_checkpoint.get: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Store apibara cursor for given stream.
+/// Must be called within the transaction persisting the batch ending at `cursor`
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * stream - [`&str`] - Stream name, usually the network name
+/// * cursor - [`&Cursor`] - Last processed cursor
+///
+pub async fn store_checkpoint<'a>(
+    tx: &Transaction<'a>,
+    stream: &str,
+    cursor: &Cursor,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::insert()
+        .into_table(IndexerCheckpointIden::Table)
+        .columns([
+            IndexerCheckpointIden::Stream,
+            IndexerCheckpointIden::OrderKey,
+            IndexerCheckpointIden::UniqueKey,
+            IndexerCheckpointIden::UpdatedAt,
+        ])
+        .values([
+            stream.into(),
+            U256::from(cursor.order_key).into(),
+            cursor.unique_key.clone().into(),
+            OffsetDateTime::now_utc().into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(IndexerCheckpointIden::Stream)
+                .update_columns([
+                    IndexerCheckpointIden::OrderKey,
+                    IndexerCheckpointIden::UniqueKey,
+                    IndexerCheckpointIden::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => {
+            debug!("indexer_checkpoint.{}: {}", stream, cursor.order_key);
+            Ok(())
+        }
+        Err(e) => {
+            error!("indexer_checkpoint.store: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Delete stored cursor of given stream
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * stream - [`&str`] - Stream name
+///
+pub async fn delete_checkpoint<'a>(
+    tx: &Transaction<'a>,
+    stream: &str,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::delete()
+        .from_table(IndexerCheckpointIden::Table)
+        .and_where(Expr::col(IndexerCheckpointIden::Stream).eq(stream))
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("indexer_checkpoint.delete: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
@@ -1,6 +1,14 @@
-use crate::{domain::crypto::U256, infrastructure::view_model::customer::CustomerToken};
+use crate::{
+    domain::crypto::U256,
+    infrastructure::view_model::customer::{
+        CustomerApprovals, CustomerOperatorApproval, CustomerToken, CustomerTokenApproval,
+    },
+};
 
-use super::{entity::CustomerTokenIden, PostgresError};
+use super::{
+    entity::{CustomerTokenIden, OperatorApprovalIden, TokenApprovalIden},
+    PostgresError,
+};
 use deadpool_postgres::Pool;
 use sea_query::{Expr, Func, PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
@@ -101,4 +109,76 @@ impl PostgresCustomer {
             }
         }
     }
+
+    ///
+    /// Get operators approved on customer tokens, whole token, value or every token
+    /// * `wallet` - [`&str`] The wallet address.
+    ///
+    /// # Errors
+    /// * [`PostgresError`] - If query fails or if cannot get client pool.
+    ///
+    pub async fn get_customer_approvals(
+        &self,
+        wallet: &str,
+    ) -> Result<CustomerApprovals, PostgresError> {
+        let client = self.db_client_pool.get().await?;
+        let (sql, values) = Query::select()
+            .from(TokenApprovalIden::Table)
+            .columns([
+                (TokenApprovalIden::Table, TokenApprovalIden::ProjectAddress),
+                (TokenApprovalIden::Table, TokenApprovalIden::TokenId),
+                (TokenApprovalIden::Table, TokenApprovalIden::OperatorAddress),
+                (TokenApprovalIden::Table, TokenApprovalIden::Approved),
+                (TokenApprovalIden::Table, TokenApprovalIden::Value),
+            ])
+            .inner_join(
+                CustomerTokenIden::Table,
+                Expr::col((CustomerTokenIden::Table, CustomerTokenIden::ProjectAddress))
+                    .equals((TokenApprovalIden::Table, TokenApprovalIden::ProjectAddress))
+                    .and(
+                        Expr::col((CustomerTokenIden::Table, CustomerTokenIden::TokenId))
+                            .equals((TokenApprovalIden::Table, TokenApprovalIden::TokenId)),
+                    ),
+            )
+            .and_where(
+                Expr::expr(Func::lower(Expr::col((
+                    CustomerTokenIden::Table,
+                    CustomerTokenIden::Address,
+                ))))
+                .eq(Func::lower(wallet)),
+            )
+            .build_postgres(PostgresQueryBuilder);
+        let tokens = match client.query(&sql, &values.as_params()).await {
+            Ok(res) => res.into_iter().map(CustomerTokenApproval::from).collect(),
+            Err(e) => {
+                tracing::error!("error while fetching customer token approvals {:#?}", e);
+                return Err(e.into());
+            }
+        };
+
+        let (sql, values) = Query::select()
+            .from(OperatorApprovalIden::Table)
+            .columns([
+                OperatorApprovalIden::ProjectAddress,
+                OperatorApprovalIden::OperatorAddress,
+            ])
+            .and_where(
+                Expr::expr(Func::lower(Expr::col(OperatorApprovalIden::OwnerAddress)))
+                    .eq(Func::lower(wallet)),
+            )
+            .build_postgres(PostgresQueryBuilder);
+        match client.query(&sql, &values.as_params()).await {
+            Ok(res) => Ok(CustomerApprovals {
+                tokens,
+                operators: res
+                    .into_iter()
+                    .map(CustomerOperatorApproval::from)
+                    .collect(),
+            }),
+            Err(e) => {
+                tracing::error!("error while fetching customer operator approvals {:#?}", e);
+                Err(e.into())
+            }
+        }
+    }
 }
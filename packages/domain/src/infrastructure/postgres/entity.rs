@@ -160,7 +160,7 @@ impl<'a> FromSql<'a> for Event {
         raw: &'a [u8],
     ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
         let val = std::str::from_utf8(raw)?;
-        Ok(Event::from(val))
+        Event::try_from(val).map_err(|e| e.to_string().into())
     }
 
     accepts!(VARCHAR);
@@ -175,8 +175,7 @@ impl ToSql for Event {
     where
         Self: Sized,
     {
-        let s: &str = self.clone().into();
-        postgres_protocol::types::text_to_sql(s, out);
+        postgres_protocol::types::text_to_sql(&self.to_string(), out);
         Ok(postgres_types::IsNull::No)
     }
 
@@ -185,9 +184,7 @@ impl ToSql for Event {
 }
 impl From<Event> for sea_query::Value {
     fn from(value: Event) -> Self {
-        sea_query::Value::String(Some(Box::new(String::from(<Event as Into<&str>>::into(
-            value,
-        )))))
+        sea_query::Value::String(Some(Box::new(value.to_string())))
     }
 }
 
@@ -342,7 +339,7 @@ pub struct Offseter {
 }
 
 #[enum_def]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Snapshot {
     pub id: Ulid,
     pub previous_time: OffsetDateTime,
@@ -426,9 +423,11 @@ pub struct Transfer {
 }
 
 #[enum_def]
+#[derive(Debug, Clone)]
 pub struct Airdrop {
     pub id: Ulid,
     pub hash: String,
+    pub event_id: String,
     pub address: String,
     pub quantity: U256,
     pub time: PrimitiveDateTime,
@@ -437,11 +436,14 @@ pub struct Airdrop {
 }
 
 #[enum_def]
+#[derive(Debug, Clone)]
 pub struct Buy {
     pub id: Ulid,
     pub hash: String,
+    pub event_id: String,
     pub address: String,
     pub quantity: U256,
+    pub amount: Option<U256>,
     pub time: PrimitiveDateTime,
     pub block_id: U256,
     pub minter_id: Option<Ulid>,
@@ -494,6 +496,10 @@ pub struct EventStore {
     pub payload: serde_json::Value,
     pub r#type: Event,
     pub recorded_at: PrimitiveDateTime,
+    pub keys: Option<serde_json::Value>,
+    pub schema_version: i32,
+    /// Checkpoint stream which stored the event, `None` for the live stream before backfills
+    pub stream: Option<String>,
 }
 
 #[enum_def]
@@ -512,3 +518,150 @@ pub struct CustomerFarm {
 pub struct LastStoredEvent {
     pub id: Ulid,
 }
+
+#[enum_def]
+pub struct IndexerCheckpoint {
+    pub stream: String,
+    pub order_key: U256,
+    pub unique_key: Vec<u8>,
+    pub updated_at: PrimitiveDateTime,
+}
+
+#[enum_def]
+pub struct ContractRegistry {
+    pub network: String,
+    pub address: String,
+    pub contract_type: String,
+    pub project_address: Option<String>,
+    pub slot: Option<U256>,
+    pub deployment_block: U256,
+    pub active: bool,
+    pub created_at: PrimitiveDateTime,
+}
+
+#[enum_def]
+pub struct IndexerBackfill {
+    pub id: Ulid,
+    pub network: String,
+    pub contracts: serde_json::Value,
+    pub start_block: U256,
+    pub end_block: Option<U256>,
+    pub created_at: PrimitiveDateTime,
+}
+
+#[enum_def]
+pub struct ProjectionCheckpoint {
+    pub name: String,
+    pub last_event_id: Option<Ulid>,
+    pub status: String,
+    pub updated_at: PrimitiveDateTime,
+}
+
+#[enum_def]
+pub struct FailedEvent {
+    pub id: Ulid,
+    pub event_store_id: Ulid,
+    pub event_id: String,
+    pub projection: String,
+    pub consumer: String,
+    pub error: String,
+    pub attempts: i32,
+    pub created_at: PrimitiveDateTime,
+    pub updated_at: PrimitiveDateTime,
+}
+
+/// Unknown events a projection skipped, counted per emitting contract and selector
+#[enum_def]
+pub struct SkippedEvent {
+    pub id: Ulid,
+    pub projection: String,
+    pub contract_address: String,
+    pub selector: String,
+    pub count: i64,
+    pub last_event_id: String,
+    pub updated_at: PrimitiveDateTime,
+}
+
+/// Operator allowed to move a token, whole token on `Approval`, up to `value` on `ApprovalValue`
+#[enum_def]
+#[derive(Debug, Clone)]
+pub struct TokenApproval {
+    pub id: Ulid,
+    pub project_address: String,
+    pub token_id: U256,
+    pub operator_address: String,
+    pub approved: bool,
+    pub value: Option<U256>,
+}
+
+/// Operator allowed to move every token of owner, from `ApprovalForAll`
+#[enum_def]
+#[derive(Debug, Clone)]
+pub struct OperatorApproval {
+    pub id: Ulid,
+    pub project_address: String,
+    pub owner_address: String,
+    pub operator_address: String,
+}
+
+/// Event id of version 0 revisions, curve seeded with project before any `AbsorptionUpdate`
+pub const SEED_ABSORPTION_CURVE_EVENT_ID: &str = "seed";
+
+/// Revision of project absorption curve, a new one is stored each time curve changes on chain,
+/// version 0 being the curve seeded with project. `slot` is `None` for an ERC-721 project
+#[enum_def]
+#[derive(Debug, Clone)]
+pub struct AbsorptionCurve {
+    pub id: Ulid,
+    pub project_address: String,
+    pub slot: Option<U256>,
+    pub version: i32,
+    pub times: Vec<PrimitiveDateTime>,
+    pub absorptions: Vec<U256>,
+    pub event_id: String,
+    pub time: PrimitiveDateTime,
+}
+
+impl AbsorptionCurve {
+    /// Version 0 revision, curve stored on project when it was seeded.
+    /// Project id is reused and project creation time is the revision time
+    /// * project_id - [`Ulid`]
+    /// * project_address - [`&str`]
+    /// * slot - [`Option<U256>`] - `None` for an ERC-721 project
+    /// * times - [`Vec<PrimitiveDateTime>`]
+    /// * absorptions - [`Vec<U256>`]
+    ///
+    pub fn seeded(
+        project_id: Ulid,
+        project_address: &str,
+        slot: Option<U256>,
+        times: Vec<PrimitiveDateTime>,
+        absorptions: Vec<U256>,
+    ) -> Self {
+        Self {
+            id: project_id,
+            project_address: project_address.to_owned(),
+            slot,
+            version: 0,
+            times,
+            absorptions,
+            event_id: SEED_ABSORPTION_CURVE_EVENT_ID.to_owned(),
+            time: project_id.created_at(),
+        }
+    }
+}
+
+impl From<tokio_postgres::Row> for AbsorptionCurve {
+    fn from(value: tokio_postgres::Row) -> Self {
+        Self {
+            id: value.get(0),
+            project_address: value.get(1),
+            slot: value.get(2),
+            version: value.get(3),
+            times: value.get(4),
+            absorptions: value.get(5),
+            event_id: value.get(6),
+            time: value.get(7),
+        }
+    }
+}
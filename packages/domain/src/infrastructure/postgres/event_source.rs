@@ -1,25 +1,31 @@
 use crate::domain::Ulid;
 use deadpool_postgres::Transaction;
 use deadpool_postgres::{GenericClient, Object, Pool};
-use sea_query::{Expr, Func, Iden, PostgresQueryBuilder, Query};
+use sea_query::{Expr, Func, Iden, OnConflict, Order, PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
 use serde_json::json;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, PrimitiveDateTime};
 use tokio_postgres::error::SqlState;
 use tracing::{debug, error};
 
-use crate::domain::event_source::BlockMetadata;
+use crate::domain::event_source::repository::{
+    AbsorptionCurveRepository, CustomerActionRepository, CustomerApprovalRepository,
+    CustomerTokenRepository, MinterSaleRepository, MinterStatusRepository,
+    ProjectMetadataRepository, ProjectRepository, YielderRepository,
+};
+use crate::domain::event_source::{project::merge_project_metadata, BlockMetadata};
 use crate::domain::{
     crypto::U256,
-    event_source::{DomainError, DomainEvent, StorageClientPool},
+    event_source::{upcaster::schema_version, DomainError, DomainEvent, StorageClientPool},
 };
 use std::sync::Arc;
 
 use super::entity::{
-    ActionType, CustomerFarmIden, EventStoreIden, FarmType, OffseterIden, ProjectIden,
-    ProvisionIden, Snapshot, SnapshotIden, YielderIden,
+    AbsorptionCurve, AbsorptionCurveIden, ActionType, Airdrop, AirdropIden, Buy, BuyIden,
+    CustomerFarmIden, EventStoreIden, FarmType, MinterIden, OffseterIden, OperatorApprovalIden,
+    ProjectIden, ProvisionIden, Snapshot, SnapshotIden, TokenApprovalIden, UriIden, YielderIden,
 };
-use super::{entity::CustomerTokenIden, PostgresError};
+use super::{entity::CustomerTokenIden, event_store::get_implementation_history, PostgresError};
 
 pub struct PgDecodeFn;
 impl Iden for PgDecodeFn {
@@ -47,20 +53,34 @@ impl StorageClientPool for PostgresStorageClientPool {
     }
 }
 
-/// Insert last domain event after all consumers did properly handled last domain event
+/// Maximum number of events inserted per statement to stay below postgres bind parameters limit
+const EVENT_INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Bulk insert domain events into event store.
+/// Events that were already stored are ignored.
 ///
 /// * tx: [`deadpool_postgres::Transaction`]
-/// * event: [`DomainEvent`]
-pub async fn insert_last_domain_event<'a>(
+/// * events: [`&[(DomainEvent, BlockMetadata)]`]
+/// * stream: [`&str`] - Checkpoint stream storing the events
+pub async fn insert_domain_events<'a>(
     tx: &Transaction<'a>,
-    event: &DomainEvent,
-    metadata: &BlockMetadata,
-) -> Result<(), PostgresError> {
-    let id = Ulid::new();
-    let block_number = U256::from(metadata.number);
-    let (sql, values) = Query::insert()
-        .into_table(EventStoreIden::Table)
-        .columns([
+    events: &[(DomainEvent, BlockMetadata)],
+    stream: &str,
+) -> Result<u64, PostgresError> {
+    // Schema version follows contract implementation at event block, upgrades of the batch
+    // included, so that blocks indexed after an upgrade keep the layout they were emitted with
+    let contracts: Vec<String> = events
+        .iter()
+        .filter_map(|(event, _)| event.from_address().ok().map(|a| a.to_owned()))
+        .collect();
+    let mut history = get_implementation_history(tx, &contracts).await?;
+
+    // Event store is read ordered by id, ids have to be monotonic within a batch
+    let mut generator = ulid::Generator::new();
+    let mut inserted = 0;
+    for chunk in events.chunks(EVENT_INSERT_CHUNK_SIZE) {
+        let mut query = Query::insert();
+        query.into_table(EventStoreIden::Table).columns([
             EventStoreIden::Id,
             EventStoreIden::EventId,
             EventStoreIden::BlockNumber,
@@ -69,50 +89,53 @@ pub async fn insert_last_domain_event<'a>(
             EventStoreIden::Payload,
             EventStoreIden::RType,
             EventStoreIden::RecordedAt,
-        ])
-        .values([
-            id.into(),
-            event.id.clone().into(),
-            block_number.into(),
-            metadata.hash.clone().into(),
-            sea_query::Value::Json(Some(Box::new(json!(&event.metadata)))).into(),
-            sea_query::Value::Json(Some(Box::new(json!(&event.payload)))).into(),
-            event.r#type.clone().into(),
-            metadata.timestamp.into(),
-        ])?
-        .build_postgres(PostgresQueryBuilder);
-
-    match tx.execute(sql.as_str(), &values.as_params()).await {
-        Ok(res) => {
-            debug!("event_store.domain_event.create: {:#?}", res);
-            Ok(())
+            EventStoreIden::Keys,
+            EventStoreIden::SchemaVersion,
+            EventStoreIden::Stream,
+        ]);
+        for (event, metadata) in chunk {
+            let schema_version = schema_version(event, metadata.number, &history);
+            history.record(event, metadata.number);
+            let id = Ulid::from(
+                generator
+                    .generate()
+                    .map_err(|_| PostgresError::UnexpectedError)?,
+            );
+            query.values([
+                id.into(),
+                event.id.clone().into(),
+                U256::from(metadata.number).into(),
+                metadata.hash.clone().into(),
+                sea_query::Value::Json(Some(Box::new(json!(&event.metadata)))).into(),
+                sea_query::Value::Json(Some(Box::new(json!(&event.payload)))).into(),
+                event.r#type.clone().into(),
+                metadata.timestamp.into(),
+                sea_query::Value::Json(Some(Box::new(json!(&event.keys)))).into(),
+                (schema_version as i32).into(),
+                stream.into(),
+            ])?;
         }
-        Err(e) => {
-            if e.code().eq(&Some(&SqlState::UNIQUE_VIOLATION)) {
-                debug!("event_store.domain_event.create: ignored due to duplication");
-                return Ok(());
+        let (sql, values) = query
+            .on_conflict(
+                OnConflict::column(EventStoreIden::EventId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_postgres(PostgresQueryBuilder);
+
+        match tx.execute(sql.as_str(), &values.as_params()).await {
+            Ok(res) => {
+                debug!("event_store.domain_event.create: {} events", res);
+                inserted += res;
+            }
+            Err(e) => {
+                error!("event_store.domain_event.create: {:#?}", e);
+                return Err(PostgresError::from(e));
             }
-            error!("event_store.domain_event.create: {:#?}", e);
-            Err(PostgresError::from(e))
         }
     }
-}
 
-/// Check if event was already processed by indexer.
-/// * client - [`&deadpool_postgres::Object`]
-/// * event_id - [`&str`]
-///
-pub async fn event_was_processed(client: &Object, event_id: &str) -> bool {
-    match client
-        .query_one(
-            r#"select id from event_store es where es.event_id = $1"#,
-            &[&event_id],
-        )
-        .await
-    {
-        Ok(_r) => true,
-        Err(_e) => false,
-    }
+    Ok(inserted)
 }
 
 /// From blockchain `Transfer` event feeds data into database
@@ -465,6 +488,165 @@ pub async fn get_yielder_id_from_address<'a>(
     }
 }
 
+/// Get minter id from blockchain address, `None` when minter is unknown
+///
+/// * tx: [`deadpool_postgres::Object`]
+/// * minter_address: [`&str`]
+///
+pub async fn get_minter_id_from_address<'a>(
+    tx: &Transaction<'a>,
+    minter_address: &str,
+) -> Result<Option<Ulid>, PostgresError> {
+    let (sql, values) = Query::select()
+        .from(MinterIden::Table)
+        .column(MinterIden::Id)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                MinterIden::Table,
+                MinterIden::Address,
+            ))))
+            .eq(Func::lower(minter_address)),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.query_opt(&sql, &values.as_params()).await {
+        Ok(res) => Ok(res.map(|row| row.get::<usize, Ulid>(0))),
+        Err(e) => {
+            error!("minter.id: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// From blockchain `Buy` event updates database
+///
+/// * tx: [`deadpool_postgres::Object`]
+/// * buy: [`&Buy`]
+///
+pub async fn add_buy_to_minter<'a>(tx: &Transaction<'a>, buy: &Buy) -> Result<(), PostgresError> {
+    let (sql, values) = Query::insert()
+        .into_table(BuyIden::Table)
+        .columns([
+            BuyIden::Id,
+            BuyIden::Hash,
+            BuyIden::EventId,
+            BuyIden::Address,
+            BuyIden::Quantity,
+            BuyIden::Amount,
+            BuyIden::Time,
+            BuyIden::BlockId,
+            BuyIden::MinterId,
+        ])
+        .values([
+            buy.id.into(),
+            buy.hash.as_str().into(),
+            buy.event_id.as_str().into(),
+            Func::lower(buy.address.as_str()).into(),
+            buy.quantity.into(),
+            buy.amount.into(),
+            buy.time.into(),
+            buy.block_id.into(),
+            buy.minter_id.into(),
+        ])?
+        .on_conflict(OnConflict::column(BuyIden::EventId).do_nothing().to_owned())
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(res) => {
+            debug!("minter.buy: {:#?}", res);
+            Ok(())
+        }
+        Err(e) => {
+            error!("minter.buy: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// From blockchain `Airdrop` event updates database
+///
+/// * tx: [`deadpool_postgres::Object`]
+/// * airdrop: [`&Airdrop`]
+///
+pub async fn add_airdrop_to_minter<'a>(
+    tx: &Transaction<'a>,
+    airdrop: &Airdrop,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::insert()
+        .into_table(AirdropIden::Table)
+        .columns([
+            AirdropIden::Id,
+            AirdropIden::Hash,
+            AirdropIden::EventId,
+            AirdropIden::Address,
+            AirdropIden::Quantity,
+            AirdropIden::Time,
+            AirdropIden::BlockId,
+            AirdropIden::MinterId,
+        ])
+        .values([
+            airdrop.id.into(),
+            airdrop.hash.as_str().into(),
+            airdrop.event_id.as_str().into(),
+            Func::lower(airdrop.address.as_str()).into(),
+            airdrop.quantity.into(),
+            airdrop.time.into(),
+            airdrop.block_id.into(),
+            airdrop.minter_id.into(),
+        ])?
+        .on_conflict(
+            OnConflict::column(AirdropIden::EventId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(res) => {
+            debug!("minter.airdrop: {:#?}", res);
+            Ok(())
+        }
+        Err(e) => {
+            error!("minter.airdrop: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Update minter sale status from blockchain `PreSaleOpen`, `PublicSaleOpen`, `SoldOut`...
+/// events, returns the number of updated rows, `0` when minter is unknown
+/// * tx: [`deadpool_postgres::Object`]
+/// * minter_address: [`&str`]
+/// * column: [`MinterIden`] - `PreSaleOpen`, `PublicSaleOpen` or `SoldOut`
+/// * value: [`bool`]
+///
+pub async fn update_minter_sale_status<'a>(
+    tx: &Transaction<'a>,
+    minter_address: &str,
+    column: MinterIden,
+    value: bool,
+) -> Result<u64, PostgresError> {
+    let (sql, values) = Query::update()
+        .table(MinterIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                MinterIden::Table,
+                MinterIden::Address,
+            ))))
+            .eq(Func::lower(minter_address)),
+        )
+        .values([(column, value.into())])
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(&sql, &values.as_params()).await {
+        Ok(updated) => Ok(updated),
+        Err(e) => {
+            error!("minter.sale_status.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
 /// Update project total_supply when token is migrated
 /// * tx: [`deadpool_postgres::Object`]
 /// * contract_address: [`&str`]
@@ -531,6 +713,335 @@ pub async fn update_project_project_value<'a>(
     }
 }
 
+/// Get seeded slots of project, `None` for an ERC-721 project
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+///
+pub async fn get_project_slots<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+) -> Result<Vec<Option<U256>>, PostgresError> {
+    let (sql, values) = Query::select()
+        .column((ProjectIden::Table, ProjectIden::Slot))
+        .from(ProjectIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                ProjectIden::Table,
+                ProjectIden::Address,
+            ))))
+            .eq(Func::lower(project_address)),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => Ok(rows.iter().map(|row| row.get(0)).collect()),
+        Err(e) => {
+            error!("project.slots.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// Get distinct slots of project tokens within token id range
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * from_token_id: [`&U256`]
+/// * to_token_id: [`&U256`]
+///
+pub async fn get_project_token_slots<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    from_token_id: &U256,
+    to_token_id: &U256,
+) -> Result<Vec<U256>, PostgresError> {
+    let (sql, values) = Query::select()
+        .distinct()
+        .column((CustomerTokenIden::Table, CustomerTokenIden::Slot))
+        .from(CustomerTokenIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                CustomerTokenIden::Table,
+                CustomerTokenIden::ProjectAddress,
+            ))))
+            .eq(Func::lower(project_address)),
+        )
+        .and_where(
+            Expr::col((CustomerTokenIden::Table, CustomerTokenIden::TokenId))
+                .between(*from_token_id, *to_token_id),
+        )
+        .and_where(Expr::col((CustomerTokenIden::Table, CustomerTokenIden::Slot)).is_not_null())
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => Ok(rows.iter().map(|row| row.get(0)).collect()),
+        Err(e) => {
+            error!("project.token_slots.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// Replace project uri, its `uri` row and project metadata when uri is updated on chain
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * slot: [`Option<&U256>`] - `None` for an ERC-721 project
+/// * uri: [`&str`]
+/// * metadata: [`&serde_json::Value`] - metadata fetched from uri
+///
+pub async fn refresh_project_uri<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    slot: Option<&U256>,
+    uri: &str,
+    metadata: &serde_json::Value,
+) -> Result<(), PostgresError> {
+    let mut query = Query::select();
+    query
+        .columns([
+            (ProjectIden::Table, ProjectIden::Id),
+            (ProjectIden::Table, ProjectIden::UriId),
+            (ProjectIden::Table, ProjectIden::Metadata),
+        ])
+        .from(ProjectIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                ProjectIden::Table,
+                ProjectIden::Address,
+            ))))
+            .eq(Func::lower(project_address)),
+        );
+    match slot {
+        Some(slot) => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).eq(slot)),
+        None => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).is_null()),
+    };
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+    let project = match tx.query_opt(sql.as_str(), &values.as_params()).await? {
+        Some(project) => project,
+        None => return Ok(()),
+    };
+    let project_id: Ulid = project.get(0);
+    let uri_id: Option<Ulid> = project.get(1);
+    let stored: Option<serde_json::Value> = project.get(2);
+
+    let mut query = Query::update();
+    query
+        .table(ProjectIden::Table)
+        .and_where(Expr::col((ProjectIden::Table, ProjectIden::Id)).eq(project_id))
+        .values([(
+            ProjectIden::Metadata,
+            merge_project_metadata(stored, metadata).into(),
+        )]);
+    // ERC-3525 slots are named after their uri metadata when seeded
+    if slot.is_some() {
+        query.values([(ProjectIden::SlotUri, uri.into())]);
+        if let Some(name) = metadata.get("name").and_then(|n| n.as_str()) {
+            query.values([(ProjectIden::Name, name.into())]);
+        }
+    }
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+    if let Err(e) = tx.execute(sql.as_str(), &values.as_params()).await {
+        error!("project.metadata.error : {:#?}", e);
+        return Err(PostgresError::TokioPostgresError(e));
+    }
+
+    if let Some(uri_id) = uri_id {
+        let (sql, values) = Query::update()
+            .table(UriIden::Table)
+            .and_where(Expr::col(UriIden::Id).eq(uri_id))
+            .values([
+                (UriIden::Uri, uri.into()),
+                (UriIden::Data, metadata.clone().into()),
+            ])
+            .build_postgres(PostgresQueryBuilder);
+        if let Err(e) = tx.execute(sql.as_str(), &values.as_params()).await {
+            error!("project.uri.error : {:#?}", e);
+            return Err(PostgresError::TokioPostgresError(e));
+        }
+    }
+    Ok(())
+}
+
+/// Get current absorption curve of project, last revision stored
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * slot: [`Option<&U256>`] - `None` for an ERC-721 project
+///
+pub async fn get_current_absorption_curve<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    slot: Option<&U256>,
+) -> Result<Option<AbsorptionCurve>, PostgresError> {
+    let mut query = Query::select();
+    query
+        .columns([
+            AbsorptionCurveIden::Id,
+            AbsorptionCurveIden::ProjectAddress,
+            AbsorptionCurveIden::Slot,
+            AbsorptionCurveIden::Version,
+            AbsorptionCurveIden::Times,
+            AbsorptionCurveIden::Absorptions,
+            AbsorptionCurveIden::EventId,
+            AbsorptionCurveIden::Time,
+        ])
+        .from(AbsorptionCurveIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col(AbsorptionCurveIden::ProjectAddress)))
+                .eq(Func::lower(project_address)),
+        )
+        .order_by(AbsorptionCurveIden::Version, Order::Desc)
+        .limit(1);
+    match slot {
+        Some(slot) => query.and_where(Expr::col(AbsorptionCurveIden::Slot).eq(slot)),
+        None => query.and_where(Expr::col(AbsorptionCurveIden::Slot).is_null()),
+    };
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+
+    match tx.query_opt(sql.as_str(), &values.as_params()).await {
+        Ok(curve) => Ok(curve.map(AbsorptionCurve::from)),
+        Err(e) => {
+            error!("project.absorption_curve.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// Get absorption curve stored on project row as version 0 revision, the seeded one until
+/// a revision is stored
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * slot: [`Option<&U256>`] - `None` for an ERC-721 project
+///
+pub async fn get_seeded_absorption_curve<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    slot: Option<&U256>,
+) -> Result<Option<AbsorptionCurve>, PostgresError> {
+    let mut query = Query::select();
+    query
+        .columns([
+            (ProjectIden::Table, ProjectIden::Id),
+            (ProjectIden::Table, ProjectIden::Times),
+            (ProjectIden::Table, ProjectIden::Absorptions),
+        ])
+        .from(ProjectIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                ProjectIden::Table,
+                ProjectIden::Address,
+            ))))
+            .eq(Func::lower(project_address)),
+        )
+        .limit(1);
+    match slot {
+        Some(slot) => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).eq(slot)),
+        None => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).is_null()),
+    };
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+
+    match tx.query_opt(sql.as_str(), &values.as_params()).await {
+        Ok(row) => Ok(row.and_then(|r| {
+            match (
+                r.get::<usize, Option<Vec<PrimitiveDateTime>>>(1),
+                r.get::<usize, Option<Vec<U256>>>(2),
+            ) {
+                (Some(times), Some(absorptions)) => Some(AbsorptionCurve::seeded(
+                    r.get(0),
+                    project_address,
+                    slot.copied(),
+                    times,
+                    absorptions,
+                )),
+                _ => None,
+            }
+        })),
+        Err(e) => {
+            error!("project.absorption_curve.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// Store new revision of project absorption curve and make it project current curve
+/// * tx: [`deadpool_postgres::Object`]
+/// * curve: [`&AbsorptionCurve`]
+///
+pub async fn add_absorption_curve<'a>(
+    tx: &Transaction<'a>,
+    curve: &AbsorptionCurve,
+) -> Result<(), PostgresError> {
+    let times = sea_query::Value::Array(
+        sea_query::ArrayType::TimeDateTime,
+        Some(Box::new(
+            curve
+                .times
+                .iter()
+                .map(|t| sea_query::Value::TimeDateTime(Some(Box::new(*t))))
+                .collect(),
+        )),
+    );
+    let absorptions = sea_query::Value::Array(
+        sea_query::ArrayType::Bytes,
+        Some(Box::new(
+            curve.absorptions.iter().map(|a| a.into()).collect(),
+        )),
+    );
+
+    let (sql, values) = Query::insert()
+        .into_table(AbsorptionCurveIden::Table)
+        .columns([
+            AbsorptionCurveIden::Id,
+            AbsorptionCurveIden::ProjectAddress,
+            AbsorptionCurveIden::Slot,
+            AbsorptionCurveIden::Version,
+            AbsorptionCurveIden::Times,
+            AbsorptionCurveIden::Absorptions,
+            AbsorptionCurveIden::EventId,
+            AbsorptionCurveIden::Time,
+        ])
+        .values([
+            curve.id.into(),
+            curve.project_address.as_str().into(),
+            curve.slot.into(),
+            curve.version.into(),
+            times.clone().into(),
+            absorptions.clone().into(),
+            curve.event_id.as_str().into(),
+            curve.time.into(),
+        ])?
+        .build_postgres(PostgresQueryBuilder);
+    if let Err(e) = tx.execute(sql.as_str(), &values.as_params()).await {
+        error!("project.absorption_curve.error : {:#?}", e);
+        return Err(PostgresError::TokioPostgresError(e));
+    }
+
+    let mut query = Query::update();
+    query
+        .table(ProjectIden::Table)
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((
+                ProjectIden::Table,
+                ProjectIden::Address,
+            ))))
+            .eq(Func::lower(curve.project_address.as_str())),
+        )
+        .values([
+            (ProjectIden::Times, times.into()),
+            (ProjectIden::Absorptions, absorptions.into()),
+        ]);
+    match &curve.slot {
+        Some(slot) => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).eq(slot)),
+        None => query.and_where(Expr::col((ProjectIden::Table, ProjectIden::Slot)).is_null()),
+    };
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("project.absorption_curve.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
 /// Append customer action on farms
 /// * tx: [`deadpool_postgres::Object`]
 /// * customer_address: [`&str`]
@@ -590,6 +1101,214 @@ pub async fn append_customer_action<'a>(
     }
 }
 
+/// Remove token approvals left with neither whole token approval nor value
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * token_id: [`&U256`]
+///
+async fn delete_empty_token_approvals<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    token_id: &U256,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::delete()
+        .from_table(TokenApprovalIden::Table)
+        .and_where(Expr::col(TokenApprovalIden::ProjectAddress).eq(project_address))
+        .and_where(Expr::col(TokenApprovalIden::TokenId).eq(token_id))
+        .and_where(Expr::col(TokenApprovalIden::Approved).eq(false))
+        .and_where(Expr::col(TokenApprovalIden::Value).is_null())
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(&sql, &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("project.approval.cleanup.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// From blockchain `Approval` event with approved address 0, clear whole token approval
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * token_id: [`&U256`]
+///
+pub async fn clear_token_approval<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    token_id: &U256,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::update()
+        .table(TokenApprovalIden::Table)
+        .values([(TokenApprovalIden::Approved, false.into())])
+        .and_where(Expr::col(TokenApprovalIden::ProjectAddress).eq(project_address))
+        .and_where(Expr::col(TokenApprovalIden::TokenId).eq(token_id))
+        .build_postgres(PostgresQueryBuilder);
+
+    if let Err(e) = tx.execute(&sql, &values.as_params()).await {
+        error!("project.approval.error : {:#?}", e);
+        return Err(PostgresError::TokioPostgresError(e));
+    }
+
+    delete_empty_token_approvals(tx, project_address, token_id).await
+}
+
+/// From blockchain `Approval` event, approve operator on whole token
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * token_id: [`&U256`]
+/// * operator_address: [`&str`]
+///
+pub async fn approve_token<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    token_id: &U256,
+    operator_address: &str,
+) -> Result<(), PostgresError> {
+    let id = Ulid::new();
+    let (sql, values) = Query::insert()
+        .into_table(TokenApprovalIden::Table)
+        .columns([
+            TokenApprovalIden::Id,
+            TokenApprovalIden::ProjectAddress,
+            TokenApprovalIden::TokenId,
+            TokenApprovalIden::OperatorAddress,
+            TokenApprovalIden::Approved,
+        ])
+        .values([
+            id.into(),
+            project_address.into(),
+            token_id.into(),
+            operator_address.into(),
+            true.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([
+                TokenApprovalIden::ProjectAddress,
+                TokenApprovalIden::TokenId,
+                TokenApprovalIden::OperatorAddress,
+            ])
+            .update_column(TokenApprovalIden::Approved)
+            .to_owned(),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(&sql, &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("project.approval.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
+/// From blockchain `ApprovalValue` event, set value operator can move out of token
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * token_id: [`&U256`]
+/// * operator_address: [`&str`]
+/// * value: [`&U256`]
+///
+pub async fn approve_token_value<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    token_id: &U256,
+    operator_address: &str,
+    value: &U256,
+) -> Result<(), PostgresError> {
+    let id = Ulid::new();
+    let value = Some(*value).filter(|v| &U256::zero() != v);
+    let (sql, values) = Query::insert()
+        .into_table(TokenApprovalIden::Table)
+        .columns([
+            TokenApprovalIden::Id,
+            TokenApprovalIden::ProjectAddress,
+            TokenApprovalIden::TokenId,
+            TokenApprovalIden::OperatorAddress,
+            TokenApprovalIden::Value,
+        ])
+        .values([
+            id.into(),
+            project_address.into(),
+            token_id.into(),
+            operator_address.into(),
+            value.into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([
+                TokenApprovalIden::ProjectAddress,
+                TokenApprovalIden::TokenId,
+                TokenApprovalIden::OperatorAddress,
+            ])
+            .update_column(TokenApprovalIden::Value)
+            .to_owned(),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    if let Err(e) = tx.execute(&sql, &values.as_params()).await {
+        error!("project.approval_value.error : {:#?}", e);
+        return Err(PostgresError::TokioPostgresError(e));
+    }
+
+    delete_empty_token_approvals(tx, project_address, token_id).await
+}
+
+/// From blockchain `ApprovalForAll` event, approve or revoke operator on every owner token
+/// * tx: [`deadpool_postgres::Object`]
+/// * project_address: [`&str`]
+/// * owner_address: [`&str`]
+/// * operator_address: [`&str`]
+/// * approved: [`bool`]
+///
+pub async fn set_approval_for_all<'a>(
+    tx: &Transaction<'a>,
+    project_address: &str,
+    owner_address: &str,
+    operator_address: &str,
+    approved: bool,
+) -> Result<(), PostgresError> {
+    let (sql, values) = match approved {
+        true => Query::insert()
+            .into_table(OperatorApprovalIden::Table)
+            .columns([
+                OperatorApprovalIden::Id,
+                OperatorApprovalIden::ProjectAddress,
+                OperatorApprovalIden::OwnerAddress,
+                OperatorApprovalIden::OperatorAddress,
+            ])
+            .values([
+                Ulid::new().into(),
+                project_address.into(),
+                owner_address.into(),
+                operator_address.into(),
+            ])?
+            .on_conflict(
+                OnConflict::columns([
+                    OperatorApprovalIden::ProjectAddress,
+                    OperatorApprovalIden::OwnerAddress,
+                    OperatorApprovalIden::OperatorAddress,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .build_postgres(PostgresQueryBuilder),
+        false => Query::delete()
+            .from_table(OperatorApprovalIden::Table)
+            .and_where(Expr::col(OperatorApprovalIden::ProjectAddress).eq(project_address))
+            .and_where(Expr::col(OperatorApprovalIden::OwnerAddress).eq(owner_address))
+            .and_where(Expr::col(OperatorApprovalIden::OperatorAddress).eq(operator_address))
+            .build_postgres(PostgresQueryBuilder),
+    };
+
+    match tx.execute(&sql, &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("project.approval_for_all.error : {:#?}", e);
+            Err(PostgresError::TokioPostgresError(e))
+        }
+    }
+}
+
 /// Find related project data for given contract
 /// * tx: [`deadpool_postgres::Object`]
 /// * addrses: [`&str`]
@@ -644,3 +1363,318 @@ pub async fn find_related_project_address_and_slot<'a>(
         }
     }
 }
+
+#[async_trait::async_trait]
+impl CustomerTokenRepository for Transaction<'_> {
+    async fn create_token_for_customer(
+        &mut self,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(create_token_for_customer(self, contract_address, to, token_id).await?)
+    }
+
+    async fn update_token_owner(
+        &mut self,
+        from: &str,
+        contract_address: &str,
+        to: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(update_token_owner(self, from, contract_address, to, token_id).await?)
+    }
+
+    async fn migrate_customer_token(
+        &mut self,
+        project_address: &str,
+        from_project_address: &str,
+        customer_address: &str,
+        token_id: &U256,
+        new_token_id: &U256,
+        slot: &U256,
+        value: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(migrate_customer_token(
+            self,
+            project_address,
+            from_project_address,
+            customer_address,
+            token_id,
+            new_token_id,
+            slot,
+            value,
+        )
+        .await?)
+    }
+
+    async fn update_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError> {
+        Ok(update_token_value(self, contract_address, token_id, value).await?)
+    }
+
+    async fn decrease_token_value(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        value: U256,
+    ) -> Result<(), DomainError> {
+        Ok(decrease_token_value(self, contract_address, token_id, value).await?)
+    }
+
+    async fn update_token_slot(
+        &mut self,
+        contract_address: &str,
+        token_id: &U256,
+        slot: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(update_token_slot(self, contract_address, token_id, slot).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectRepository for Transaction<'_> {
+    async fn get_project_slots(
+        &mut self,
+        project_address: &str,
+    ) -> Result<Vec<Option<U256>>, DomainError> {
+        Ok(get_project_slots(self, project_address).await?)
+    }
+
+    async fn update_project_total_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        total_value: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(update_project_total_value(self, project_address, slot, total_value).await?)
+    }
+
+    async fn update_project_project_value(
+        &mut self,
+        project_address: &str,
+        slot: &U256,
+        project_value: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(update_project_project_value(self, project_address, slot, project_value).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl YielderRepository for Transaction<'_> {
+    async fn get_yielder_id_from_address(&mut self, yielder_address: &str) -> Option<Ulid> {
+        get_yielder_id_from_address(self, yielder_address).await
+    }
+
+    async fn add_provision_to_yielder(
+        &mut self,
+        yielder_id: Ulid,
+        amount: U256,
+        time: OffsetDateTime,
+    ) -> Result<(), DomainError> {
+        Ok(add_provision_to_yielder(self, yielder_id, amount, time).await?)
+    }
+
+    async fn add_snapshot_to_yielder(&mut self, snapshot: &Snapshot) -> Result<(), DomainError> {
+        Ok(add_snapshot_to_yielder(self, snapshot).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl CustomerActionRepository for Transaction<'_> {
+    async fn find_related_project_address_and_slot(
+        &mut self,
+        address: &str,
+        farm_type: FarmType,
+    ) -> Result<(String, U256), DomainError> {
+        Ok(find_related_project_address_and_slot(self, address, farm_type).await?)
+    }
+
+    async fn append_customer_action(
+        &mut self,
+        event_id: &str,
+        event_timestamp: OffsetDateTime,
+        customer_address: &str,
+        project_address: &str,
+        slot: &U256,
+        value: &U256,
+        farm_type: FarmType,
+        action_type: ActionType,
+    ) -> Result<(), DomainError> {
+        Ok(append_customer_action(
+            self,
+            event_id,
+            event_timestamp,
+            customer_address,
+            project_address,
+            slot,
+            value,
+            farm_type,
+            action_type,
+        )
+        .await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl MinterSaleRepository for Transaction<'_> {
+    async fn get_minter_id_from_address(
+        &mut self,
+        minter_address: &str,
+    ) -> Result<Option<Ulid>, DomainError> {
+        Ok(get_minter_id_from_address(self, minter_address).await?)
+    }
+
+    async fn add_buy_to_minter(&mut self, buy: &Buy) -> Result<(), DomainError> {
+        Ok(add_buy_to_minter(self, buy).await?)
+    }
+
+    async fn add_airdrop_to_minter(&mut self, airdrop: &Airdrop) -> Result<(), DomainError> {
+        Ok(add_airdrop_to_minter(self, airdrop).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl MinterStatusRepository for Transaction<'_> {
+    async fn update_minter_pre_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError> {
+        let updated =
+            update_minter_sale_status(self, minter_address, MinterIden::PreSaleOpen, open).await?;
+        minter_sale_status_updated(minter_address, updated)
+    }
+
+    async fn update_minter_public_sale_open(
+        &mut self,
+        minter_address: &str,
+        open: bool,
+    ) -> Result<(), DomainError> {
+        let updated =
+            update_minter_sale_status(self, minter_address, MinterIden::PublicSaleOpen, open)
+                .await?;
+        minter_sale_status_updated(minter_address, updated)
+    }
+
+    async fn update_minter_sold_out(
+        &mut self,
+        minter_address: &str,
+        sold_out: bool,
+    ) -> Result<(), DomainError> {
+        let updated =
+            update_minter_sale_status(self, minter_address, MinterIden::SoldOut, sold_out).await?;
+        minter_sale_status_updated(minter_address, updated)
+    }
+}
+
+/// Status events of a minter without row must not advance checkpoint silently
+/// * minter_address - [`&str`]
+/// * updated - [`u64`] affected rows
+///
+fn minter_sale_status_updated(minter_address: &str, updated: u64) -> Result<(), DomainError> {
+    if 0 == updated {
+        error!(
+            "minter.sale_status => did not find minter matching address: {}",
+            minter_address
+        );
+        return Err(DomainError::ContractNotFound(minter_address.to_string()));
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl CustomerApprovalRepository for Transaction<'_> {
+    async fn clear_token_approval(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(clear_token_approval(self, project_address, token_id).await?)
+    }
+
+    async fn approve_token(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+    ) -> Result<(), DomainError> {
+        Ok(approve_token(self, project_address, token_id, operator_address).await?)
+    }
+
+    async fn approve_token_value(
+        &mut self,
+        project_address: &str,
+        token_id: &U256,
+        operator_address: &str,
+        value: &U256,
+    ) -> Result<(), DomainError> {
+        Ok(approve_token_value(self, project_address, token_id, operator_address, value).await?)
+    }
+
+    async fn set_approval_for_all(
+        &mut self,
+        project_address: &str,
+        owner_address: &str,
+        operator_address: &str,
+        approved: bool,
+    ) -> Result<(), DomainError> {
+        Ok(set_approval_for_all(
+            self,
+            project_address,
+            owner_address,
+            operator_address,
+            approved,
+        )
+        .await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectMetadataRepository for Transaction<'_> {
+    async fn refresh_project_uri(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+        uri: &str,
+        metadata: &serde_json::Value,
+    ) -> Result<(), DomainError> {
+        Ok(refresh_project_uri(self, project_address, slot, uri, metadata).await?)
+    }
+
+    async fn get_project_token_slots(
+        &mut self,
+        project_address: &str,
+        from_token_id: &U256,
+        to_token_id: &U256,
+    ) -> Result<Vec<U256>, DomainError> {
+        Ok(get_project_token_slots(self, project_address, from_token_id, to_token_id).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl AbsorptionCurveRepository for Transaction<'_> {
+    async fn get_current_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError> {
+        Ok(get_current_absorption_curve(self, project_address, slot).await?)
+    }
+
+    async fn get_seeded_absorption_curve(
+        &mut self,
+        project_address: &str,
+        slot: Option<&U256>,
+    ) -> Result<Option<AbsorptionCurve>, DomainError> {
+        Ok(get_seeded_absorption_curve(self, project_address, slot).await?)
+    }
+
+    async fn add_absorption_curve(&mut self, curve: &AbsorptionCurve) -> Result<(), DomainError> {
+        Ok(add_absorption_curve(self, curve).await?)
+    }
+}
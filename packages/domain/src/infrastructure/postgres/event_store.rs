@@ -1,17 +1,33 @@
-use deadpool_postgres::{Object, Pool};
-use sea_query::{PostgresQueryBuilder, Query};
+use apibara_core::node::v1alpha2::Cursor;
+use crypto_bigint::Encoding;
+use deadpool_postgres::{GenericClient, Object, Pool};
+use sea_query::{Expr, PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
-use tracing::{error, info};
+use tokio_postgres::types::ToSql;
+use tracing::{error, info, warn};
 
 use crate::{
-    domain::{crypto::U256, Ulid},
+    domain::{
+        crypto::U256,
+        event_source::{
+            projection::Projection, upcaster::ImplementationHistory, DomainEvent, Event,
+        },
+        Ulid,
+    },
     infrastructure::{
-        postgres::{entity::EventStoreIden, PostgresError},
+        postgres::{
+            backfill::is_backfill_stream,
+            checkpoint::store_checkpoint,
+            entity::EventStoreIden,
+            projection::{reset_projection, revert_projection},
+            PostgresError,
+        },
         view_model::DomainEventViewModel,
     },
 };
 
-/// Get last block from event_store
+/// Get last block from event_store.
+/// Only used as a fallback when no checkpoint was stored for the stream yet.
 ///
 /// * `client_pool` - Postgres client pool
 /// * `cla_starting_block` - Starting block number defined by command line argument
@@ -44,6 +60,10 @@ pub async fn get_last_dispatched_block(
     }
 }
 
+/// `event_store` columns read into [`DomainEventViewModel`]
+const DOMAIN_EVENT_COLUMNS: &str =
+    "id, event_id, block_number, block_hash, metadata, payload, r_type, recorded_at, keys, schema_version";
+
 pub async fn batch_events(
     client: &Object,
     limit: i8,
@@ -56,8 +76,8 @@ pub async fn batch_events(
     match client
         .query(
             &format!(
-                "SELECT * FROM event_store WHERE id > $1 ORDER BY id LIMIT {}",
-                limit
+                "SELECT {} FROM event_store WHERE id > $1 ORDER BY id LIMIT {}",
+                DOMAIN_EVENT_COLUMNS, limit
             ),
             &[&key],
         )
@@ -71,61 +91,256 @@ pub async fn batch_events(
     }
 }
 
-pub async fn store_last_handled_event(
+/// Get event_store event by id
+/// * client - [`&deadpool_postgres::Object`]
+/// * id - [`Ulid`]
+///
+pub async fn get_stored_event(
     client: &Object,
-    key: Option<Ulid>,
-) -> Result<(), PostgresError> {
-    let key = match key {
-        Some(k) => k.to_string(),
-        None => "".to_owned(),
-    };
+    id: Ulid,
+) -> Result<Option<DomainEventViewModel>, PostgresError> {
     match client
-        .execute("UPDATE last_stored_event set id = $1", &[&key])
+        .query_opt(
+            &format!("SELECT {DOMAIN_EVENT_COLUMNS} FROM event_store WHERE id = $1"),
+            &[&id.to_string()],
+        )
         .await
     {
-        Ok(_) => Ok(()),
+        Ok(row) => Ok(row.map(|r| r.into())),
         Err(e) => {
-            error!("Failed to store last handled event : {:#?}", e);
+            error!("{:#?}", e);
             Err(PostgresError::from(e))
         }
     }
 }
-pub async fn get_last_handled_event(client: &Object) -> Option<Ulid> {
+
+/// Get last block every projection consumed events of
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn get_last_stored_event_block(client: &Object) -> Result<u64, PostgresError> {
+    match client.query_one("SELECT es.block_number from projection_checkpoint pc INNER JOIN event_store es on es.id = pc.last_event_id ORDER BY es.id ASC LIMIT 1", &[]).await {
+        Ok(row) => Ok(row.get::<usize, U256>(0).into()),
+        Err(_) => Err(PostgresError::FailedToFetchLastBlockNumber)
+    }
+}
+
+/// Rebuild every projection from scratch
+/// * client - [`&mut deadpool_postgres::Object`]
+///
+pub async fn clear_view_models(client: &mut Object) -> Result<(), PostgresError> {
+    let tx = client.transaction().await?;
+    for projection in Projection::all() {
+        reset_projection(&tx, projection).await?;
+    }
+    tx.commit().await?;
+
+    info!("View models cleared...");
+    Ok(())
+}
+
+/// Rollback event store after a chain reorganization.
+/// Deletes events stored by `stream` above `cursor` and rewinds its checkpoint to it, events of
+/// other streams are left to their own invalidation. Projections which already consumed some of
+/// the orphaned events are reverted, or reset when their read model cannot be reverted event by
+/// event, see [`revert_projection`].
+///
+/// * client - [`&mut deadpool_postgres::Object`]
+/// * stream - [`&str`] stream name the checkpoint is stored under
+/// * cursor - [`&Cursor`] last valid cursor
+///
+pub async fn invalidate_events_after_cursor(
+    client: &mut Object,
+    stream: &str,
+    cursor: &Cursor,
+) -> Result<u64, PostgresError> {
+    let block_number = cursor.order_key;
+    let block = U256::from(block_number);
+    // events stored before streams were recorded belong to the live stream
+    let orphaned = match is_backfill_stream(stream) {
+        true => "es.block_number > $1 AND es.stream = $2",
+        false => "es.block_number > $1 AND (es.stream = $2 OR es.stream IS NULL)",
+    };
+    let block_bytes = block.0.to_be_bytes().to_vec();
+    let params: [&(dyn ToSql + Sync); 2] = [&block_bytes, &stream];
+    let tx = client.transaction().await?;
+
+    let projected = tx
+        .query(
+            &format!(
+                r#"SELECT pc.name, array_agg(es.event_id) FROM projection_checkpoint pc INNER JOIN event_store es ON es.id <= pc.last_event_id AND {orphaned} GROUP BY pc.name"#
+            ),
+            &params,
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Projection::try_from(row.get::<usize, &str>(0))
+                .map(|projection| (projection, row.get::<usize, Vec<String>>(1)))
+        })
+        .collect::<Result<Vec<(Projection, Vec<String>)>, _>>()
+        .map_err(|e| {
+            error!("projection_checkpoint.name: {:#?}", e);
+            PostgresError::UnexpectedError
+        })?;
+
+    tx.execute(
+        &format!(
+            r#"DELETE FROM failed_event fe USING event_store es WHERE es.id = fe.event_store_id AND {orphaned}"#
+        ),
+        &params,
+    )
+    .await?;
+    let deleted = tx
+        .execute(
+            &format!(r#"DELETE FROM event_store es WHERE {orphaned}"#),
+            &params,
+        )
+        .await?;
+    store_checkpoint(&tx, stream, cursor).await?;
+
+    for (projection, event_ids) in projected {
+        if revert_projection(&tx, projection, &event_ids).await? {
+            info!(
+                "Projection {} reverted {} orphaned events",
+                projection,
+                event_ids.len()
+            );
+            continue;
+        }
+        reset_projection(&tx, projection).await?;
+        warn!(
+            "Projection {} already consumed orphaned events, it will be rebuilt",
+            projection
+        );
+    }
+
+    tx.commit().await?;
+    info!(
+        "Invalidated {} events stored by {} after block {}",
+        deleted, stream, block_number
+    );
+
+    Ok(deleted)
+}
+
+/// Count stored unknown events per event type, i.e. per contract and selector.
+/// Projections skip them, a non zero value means some events are not supported yet.
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn count_unknown_events(client: &Object) -> Result<Vec<(String, i64)>, PostgresError> {
     match client
-        .query_one("SELECT id FROM last_stored_event", &[])
+        .query(
+            r#"SELECT r_type, COUNT(id) FROM event_store WHERE r_type LIKE 'unknown.%' GROUP BY r_type ORDER BY r_type"#,
+            &[],
+        )
         .await
     {
-        Ok(r) => {
-            // little hack here as id in table last_event_store is either an empty string or a Ulid
-            let id: String = r.get(0);
-            if id.is_empty() {
-                return None;
-            }
-            Some(Ulid::from(id))
-        }
+        Ok(rows) => Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect()),
         Err(e) => {
-            error!("{e}");
-            None
+            error!("event_store.unknown.count: {:#?}", e);
+            Err(PostgresError::from(e))
         }
     }
 }
 
-pub async fn get_last_stored_event_block(client: &Object) -> Result<u64, PostgresError> {
-    match client.query_one("SELECT es.block_number from last_stored_event lse INNER JOIN event_store es on es.id = lse.id", &[]).await {
-        Ok(row) => Ok(row.get::<usize, U256>(0).into()),
-        Err(_) => Err(PostgresError::FailedToFetchLastBlockNumber)
+/// Get events stored as unknown, oldest first
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn get_unknown_events(
+    client: &Object,
+) -> Result<Vec<DomainEventViewModel>, PostgresError> {
+    match client
+        .query(
+            &format!(
+                r#"SELECT {DOMAIN_EVENT_COLUMNS} FROM event_store WHERE r_type LIKE 'unknown.%' ORDER BY id"#
+            ),
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => Ok(rows.into_iter().map(|row| row.into()).collect()),
+        Err(e) => {
+            error!("event_store.unknown.list: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
     }
 }
 
-pub async fn clear_view_models(client: &Object) -> Result<(), PostgresError> {
-    let _ = client.execute(r#"TRUNCATE TABLE customer_farm"#, &[]).await;
-    let _ = client
-        .execute(r#"TRUNCATE TABLE customer_token"#, &[])
-        .await;
-    let _ = client
-        .execute(r#"UPDATE last_stored_event set id = ''"#, &[])
-        .await;
+/// Store type, payload and schema version an unknown event was resolved to
+/// * client - [`&deadpool_postgres::Object`]
+/// * id - [`Ulid`] - event_store id of the event
+/// * event - [`&DomainEvent`] - Resolved event
+///
+pub async fn store_resolved_event(
+    client: &Object,
+    id: Ulid,
+    event: &DomainEvent,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::update()
+        .table(EventStoreIden::Table)
+        .values([
+            (
+                EventStoreIden::Payload,
+                sea_query::Value::Json(Some(Box::new(serde_json::json!(&event.payload)))).into(),
+            ),
+            (EventStoreIden::RType, event.r#type.clone().into()),
+            (
+                EventStoreIden::SchemaVersion,
+                (event.schema_version as i32).into(),
+            ),
+        ])
+        .and_where(Expr::col(EventStoreIden::Id).eq(id))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => {
+            info!(
+                "event_store.unknown: event {} resolved to {}",
+                event.id, event.r#type
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!("event_store.unknown.resolve: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
 
-    info!("View models cleared...");
-    Ok(())
+/// Implementations given contracts were upgraded to, from their stored `Upgraded` events
+/// * client - [`&impl GenericClient`]
+/// * contracts - [`&[String]`] - Contract addresses
+///
+pub async fn get_implementation_history<C: GenericClient>(
+    client: &C,
+    contracts: &[String],
+) -> Result<ImplementationHistory, PostgresError> {
+    let types: Vec<String> = Event::upgraded().iter().map(|e| e.to_string()).collect();
+    let contracts: Vec<String> = contracts.iter().map(|c| c.to_lowercase()).collect();
+    match client
+        .query(
+            r#"SELECT metadata->>'from_address', block_number, payload->>'0' FROM event_store
+            WHERE r_type = ANY($1) AND lower(metadata->>'from_address') = ANY($2)
+            ORDER BY block_number, id"#,
+            &[&types, &contracts],
+        )
+        .await
+    {
+        Ok(rows) => {
+            let mut history = ImplementationHistory::default();
+            for row in rows.iter() {
+                if let (Some(contract), Some(implementation)) = (
+                    row.get::<usize, Option<String>>(0),
+                    row.get::<usize, Option<String>>(2),
+                ) {
+                    history.add(&contract, row.get::<usize, U256>(1).into(), &implementation);
+                }
+            }
+            Ok(history)
+        }
+        Err(e) => {
+            error!("event_store.implementation_history: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
 }
@@ -0,0 +1,195 @@
+use deadpool_postgres::{Object, Transaction};
+use sea_query::{Expr, Order, PostgresQueryBuilder, Query};
+use sea_query_postgres::PostgresBinder;
+use time::PrimitiveDateTime;
+use tokio_postgres::Row;
+use tracing::{error, warn};
+
+use crate::domain::{event_source::projection::Projection, Ulid};
+
+use super::{entity::FailedEventIden, PostgresError};
+
+/// Event store event a projection failed to consume, projection moved on without it
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub id: Ulid,
+    pub event_store_id: Ulid,
+    pub event_id: String,
+    pub projection: Projection,
+    pub consumer: String,
+    pub error: String,
+    pub attempts: i32,
+    pub created_at: PrimitiveDateTime,
+    pub updated_at: PrimitiveDateTime,
+}
+
+impl TryFrom<&Row> for DeadLetter {
+    type Error = PostgresError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let projection: String = row.get(3);
+
+        Ok(Self {
+            id: row.get(0),
+            event_store_id: row.get(1),
+            event_id: row.get(2),
+            projection: Projection::try_from(projection.as_str()).map_err(|e| {
+                error!("failed_event.projection: {:#?}", e);
+                PostgresError::UnexpectedError
+            })?,
+            consumer: row.get(4),
+            error: row.get(5),
+            attempts: row.get(6),
+            created_at: row.get(7),
+            updated_at: row.get(8),
+        })
+    }
+}
+
+const DEAD_LETTER_COLUMNS: [FailedEventIden; 9] = [
+    FailedEventIden::Id,
+    FailedEventIden::EventStoreId,
+    FailedEventIden::EventId,
+    FailedEventIden::Projection,
+    FailedEventIden::Consumer,
+    FailedEventIden::Error,
+    FailedEventIden::Attempts,
+    FailedEventIden::CreatedAt,
+    FailedEventIden::UpdatedAt,
+];
+
+/// Record a failed consumption, attempts are incremented if event already failed for projection
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * projection - [`Projection`]
+/// * consumer - [`&str`] - Name of the failing consumer
+/// * event_store_id - [`Ulid`]
+/// * event_id - [`&str`] - Onchain event id
+/// * failure - [`&str`] - Error message
+///
+pub async fn store_failed_event<'a>(
+    tx: &Transaction<'a>,
+    projection: Projection,
+    consumer: &str,
+    event_store_id: Ulid,
+    event_id: &str,
+    failure: &str,
+) -> Result<(), PostgresError> {
+    match tx
+        .execute(
+            r#"INSERT INTO failed_event (id, event_store_id, event_id, projection, consumer, error, attempts, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 1, NOW(), NOW())
+            ON CONFLICT (event_store_id, projection) DO UPDATE
+            SET consumer = EXCLUDED.consumer, error = EXCLUDED.error, attempts = failed_event.attempts + 1, updated_at = NOW()"#,
+            &[
+                &Ulid::new().to_string(),
+                &event_store_id.to_string(),
+                &event_id,
+                &projection.as_str(),
+                &consumer,
+                &failure,
+            ],
+        )
+        .await
+    {
+        Ok(_) => {
+            warn!(
+                "failed_event.{}: {} failed on event {}: {}",
+                projection, consumer, event_id, failure
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!("failed_event.store: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Get failed events, oldest first
+/// * client - [`&deadpool_postgres::Object`]
+/// * projection - [`Option<Projection>`] - Only failed events of given projection when provided
+///
+pub async fn get_failed_events(
+    client: &Object,
+    projection: Option<Projection>,
+) -> Result<Vec<DeadLetter>, PostgresError> {
+    let mut query = Query::select();
+    query
+        .columns(DEAD_LETTER_COLUMNS)
+        .from(FailedEventIden::Table)
+        .order_by(FailedEventIden::EventStoreId, Order::Asc);
+    if let Some(projection) = projection {
+        query.and_where(Expr::col(FailedEventIden::Projection).eq(projection.as_str()));
+    }
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+
+    match client.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => rows.iter().map(DeadLetter::try_from).collect(),
+        Err(e) => {
+            error!("failed_event.list: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Get failed event by id
+/// * client - [`&deadpool_postgres::Object`]
+/// * id - [`Ulid`]
+///
+pub async fn get_failed_event(
+    client: &Object,
+    id: Ulid,
+) -> Result<Option<DeadLetter>, PostgresError> {
+    let (sql, values) = Query::select()
+        .columns(DEAD_LETTER_COLUMNS)
+        .from(FailedEventIden::Table)
+        .and_where(Expr::col(FailedEventIden::Id).eq(id))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.query_opt(sql.as_str(), &values.as_params()).await {
+        Ok(Some(row)) => Ok(Some(DeadLetter::try_from(&row)?)),
+        Ok(None) => Ok(None),
+        Err(e) => {
+            error!("failed_event.get: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Remove failed event once retried successfully or discarded
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * id - [`Ulid`]
+///
+pub async fn delete_failed_event<'a>(tx: &Transaction<'a>, id: Ulid) -> Result<u64, PostgresError> {
+    let (sql, values) = Query::delete()
+        .from_table(FailedEventIden::Table)
+        .and_where(Expr::col(FailedEventIden::Id).eq(id))
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(deleted) => Ok(deleted),
+        Err(e) => {
+            error!("failed_event.delete: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Count failed events per projection
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn count_failed_events(client: &Object) -> Result<Vec<(String, i64)>, PostgresError> {
+    match client
+        .query(
+            r#"SELECT projection, COUNT(id) FROM failed_event GROUP BY projection ORDER BY projection"#,
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect()),
+        Err(e) => {
+            error!("failed_event.count: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use deadpool_postgres::Pool;
+use deadpool_postgres::{Object, Pool};
 use sea_query::{Expr, PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
 use std::sync::Arc;
 use tokio_postgres::error::SqlState;
+use tracing::error;
 
 use crate::domain::Ulid;
 
@@ -87,3 +88,31 @@ impl PostgresImplementation {
         }
     }
 }
+
+/// Get ABI of every contract bound to an implementation
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn get_contract_abis(
+    client: &Object,
+) -> Result<Vec<(String, serde_json::Value)>, PostgresError> {
+    match client
+        .query(
+            r#"SELECT c.address, i.abi FROM (
+                SELECT address, implementation_id FROM project
+                UNION ALL SELECT address, implementation_id FROM minter
+                UNION ALL SELECT address, implementation_id FROM offseter
+                UNION ALL SELECT address, implementation_id FROM yielder
+                UNION ALL SELECT address, implementation_id FROM payment
+                UNION ALL SELECT address, implementation_id FROM badge
+            ) c INNER JOIN implementation i ON i.id = c.implementation_id"#,
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect()),
+        Err(e) => {
+            error!("implementation.abis: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
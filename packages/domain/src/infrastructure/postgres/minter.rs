@@ -1,7 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use crate::domain::Ulid;
-use deadpool_postgres::Pool;
+use deadpool_postgres::{Object, Pool};
 use sea_query::{Expr, PostgresQueryBuilder, Query};
 use sea_query_postgres::PostgresBinder;
 use tokio_postgres::error::SqlState;
@@ -9,7 +9,10 @@ use tracing::error;
 
 use crate::{
     domain::{crypto::U256, Contract, Erc3525, Erc721},
-    infrastructure::starknet::model::{StarknetValue, StarknetValueResolver},
+    infrastructure::{
+        starknet::model::{StarknetValue, StarknetValueResolver},
+        view_model::launchpad::{LaunchpadSales, MinterSale},
+    },
 };
 
 use super::{
@@ -202,3 +205,48 @@ impl PostgresMinter<Erc3525> {
         Ok(())
     }
 }
+
+/// Sales volume per payment token, buyers and daily mints of project minters, `None` if project
+/// has no minter
+/// * client - [`&deadpool_postgres::Object`]
+/// * slug - [`&str`]
+///
+pub async fn get_launchpad_sales(
+    client: &Object,
+    slug: &str,
+) -> Result<Option<LaunchpadSales>, PostgresError> {
+    match client
+        .query_opt(
+            r#"SELECT m.id FROM project p INNER JOIN minter m ON m.project_id = p.id WHERE p.slug = $1 LIMIT 1"#,
+            &[&slug],
+        )
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            error!("launchpad.sales.minter: {:#?}", e);
+            return Err(PostgresError::from(e));
+        }
+    };
+
+    match client
+        .query(
+            r#"SELECT b.address, b.quantity, b.amount, b.time, pay.address, pay.decimals, pay.symbol FROM buy b INNER JOIN minter m ON m.id = b.minter_id INNER JOIN project p ON p.id = m.project_id LEFT JOIN payment pay ON pay.id = m.payment_id WHERE p.slug = $1
+            UNION ALL
+            SELECT a.address, a.quantity, NULL, a.time, NULL, NULL, NULL FROM airdrop a INNER JOIN minter m ON m.id = a.minter_id INNER JOIN project p ON p.id = m.project_id WHERE p.slug = $1
+            ORDER BY time"#,
+            &[&slug],
+        )
+        .await
+    {
+        Ok(rows) => {
+            let sales: Vec<MinterSale> = rows.into_iter().map(MinterSale::from).collect();
+            Ok(Some(LaunchpadSales::new(&sales)))
+        }
+        Err(e) => {
+            error!("launchpad.sales: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
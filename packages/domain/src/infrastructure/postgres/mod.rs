@@ -1,14 +1,20 @@
+pub mod backfill;
 pub mod badge;
+pub mod checkpoint;
 pub mod customer;
 pub mod entity;
 pub mod event_source;
 pub mod event_store;
+pub mod failed_event;
 pub mod farming;
 pub mod implementation;
 pub mod minter;
 pub mod offseter;
 pub mod payment;
 pub mod project;
+pub mod projection;
+pub mod registry;
+pub mod skipped_event;
 pub mod uri;
 pub mod yielder;
 
@@ -41,7 +47,7 @@ use self::{
 };
 
 use super::{
-    seed::{project::ProjectSeeder, Seeder},
+    seed::project::ProjectSeeder,
     starknet::{
         get_proxy_abi,
         model::ModelError,
@@ -72,6 +78,39 @@ pub enum PostgresError {
     FailedToFetchLastBlockNumber,
 }
 
+impl PostgresError {
+    /// Whether running the same statements again later may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PostgresError::TokioPostgresError(e) => is_transient_tokio_postgres_error(e),
+            PostgresError::PoolError(_) => true,
+            PostgresError::ModelError(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+/// Connection losses, serialization failures and deadlocks are worth a retry
+/// * e - [`&tokio_postgres::Error`]
+///
+pub fn is_transient_tokio_postgres_error(e: &tokio_postgres::Error) -> bool {
+    if e.is_closed() {
+        return true;
+    }
+    match e.code() {
+        Some(code) => [
+            SqlState::T_R_SERIALIZATION_FAILURE,
+            SqlState::T_R_DEADLOCK_DETECTED,
+            SqlState::TOO_MANY_CONNECTIONS,
+            SqlState::CONNECTION_FAILURE,
+        ]
+        .contains(code),
+        None => std::error::Error::source(e)
+            .map(|source| source.is::<std::io::Error>())
+            .unwrap_or(false),
+    }
+}
+
 pub async fn get_connection(database_uri: Option<&str>) -> Result<Pool, PostgresError> {
     let db_env_uri = std::env::var("DATABASE_URL")?;
     let config = database_uri.unwrap_or(&db_env_uri).parse::<Config>()?;
@@ -133,7 +172,7 @@ pub async fn find_or_create_project(
         Some(p) => Ok(p),
         None => {
             let seeder = ProjectSeeder::<Erc721>::new(db_models.clone());
-            match seeder.seed(address.to_string()).await {
+            match seeder.seed_from_address(address.to_string()).await {
                 Ok(_p) => Ok(db_models
                     .project
                     .find_by_address(address)
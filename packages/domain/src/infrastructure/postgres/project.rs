@@ -1,6 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
-use deadpool_postgres::Pool;
+use deadpool_postgres::{Object, Pool};
 use tokio_postgres::error::SqlState;
 
 use crate::domain::Ulid;
@@ -10,8 +10,9 @@ use crate::{
         postgres::entity::ProjectIden,
         starknet::model::StarknetValueResolver,
         view_model::{
-            launchpad::LaunchpadProject, portfolio::ProjectWithMinterAndPaymentViewModel,
-            project::ProjectViewModel,
+            launchpad::LaunchpadProject,
+            portfolio::ProjectWithMinterAndPaymentViewModel,
+            project::{AbsorptionCurveRevision, ProjectViewModel},
         },
     },
 };
@@ -24,8 +25,8 @@ use crate::infrastructure::starknet::model::StarknetValue;
 
 use super::{
     entity::{
-        ErcImplementation, ImplementationIden, MinterIden, OffseterIden, PaymentIden, Project,
-        UriIden, YielderIden,
+        AbsorptionCurve, ErcImplementation, ImplementationIden, MinterIden, OffseterIden,
+        PaymentIden, Project, UriIden, YielderIden,
     },
     PostgresError,
 };
@@ -914,3 +915,29 @@ impl PostgresProject<Erc3525> {
         }
     }
 }
+
+/// Revisions of project absorption curve, oldest first
+/// * client - [`&deadpool_postgres::Object`]
+/// * slug - [`&str`]
+///
+pub async fn get_absorption_curves(
+    client: &Object,
+    slug: &str,
+) -> Result<Vec<AbsorptionCurveRevision>, PostgresError> {
+    match client
+        .query(
+            r#"SELECT c.id, c.project_address, c.slot, c.version, c.times, c.absorptions, c.event_id, c.time FROM absorption_curve c INNER JOIN project p ON LOWER(p.address) = LOWER(c.project_address) AND p.slot IS NOT DISTINCT FROM c.slot WHERE p.slug = $1 ORDER BY c.version"#,
+            &[&slug],
+        )
+        .await
+    {
+        Ok(rows) => Ok(rows
+            .into_iter()
+            .map(|row| AbsorptionCurve::from(row).into())
+            .collect()),
+        Err(e) => {
+            error!("project.absorption_curves: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
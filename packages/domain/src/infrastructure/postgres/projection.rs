@@ -0,0 +1,284 @@
+use deadpool_postgres::{Object, Transaction};
+use sea_query::{Expr, PostgresQueryBuilder, Query};
+use sea_query_postgres::PostgresBinder;
+use time::OffsetDateTime;
+use tokio_postgres::Row;
+use tracing::{error, info};
+
+use crate::domain::{
+    event_source::projection::{Projection, ProjectionStatus},
+    Ulid,
+};
+
+use super::{entity::ProjectionCheckpointIden, PostgresError};
+
+/// Checkpoint and status of a projection
+#[derive(Debug, Clone)]
+pub struct ProjectionState {
+    pub projection: Projection,
+    /// Last event_store id consumed by the projection
+    pub last_event_id: Option<Ulid>,
+    pub status: ProjectionStatus,
+}
+
+impl TryFrom<&Row> for ProjectionState {
+    type Error = PostgresError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let name: String = row.get(0);
+        let status: String = row.get(2);
+
+        Ok(Self {
+            projection: Projection::try_from(name.as_str()).map_err(|e| {
+                error!("projection_checkpoint.name: {:#?}", e);
+                PostgresError::UnexpectedError
+            })?,
+            last_event_id: row.get(1),
+            status: ProjectionStatus::try_from(status.as_str()).map_err(|e| {
+                error!("projection_checkpoint.status: {:#?}", e);
+                PostgresError::UnexpectedError
+            })?,
+        })
+    }
+}
+
+/// Get state of every projection
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn get_projection_states(client: &Object) -> Result<Vec<ProjectionState>, PostgresError> {
+    let (sql, values) = Query::select()
+        .columns([
+            ProjectionCheckpointIden::Name,
+            ProjectionCheckpointIden::LastEventId,
+            ProjectionCheckpointIden::Status,
+        ])
+        .from(ProjectionCheckpointIden::Table)
+        .order_by(ProjectionCheckpointIden::Name, sea_query::Order::Asc)
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => rows.iter().map(ProjectionState::try_from).collect(),
+        Err(e) => {
+            error!("projection_checkpoint.list: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Get state of given projection
+/// * client - [`&deadpool_postgres::Object`]
+/// * projection - [`Projection`]
+///
+pub async fn get_projection_state(
+    client: &Object,
+    projection: Projection,
+) -> Result<ProjectionState, PostgresError> {
+    let (sql, values) = Query::select()
+        .columns([
+            ProjectionCheckpointIden::Name,
+            ProjectionCheckpointIden::LastEventId,
+            ProjectionCheckpointIden::Status,
+        ])
+        .from(ProjectionCheckpointIden::Table)
+        .and_where(Expr::col(ProjectionCheckpointIden::Name).eq(projection.as_str()))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.query_one(sql.as_str(), &values.as_params()).await {
+        Ok(row) => ProjectionState::try_from(&row),
+        Err(e) => {
+            error!("projection_checkpoint.{}: {:#?}", projection, e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Move projection checkpoint forward.
+/// Must be called within the transaction consuming the event `key` points to.
+/// Checkpoint is only updated if it still points to `previous` so a concurrent rebuild
+/// is never overwritten, returns false in that case.
+///
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * projection - [`Projection`]
+/// * previous - [`Option<Ulid>`] - Checkpoint value read before consuming the event
+/// * key - [`Ulid`] - Consumed event_store id
+///
+pub async fn store_projection_checkpoint<'a>(
+    tx: &Transaction<'a>,
+    projection: Projection,
+    previous: Option<Ulid>,
+    key: Ulid,
+) -> Result<bool, PostgresError> {
+    let previous_condition = match previous {
+        Some(previous) => Expr::col(ProjectionCheckpointIden::LastEventId).eq(previous),
+        None => Expr::col(ProjectionCheckpointIden::LastEventId).is_null(),
+    };
+    let (sql, values) = Query::update()
+        .table(ProjectionCheckpointIden::Table)
+        .values([
+            (ProjectionCheckpointIden::LastEventId, key.into()),
+            (
+                ProjectionCheckpointIden::UpdatedAt,
+                OffsetDateTime::now_utc().into(),
+            ),
+        ])
+        .and_where(Expr::col(ProjectionCheckpointIden::Name).eq(projection.as_str()))
+        .and_where(previous_condition)
+        .build_postgres(PostgresQueryBuilder);
+
+    match tx.execute(sql.as_str(), &values.as_params()).await {
+        Ok(updated) => Ok(0 < updated),
+        Err(e) => {
+            error!("projection_checkpoint.store: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Pause or resume a projection
+/// * client - [`&deadpool_postgres::Object`]
+/// * projection - [`Projection`]
+/// * status - [`ProjectionStatus`]
+///
+pub async fn set_projection_status(
+    client: &Object,
+    projection: Projection,
+    status: ProjectionStatus,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::update()
+        .table(ProjectionCheckpointIden::Table)
+        .values([
+            (ProjectionCheckpointIden::Status, status.as_str().into()),
+            (
+                ProjectionCheckpointIden::UpdatedAt,
+                OffsetDateTime::now_utc().into(),
+            ),
+        ])
+        .and_where(Expr::col(ProjectionCheckpointIden::Name).eq(projection.as_str()))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => {
+            info!("Projection {} is now {}", projection, status);
+            Ok(())
+        }
+        Err(e) => {
+            error!("projection_checkpoint.status: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Delete read model rows projected from orphaned events, for projections whose rows record the
+/// event they come from. Checkpoint is kept, events stored again get newer ids and are consumed.
+/// Returns `false` when projection cannot be reverted event by event and has to be reset.
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * projection - [`Projection`]
+/// * event_ids - [`&[String]`] - Onchain ids of orphaned events
+///
+pub async fn revert_projection<'a>(
+    tx: &Transaction<'a>,
+    projection: Projection,
+    event_ids: &[String],
+) -> Result<bool, PostgresError> {
+    let tables: &[&str] = match projection {
+        Projection::CustomerFarm => &["customer_farm"],
+        Projection::MinterSale => &["buy", "airdrop"],
+        Projection::ProjectAbsorption => &["absorption_curve"],
+        _ => return Ok(false),
+    };
+    for table in tables {
+        tx.execute(
+            &format!(r#"DELETE FROM {table} WHERE event_id = ANY($1)"#),
+            &[&event_ids],
+        )
+        .await?;
+    }
+
+    Ok(true)
+}
+
+/// Clear projection read model and reset its checkpoint so it is replayed from first event.
+/// `project_value`, `minter_status` and `project_metadata` are overwritten by each event and are
+/// only replayed.
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * projection - [`Projection`]
+///
+pub async fn reset_projection<'a>(
+    tx: &Transaction<'a>,
+    projection: Projection,
+) -> Result<(), PostgresError> {
+    match projection {
+        Projection::CustomerToken => {
+            tx.execute(r#"TRUNCATE TABLE customer_token"#, &[]).await?;
+        }
+        Projection::CustomerFarm => {
+            tx.execute(r#"TRUNCATE TABLE customer_farm"#, &[]).await?;
+        }
+        Projection::YielderSnapshot => {
+            tx.execute(r#"DELETE FROM snapshot"#, &[]).await?;
+        }
+        Projection::YielderProvision => {
+            tx.execute(r#"DELETE FROM provision"#, &[]).await?;
+        }
+        Projection::ProjectValue | Projection::MinterStatus | Projection::ProjectMetadata => {}
+        Projection::CustomerApproval => {
+            tx.execute(r#"DELETE FROM token_approval"#, &[]).await?;
+            tx.execute(r#"DELETE FROM operator_approval"#, &[]).await?;
+        }
+        Projection::ProjectAbsorption => {
+            // seeded curves are not rebuilt from events
+            tx.execute(r#"DELETE FROM absorption_curve WHERE version > 0"#, &[])
+                .await?;
+        }
+        Projection::MinterSale => {
+            tx.execute(r#"DELETE FROM buy"#, &[]).await?;
+            tx.execute(r#"DELETE FROM airdrop"#, &[]).await?;
+        }
+    };
+    // failed events are consumed again with the whole event store
+    tx.execute(
+        r#"DELETE FROM failed_event WHERE projection = $1"#,
+        &[&projection.as_str()],
+    )
+    .await?;
+    // skipped unknown events are counted again on replay
+    tx.execute(
+        r#"DELETE FROM skipped_event WHERE projection = $1"#,
+        &[&projection.as_str()],
+    )
+    .await?;
+
+    let (sql, values) = Query::update()
+        .table(ProjectionCheckpointIden::Table)
+        .values([
+            (
+                ProjectionCheckpointIden::LastEventId,
+                Option::<Ulid>::None.into(),
+            ),
+            (
+                ProjectionCheckpointIden::UpdatedAt,
+                OffsetDateTime::now_utc().into(),
+            ),
+        ])
+        .and_where(Expr::col(ProjectionCheckpointIden::Name).eq(projection.as_str()))
+        .build_postgres(PostgresQueryBuilder);
+    tx.execute(sql.as_str(), &values.as_params()).await?;
+
+    info!("Projection {} reset", projection);
+    Ok(())
+}
+
+/// Rebuild projection from scratch
+/// * client - [`&mut deadpool_postgres::Object`]
+/// * projection - [`Projection`]
+///
+pub async fn rebuild_projection(
+    client: &mut Object,
+    projection: Projection,
+) -> Result<(), PostgresError> {
+    let tx = client.transaction().await?;
+    reset_projection(&tx, projection).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
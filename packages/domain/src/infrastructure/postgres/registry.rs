@@ -0,0 +1,150 @@
+use deadpool_postgres::Object;
+use sea_query::{Expr, OnConflict, Order, PostgresQueryBuilder, Query};
+use sea_query_postgres::PostgresBinder;
+use time::OffsetDateTime;
+use tokio_postgres::Row;
+use tracing::error;
+
+use crate::domain::{
+    crypto::U256,
+    registry::{ContractType, RegisteredContract},
+};
+
+use super::{entity::ContractRegistryIden, PostgresError};
+
+fn row_to_contract(row: &Row) -> Result<RegisteredContract, PostgresError> {
+    let contract_type: String = row.get(2);
+    let slot: Option<U256> = row.get(4);
+    let deployment_block: U256 = row.get(5);
+
+    Ok(RegisteredContract {
+        network: row.get(0),
+        address: row.get(1),
+        contract_type: ContractType::try_from(contract_type.as_str()).map_err(|e| {
+            error!("contract_registry.type: {:#?}", e);
+            PostgresError::UnexpectedError
+        })?,
+        project_address: row.get(3),
+        slot: slot.map(|s| s.into()),
+        deployment_block: deployment_block.into(),
+        active: row.get(6),
+    })
+}
+
+/// Register contract or update its registry entry
+/// * client - [`&deadpool_postgres::Object`]
+/// * contract - [`&RegisteredContract`]
+///
+pub async fn register_contract(
+    client: &Object,
+    contract: &RegisteredContract,
+) -> Result<(), PostgresError> {
+    let (sql, values) = Query::insert()
+        .into_table(ContractRegistryIden::Table)
+        .columns([
+            ContractRegistryIden::Network,
+            ContractRegistryIden::Address,
+            ContractRegistryIden::ContractType,
+            ContractRegistryIden::ProjectAddress,
+            ContractRegistryIden::Slot,
+            ContractRegistryIden::DeploymentBlock,
+            ContractRegistryIden::Active,
+            ContractRegistryIden::CreatedAt,
+        ])
+        .values([
+            contract.network.as_str().into(),
+            contract.address.as_str().into(),
+            contract.contract_type.as_str().into(),
+            contract.project_address.clone().into(),
+            contract.slot.map(U256::from).into(),
+            U256::from(contract.deployment_block).into(),
+            contract.active.into(),
+            OffsetDateTime::now_utc().into(),
+        ])?
+        .on_conflict(
+            OnConflict::columns([ContractRegistryIden::Network, ContractRegistryIden::Address])
+                .update_columns([
+                    ContractRegistryIden::ContractType,
+                    ContractRegistryIden::ProjectAddress,
+                    ContractRegistryIden::Slot,
+                    ContractRegistryIden::DeploymentBlock,
+                    ContractRegistryIden::Active,
+                ])
+                .to_owned(),
+        )
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.execute(sql.as_str(), &values.as_params()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("contract_registry.register: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Get registered contracts of given network
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+/// * active_only - [`bool`] - Skip deactivated contracts
+///
+pub async fn get_registered_contracts(
+    client: &Object,
+    network: &str,
+    active_only: bool,
+) -> Result<Vec<RegisteredContract>, PostgresError> {
+    let mut query = Query::select();
+    query
+        .columns([
+            ContractRegistryIden::Network,
+            ContractRegistryIden::Address,
+            ContractRegistryIden::ContractType,
+            ContractRegistryIden::ProjectAddress,
+            ContractRegistryIden::Slot,
+            ContractRegistryIden::DeploymentBlock,
+            ContractRegistryIden::Active,
+        ])
+        .from(ContractRegistryIden::Table)
+        .and_where(Expr::col(ContractRegistryIden::Network).eq(network))
+        .order_by(ContractRegistryIden::CreatedAt, Order::Asc)
+        .order_by(ContractRegistryIden::Address, Order::Asc);
+    if active_only {
+        query.and_where(Expr::col(ContractRegistryIden::Active).eq(true));
+    }
+    let (sql, values) = query.build_postgres(PostgresQueryBuilder);
+
+    match client.query(sql.as_str(), &values.as_params()).await {
+        Ok(rows) => rows.iter().map(row_to_contract).collect(),
+        Err(e) => {
+            error!("contract_registry.list: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Deactivate a registered contract so that it is not indexed anymore.
+/// Returns number of updated entries
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+/// * address - [`&str`]
+///
+pub async fn deactivate_contract(
+    client: &Object,
+    network: &str,
+    address: &str,
+) -> Result<u64, PostgresError> {
+    let (sql, values) = Query::update()
+        .table(ContractRegistryIden::Table)
+        .values([(ContractRegistryIden::Active, false.into())])
+        .and_where(Expr::col(ContractRegistryIden::Network).eq(network))
+        .and_where(Expr::col(ContractRegistryIden::Address).eq(address))
+        .build_postgres(PostgresQueryBuilder);
+
+    match client.execute(sql.as_str(), &values.as_params()).await {
+        Ok(updated) => Ok(updated),
+        Err(e) => {
+            error!("contract_registry.deactivate: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use deadpool_postgres::{Object, Transaction};
+use tracing::{debug, error};
+
+use crate::domain::{event_source::projection::Projection, Ulid};
+
+use super::PostgresError;
+
+/// Count an unknown event skipped by projection, per emitting contract and selector
+/// * tx - [`&deadpool_postgres::Transaction`]
+/// * projection - [`Projection`]
+/// * contract - [`&str`] - Address of the contract which emitted the event
+/// * selector - [`&str`]
+/// * event_id - [`&str`] - Onchain event id
+///
+pub async fn store_skipped_event<'a>(
+    tx: &Transaction<'a>,
+    projection: Projection,
+    contract: &str,
+    selector: &str,
+    event_id: &str,
+) -> Result<(), PostgresError> {
+    match tx
+        .execute(
+            r#"INSERT INTO skipped_event (id, projection, contract_address, selector, count, last_event_id, updated_at)
+            VALUES ($1, $2, $3, $4, 1, $5, NOW())
+            ON CONFLICT (projection, contract_address, selector) DO UPDATE
+            SET count = skipped_event.count + 1, last_event_id = EXCLUDED.last_event_id, updated_at = NOW()"#,
+            &[
+                &Ulid::new().to_string(),
+                &projection.as_str(),
+                &contract,
+                &selector,
+                &event_id,
+            ],
+        )
+        .await
+    {
+        Ok(_) => {
+            debug!(
+                "skipped_event.{}: unknown event {} of {} with selector {}",
+                projection, event_id, contract, selector
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!("skipped_event.store: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
+
+/// Count skipped unknown events per projection and event type, i.e. per contract and selector
+/// * client - [`&deadpool_postgres::Object`]
+///
+pub async fn count_skipped_events(
+    client: &Object,
+) -> Result<Vec<(String, String, i64)>, PostgresError> {
+    match client
+        .query(
+            r#"SELECT projection, 'unknown.' || contract_address || '.' || selector, count FROM skipped_event ORDER BY projection, contract_address, selector"#,
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => Ok(rows
+            .iter()
+            .map(|row| (row.get(0), row.get(1), row.get(2)))
+            .collect()),
+        Err(e) => {
+            error!("skipped_event.count: {:#?}", e);
+            Err(PostgresError::from(e))
+        }
+    }
+}
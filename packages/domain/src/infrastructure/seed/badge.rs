@@ -11,6 +11,8 @@ use crate::{
     },
 };
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{DataSeederError, Seeder};
 use std::sync::Arc;
 
@@ -40,7 +42,8 @@ impl<C> Seeder for BadgeSeeder<C>
 where
     C: Contract + Send + Sync,
 {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
         let badge_model = BadgeModel::new(FieldElement::from_hex_be(&address).unwrap())?;
         let mut data = badge_model.load().await?;
@@ -71,7 +74,7 @@ where
         Ok(address)
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        "badge" == seeder_type || "badge_3525" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Badge == contract_type
     }
 }
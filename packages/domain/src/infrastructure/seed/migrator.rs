@@ -13,6 +13,8 @@ use crate::{
 };
 use std::sync::Arc;
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{DataSeederError, Seeder};
 
 #[derive(Debug)]
@@ -28,7 +30,8 @@ impl MigratorSeeder {
 
 #[async_trait::async_trait]
 impl Seeder for MigratorSeeder {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
         let provider = get_starknet_rpc_from_env()?;
         let calldata = [
@@ -65,7 +68,7 @@ impl Seeder for MigratorSeeder {
 
         Ok("project properly seeded from migrator address".to_owned())
     }
-    fn can_process(&self, seeder_type: String) -> bool {
-        "migrator" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Migrator == contract_type
     }
 }
@@ -12,6 +12,8 @@ use crate::infrastructure::{
     starknet::minter::MinterModel,
 };
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{DataSeederError, Seeder};
 
 #[derive(Debug)]
@@ -39,17 +41,21 @@ impl MinterSeeder<Erc3525> {
 
 #[async_trait::async_trait]
 impl Seeder for MinterSeeder<Erc721> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
         let minter_model =
             MinterModel::<Erc721>::new(FieldElement::from_hex_be(&address).unwrap())?;
         let mut data = minter_model.load().await?;
 
-        let project_address: String = data
-            .get_mut("getCarbonableProjectAddress")
-            .expect("should have getCarbonableProjectAddress")
-            .resolve("address")
-            .into();
+        let project_address: String = match &contract.project_address {
+            Some(project_address) => project_address.to_owned(),
+            None => data
+                .get_mut("getCarbonableProjectAddress")
+                .expect("should have getCarbonableProjectAddress")
+                .resolve("address")
+                .into(),
+        };
         let payment_address: String = data
             .get_mut("getPaymentTokenAddress")
             .expect("should have getPaymentTokenAddress")
@@ -79,34 +85,41 @@ impl Seeder for MinterSeeder<Erc721> {
         Ok(address)
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        "minter" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Minter == contract_type
     }
 }
 
 #[async_trait::async_trait]
 impl Seeder for MinterSeeder<Erc3525> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
         let minter_model =
             MinterModel::<Erc3525>::new(FieldElement::from_hex_be(&address).unwrap())?;
         let mut data = minter_model.load().await?;
 
-        let project_address: String = data
-            .get_mut("get_carbonable_project_address")
-            .expect("should have getCarbonableProjectAddress")
-            .resolve("address")
-            .into();
+        let project_address: String = match &contract.project_address {
+            Some(project_address) => project_address.to_owned(),
+            None => data
+                .get_mut("get_carbonable_project_address")
+                .expect("should have getCarbonableProjectAddress")
+                .resolve("address")
+                .into(),
+        };
         let payment_address: String = data
             .get_mut("get_payment_token_address")
             .expect("should have getPaymentTokenAddress")
             .resolve("address")
             .into();
-        let slot: u64 = data
-            .get_mut("get_carbonable_project_slot")
-            .expect("should have getCarbonableProjectSlot")
-            .resolve("u64")
-            .into();
+        let slot: u64 = match contract.slot {
+            Some(slot) => slot,
+            None => data
+                .get_mut("get_carbonable_project_slot")
+                .expect("should have getCarbonableProjectSlot")
+                .resolve("u64")
+                .into(),
+        };
 
         let project =
             find_or_create_3525_project(db_models.clone(), project_address.as_str(), &slot).await?;
@@ -132,7 +145,7 @@ impl Seeder for MinterSeeder<Erc3525> {
         Ok(address)
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        "minter_3525" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Minter3525 == contract_type
     }
 }
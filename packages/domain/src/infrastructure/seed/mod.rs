@@ -7,7 +7,10 @@ pub mod yielder;
 
 use std::{collections::HashMap, fmt::Debug, sync::Arc};
 
-use crate::infrastructure::{flatten, postgres::PostgresError as InfraPostgresError};
+use crate::{
+    domain::registry::{ContractType, RegisteredContract},
+    infrastructure::{flatten, postgres::PostgresError as InfraPostgresError},
+};
 use starknet::providers::{ProviderError, SequencerGatewayProviderError};
 
 use thiserror::Error;
@@ -37,17 +40,19 @@ pub enum DataSeederError {
 
 #[async_trait::async_trait]
 pub trait SeederManager {
-    async fn handle(&self, data: HashMap<String, String>) -> Result<(), DataSeederError>;
+    async fn handle(&self, contract: RegisteredContract) -> Result<(), DataSeederError>;
 }
 
+/// Seeds registry contracts of a type, registry project and slot take precedence over values
+/// read onchain
 #[async_trait::async_trait]
 pub trait Seeder {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError>;
-    fn can_process(&self, seeder_type: String) -> bool;
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError>;
+    fn can_process(&self, contract_type: ContractType) -> bool;
 }
 
 pub struct DataSeeder<T: SeederManager + Send + Sync> {
-    data: Vec<HashMap<String, String>>,
+    contracts: Vec<RegisteredContract>,
     inner: Arc<T>,
 }
 
@@ -63,27 +68,39 @@ pub fn read_data_content<P: AsRef<std::path::Path>>(
 }
 
 impl DataSeeder<SqlSeederManager> {
-    pub async fn feed_from_data<P: AsRef<std::path::Path>>(
-        file_path: P,
+    /// Seed active contract registry entries
+    pub fn feed_from_registry(
+        contracts: &[RegisteredContract],
         seeders: Vec<Arc<dyn Seeder + Send + Sync>>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = read_data_content(file_path)?;
-
-        Ok(DataSeeder {
-            data: content,
+    ) -> Self {
+        DataSeeder {
+            contracts: contracts.iter().filter(|c| c.active).cloned().collect(),
             inner: Arc::new(SqlSeederManager::new(seeders)),
-        })
+        }
     }
 
+    /// Projects are seeded first, contracts belonging to them would race to create them otherwise
     pub async fn seed(&self) -> Result<(), DataSeederError> {
         debug!("Seeding data.");
 
+        let (projects, children): (Vec<RegisteredContract>, Vec<RegisteredContract>) = self
+            .contracts
+            .iter()
+            .cloned()
+            .partition(|c| c.contract_type.is_project());
+        self.seed_contracts(projects).await?;
+        self.seed_contracts(children).await
+    }
+
+    async fn seed_contracts(
+        &self,
+        contracts: Vec<RegisteredContract>,
+    ) -> Result<(), DataSeederError> {
         let mut seeds = vec![];
         // Seeds data from blockchain to database
-        for data in self.data.iter() {
+        for contract in contracts {
             let inner = self.inner.clone();
-            let data = data.clone();
-            let handle = tokio::spawn(async move { inner.handle(data).await });
+            let handle = tokio::spawn(async move { inner.handle(contract).await });
             seeds.push(flatten(handle));
         }
 
@@ -110,12 +127,13 @@ impl SqlSeederManager {
 
 #[async_trait::async_trait]
 impl SeederManager for SqlSeederManager {
-    async fn handle(&self, data: HashMap<String, String>) -> Result<(), DataSeederError> {
-        for (key, value) in data.iter() {
-            match self.seeders.iter().find(|s| s.can_process(key.to_string())) {
-                None => continue,
-                Some(seeder) => seeder.seed(value.to_string()).await?,
-            };
+    async fn handle(&self, contract: RegisteredContract) -> Result<(), DataSeederError> {
+        if let Some(seeder) = self
+            .seeders
+            .iter()
+            .find(|s| s.can_process(contract.contract_type))
+        {
+            seeder.seed(&contract).await?;
         }
 
         Ok(())
@@ -15,6 +15,8 @@ use crate::{
     },
 };
 
+use crate::domain::registry::{ContractType, RegisteredContract};
+
 use super::{DataSeederError, Seeder};
 
 #[derive(Debug)]
@@ -37,18 +39,22 @@ where
 
 #[async_trait::async_trait]
 impl Seeder for OffseterSeeder<Erc721> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
 
         let offseter_model =
             OffseterModel::<Erc721>::new(FieldElement::from_hex_be(&address).unwrap())?;
         let mut data = offseter_model.load().await?;
 
-        let project_address: String = data
-            .get_mut("getCarbonableProjectAddress")
-            .expect("should have carbonableProjectAddress")
-            .resolve("address")
-            .into();
+        let project_address: String = match &contract.project_address {
+            Some(project_address) => project_address.to_owned(),
+            None => data
+                .get_mut("getCarbonableProjectAddress")
+                .expect("should have carbonableProjectAddress")
+                .resolve("address")
+                .into(),
+        };
 
         let implementation = find_or_create_implementation(
             db_models.implementation.clone(),
@@ -67,30 +73,37 @@ impl Seeder for OffseterSeeder<Erc721> {
         Ok(address)
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        "offseter" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Offseter == contract_type
     }
 }
 
 #[async_trait::async_trait]
 impl Seeder for OffseterSeeder<Erc3525> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        let address = contract.address.to_owned();
         let db_models = self.db_models.clone();
 
         let offseter_model =
             OffseterModel::<Erc3525>::new(FieldElement::from_hex_be(&address).unwrap())?;
         let mut data = offseter_model.load().await?;
 
-        let project_address: String = data
-            .get_mut("get_carbonable_project_address")
-            .expect("should have carbonableProjectAddress")
-            .resolve("address")
-            .into();
-        let slot: u64 = data
-            .get_mut("get_carbonable_project_slot")
-            .expect("should have getCarbonableProjectSlot")
-            .resolve("u64")
-            .into();
+        let project_address: String = match &contract.project_address {
+            Some(project_address) => project_address.to_owned(),
+            None => data
+                .get_mut("get_carbonable_project_address")
+                .expect("should have carbonableProjectAddress")
+                .resolve("address")
+                .into(),
+        };
+        let slot: u64 = match contract.slot {
+            Some(slot) => slot,
+            None => data
+                .get_mut("get_carbonable_project_slot")
+                .expect("should have getCarbonableProjectSlot")
+                .resolve("u64")
+                .into(),
+        };
 
         let implementation = find_or_create_implementation(
             db_models.implementation.clone(),
@@ -109,7 +122,7 @@ impl Seeder for OffseterSeeder<Erc3525> {
         Ok(address)
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        "offseter_3525" == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Offseter3525 == contract_type
     }
 }
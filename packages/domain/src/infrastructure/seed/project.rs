@@ -5,10 +5,12 @@ use std::sync::Arc;
 use tracing::info;
 
 use crate::domain::crypto::U256;
+use crate::domain::registry::{ContractType, RegisteredContract};
 use crate::domain::{Contract, Erc3525, Erc721};
 use crate::infrastructure::postgres::entity::ErcImplementation;
 use crate::infrastructure::postgres::{
-    find_or_create_implementation, find_or_create_uri_3525, find_or_create_uri_721, PostgresModels,
+    find_or_create_3525_project, find_or_create_implementation, find_or_create_uri_3525,
+    find_or_create_uri_721, PostgresModels,
 };
 use crate::infrastructure::starknet::model::{
     parallelize_blockchain_rpc_calls, ModelError, StarknetModel, StarknetResolvedValue,
@@ -39,7 +41,17 @@ where
 
 #[async_trait::async_trait]
 impl Seeder for ProjectSeeder<Erc721> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        self.seed_from_address(contract.address.to_owned()).await
+    }
+
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Project == contract_type
+    }
+}
+
+impl ProjectSeeder<Erc721> {
+    pub async fn seed_from_address(&self, address: String) -> Result<String, DataSeederError> {
         let project_model =
             ProjectModel::<Erc721>::new(FieldElement::from_hex_be(&address).unwrap())?;
         let db_models = self.db_models.clone();
@@ -74,64 +86,24 @@ impl Seeder for ProjectSeeder<Erc721> {
         info!("Properly seeded project {}", address);
         Ok(String::from("seeded"))
     }
-
-    fn can_process(&self, seeder_type: String) -> bool {
-        "project" == seeder_type
-    }
 }
 
 #[async_trait::async_trait]
 impl Seeder for ProjectSeeder<Erc3525> {
-    async fn seed(&self, address: String) -> Result<String, DataSeederError> {
-        info!("seeding Erc3525 project {}", address);
-        let project_model =
-            ProjectModel::<Erc3525>::new(FieldElement::from_hex_be(address.as_str()).unwrap())?;
-        let db_models = self.db_models.clone();
-
-        // fetch onchain project data
-        let mut data = project_model.load().await?;
-        // ERC-3525 has many slots that represent founded projects
-        for slot in data.iter_mut() {
-            let provider = project_model.provider.clone();
-            let project_uri: String = slot
-                .get_mut("slot_uri")
-                .expect("should have contract uri")
-                .resolve("string_array")
-                .into();
-            let implementation = find_or_create_implementation(
-                db_models.implementation.clone(),
-                provider,
-                address.as_str(),
-            )
-            .await?;
-            let uri = find_or_create_uri_3525(
-                db_models.uri.clone(),
-                address.as_str(),
-                project_uri.as_str(),
-            )
-            .await?;
-
-            let _saved = self
-                .db_models
-                .clone()
-                .project
-                .create(
-                    slot.clone(),
-                    ErcImplementation::Erc3525,
-                    Some(implementation.id),
-                    Some(uri.id),
-                    Some(project_uri),
-                )
-                .await?;
+    async fn seed(&self, contract: &RegisteredContract) -> Result<String, DataSeederError> {
+        match contract.slot {
+            Some(slot) => {
+                find_or_create_3525_project(self.db_models.clone(), &contract.address, &slot)
+                    .await?;
+                Ok(String::from("seeded"))
+            }
+            // slots are seeded along with contracts registered for them
+            None => Ok(String::from("skipped")),
         }
-        info!("Properly seeded project {}", address);
-        Ok("seeded".to_owned())
     }
 
-    fn can_process(&self, seeder_type: String) -> bool {
-        // seeding is done with minter.
-        // but field is still required because based on the same file
-        "DONTSEEDWITHTHESEDATA." == seeder_type
+    fn can_process(&self, contract_type: ContractType) -> bool {
+        ContractType::Project3525 == contract_type
     }
 }
 
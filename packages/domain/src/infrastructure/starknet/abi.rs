@@ -0,0 +1,745 @@
+use std::collections::{HashMap, HashSet};
+
+use apibara_core::starknet::v1alpha2::FieldElement;
+use crypto_bigint::Encoding;
+use serde_json::{Map, Value};
+use starknet::core::utils::get_selector_from_name;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+#[derive(Debug, Error)]
+pub enum AbiError {
+    #[error("invalid abi: {0}")]
+    InvalidAbi(String),
+    #[error("not enough felts to decode {0}")]
+    MissingFelts(String),
+    #[error("invalid value for {0}")]
+    InvalidValue(String),
+}
+
+const U256_TYPES: [&str; 2] = ["Uint256", "core::integer::u256"];
+const BOOL_TYPE: &str = "core::bool";
+const CAIRO_1_ARRAY_PREFIXES: [&str; 2] = ["core::array::Array::<", "core::array::Span::<"];
+
+/// User defined types an event member may refer to
+#[derive(Debug, Clone)]
+enum TypeDefinition {
+    Struct(Vec<(String, String)>),
+    Enum(Vec<(String, String)>),
+}
+
+/// Named members of an event, in emission order
+#[derive(Debug, Clone)]
+struct EventDefinition {
+    name: String,
+    keys: Vec<(String, String)>,
+    data: Vec<(String, String)>,
+}
+
+/// Decodes raw event keys and data into named fields using a contract ABI.
+/// Both Cairo 0 (`keys`/`data` events) and Cairo 1 (`kind` events, legacy `inputs` events) ABI
+/// are supported. Cairo 1 events emitted through a nested component are prefixed with the
+/// component selector.
+#[derive(Debug, Clone, Default)]
+pub struct EventDecoder {
+    events: HashMap<Vec<String>, EventDefinition>,
+    types: HashMap<String, TypeDefinition>,
+}
+
+impl TryFrom<&Value> for EventDecoder {
+    type Error = AbiError;
+
+    fn try_from(abi: &Value) -> Result<Self, Self::Error> {
+        let entries = abi
+            .as_array()
+            .ok_or_else(|| AbiError::InvalidAbi("abi should be an array".to_owned()))?;
+        let mut decoder = Self::default();
+        let mut struct_events = HashMap::new();
+        let mut enum_events = HashMap::new();
+        for entry in entries {
+            let name = match entry.get("name").and_then(Value::as_str) {
+                Some(name) => name,
+                None => continue,
+            };
+            match entry.get("type").and_then(Value::as_str) {
+                Some("struct") => {
+                    decoder.types.insert(
+                        name.to_owned(),
+                        TypeDefinition::Struct(members(entry, "members")?),
+                    );
+                }
+                Some("enum") => {
+                    decoder.types.insert(
+                        name.to_owned(),
+                        TypeDefinition::Enum(members(entry, "variants")?),
+                    );
+                }
+                // Cairo 1 event enums, their variants are declared as their own events
+                Some("event") if Some("enum") == entry.get("kind").and_then(Value::as_str) => {
+                    enum_events.insert(name.to_owned(), variants(entry)?);
+                }
+                Some("event") => {
+                    struct_events.insert(name.to_owned(), event_definition(name, entry)?);
+                }
+                _ => {}
+            }
+        }
+
+        // Cairo 1 events are selected by their enum variant name, which may differ from their
+        // type name. Events which are no enum variant are Cairo 0 or legacy ones
+        let variant_types: HashSet<&str> = enum_events
+            .values()
+            .flatten()
+            .map(|(_, r#type, _)| r#type.as_str())
+            .collect();
+        for (name, definition) in struct_events
+            .iter()
+            .filter(|(name, _)| !variant_types.contains(name.as_str()))
+        {
+            decoder
+                .events
+                .insert(vec![event_selector(name)?], definition.clone());
+        }
+        let contract_events = enum_events
+            .iter()
+            .filter(|(name, _)| !variant_types.contains(name.as_str()));
+        for (variant, r#type, kind) in contract_events.flat_map(|(_, variants)| variants) {
+            if let Some(definition) = struct_events.get(r#type) {
+                decoder
+                    .events
+                    .insert(vec![event_selector(variant)?], definition.clone());
+                continue;
+            }
+            // nested component: keys are `[component variant selector, event variant selector, ..]`
+            // flattened component: keys are `[event variant selector, ..]`
+            let component = match enum_events.get(r#type) {
+                Some(component) => component,
+                None => continue,
+            };
+            for (event_variant, event_type, _) in component {
+                if let Some(definition) = struct_events.get(event_type) {
+                    let path = match kind.as_str() {
+                        "flat" => vec![event_selector(event_variant)?],
+                        _ => vec![event_selector(variant)?, event_selector(event_variant)?],
+                    };
+                    decoder.events.insert(path, definition.clone());
+                }
+            }
+        }
+
+        Ok(decoder)
+    }
+}
+
+impl EventDecoder {
+    /// Key paths of events emitted through nested components
+    pub fn nested_paths(&self) -> Vec<Vec<String>> {
+        self.events
+            .keys()
+            .filter(|path| 1 < path.len())
+            .cloned()
+            .collect()
+    }
+
+    /// Decode event into its name and named fields, `None` when event is not part of ABI
+    /// * `keys` - [`&[FieldElement]`] - Event keys, selector first
+    /// * `data` - [`&[FieldElement]`] - Event data
+    ///
+    pub fn decode(
+        &self,
+        keys: &[FieldElement],
+        data: &[FieldElement],
+    ) -> Result<Option<(String, Map<String, Value>)>, AbiError> {
+        let selectors = keys
+            .iter()
+            .take(2)
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>();
+        // longest key path first, a nested event selector may clash with a top level one
+        let (path_len, definition) = match (1..=selectors.len())
+            .rev()
+            .find_map(|len| self.events.get(&selectors[..len]).map(|d| (len, d)))
+        {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        let mut fields = Map::new();
+        self.decode_members(&definition.keys, &keys[path_len..], &mut fields)?;
+        self.decode_members(&definition.data, data, &mut fields)?;
+
+        Ok(Some((definition.name.to_owned(), fields)))
+    }
+
+    fn decode_members(
+        &self,
+        members: &[(String, String)],
+        felts: &[FieldElement],
+        fields: &mut Map<String, Value>,
+    ) -> Result<(), AbiError> {
+        let mut position = 0;
+        let mut previous_len = None;
+        for (name, r#type) in members {
+            let value = match r#type.strip_suffix('*') {
+                // Cairo 0 arrays are preceded by their `{name}_len` member
+                Some(inner) => {
+                    let len = previous_len
+                        .ok_or_else(|| AbiError::InvalidValue(format!("{name} length")))?;
+                    self.decode_array(inner, len, felts, &mut position)?
+                }
+                None => self.decode_type(r#type, felts, &mut position)?,
+            };
+            previous_len = value.as_str().and_then(felt_to_usize);
+            fields.insert(name.to_owned(), value);
+        }
+        Ok(())
+    }
+
+    fn decode_type(
+        &self,
+        r#type: &str,
+        felts: &[FieldElement],
+        position: &mut usize,
+    ) -> Result<Value, AbiError> {
+        if U256_TYPES.contains(&r#type) {
+            let low = next_felt(r#type, felts, position)?;
+            let high = next_felt(r#type, felts, position)?;
+            return Ok(Value::String(u256_to_hex(low, high)));
+        }
+        if BOOL_TYPE == r#type {
+            let felt = next_felt(r#type, felts, position)?;
+            return Ok(Value::Bool(FieldElement::from_u64(0) != *felt));
+        }
+        if "()" == r#type {
+            return Ok(Value::Null);
+        }
+        if let Some(inner) = CAIRO_1_ARRAY_PREFIXES
+            .iter()
+            .find_map(|p| r#type.strip_prefix(p))
+            .and_then(|t| t.strip_suffix('>'))
+        {
+            let len = felt_to_usize(&next_felt(r#type, felts, position)?.to_string())
+                .ok_or_else(|| AbiError::InvalidValue(format!("{} length", r#type)))?;
+            return self.decode_array(inner, len, felts, position);
+        }
+        if let Some(inner) = r#type.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            return Ok(Value::Array(
+                split_tuple(inner)
+                    .into_iter()
+                    .map(|t| self.decode_type(t, felts, position))
+                    .collect::<Result<Vec<Value>, AbiError>>()?,
+            ));
+        }
+
+        match self.types.get(r#type) {
+            Some(TypeDefinition::Struct(members)) => {
+                let mut object = Map::new();
+                for (name, member_type) in members {
+                    object.insert(
+                        name.to_owned(),
+                        self.decode_type(member_type, felts, position)?,
+                    );
+                }
+                Ok(Value::Object(object))
+            }
+            Some(TypeDefinition::Enum(variants)) => {
+                let index = felt_to_usize(&next_felt(r#type, felts, position)?.to_string())
+                    .ok_or_else(|| AbiError::InvalidValue(r#type.to_owned()))?;
+                let (name, variant_type) = variants
+                    .get(index)
+                    .ok_or_else(|| AbiError::InvalidValue(r#type.to_owned()))?;
+                let mut object = Map::new();
+                object.insert(
+                    name.to_owned(),
+                    self.decode_type(variant_type, felts, position)?,
+                );
+                Ok(Value::Object(object))
+            }
+            // felt, integers, addresses and class hashes fit in a single felt
+            None => Ok(Value::String(
+                next_felt(r#type, felts, position)?.to_string(),
+            )),
+        }
+    }
+
+    fn decode_array(
+        &self,
+        r#type: &str,
+        len: usize,
+        felts: &[FieldElement],
+        position: &mut usize,
+    ) -> Result<Value, AbiError> {
+        if felts.len() < *position + len {
+            return Err(AbiError::MissingFelts(format!("{}*", r#type)));
+        }
+        Ok(Value::Array(
+            (0..len)
+                .map(|_| self.decode_type(r#type, felts, position))
+                .collect::<Result<Vec<Value>, AbiError>>()?,
+        ))
+    }
+}
+
+/// Decoders of every indexed contract, by contract address
+#[derive(Debug, Default)]
+pub struct AbiRegistry {
+    decoders: HashMap<String, EventDecoder>,
+}
+
+impl AbiRegistry {
+    /// Contracts with an invalid ABI are skipped, their events keep positional payload only
+    /// * `abis` - [`Vec<(String, Value)>`] - Contract address and ABI
+    ///
+    pub fn new(abis: Vec<(String, Value)>) -> Self {
+        let mut decoders = HashMap::new();
+        for (address, abi) in abis {
+            let address = match FieldElement::from_hex(&address) {
+                Ok(a) => a.to_string(),
+                Err(_) => continue,
+            };
+            match EventDecoder::try_from(&abi) {
+                Ok(decoder) => {
+                    decoders.insert(address, decoder);
+                }
+                Err(e) => warn!("Skipping abi of contract {}: {}", address, e),
+            }
+        }
+        Self { decoders }
+    }
+
+    /// Key paths of events emitted through nested components of a contract
+    /// * `address` - [`&str`]
+    ///
+    pub fn nested_paths(&self, address: &str) -> Vec<Vec<String>> {
+        FieldElement::from_hex(address)
+            .ok()
+            .and_then(|a| self.decoders.get(&a.to_string()))
+            .map(|d| d.nested_paths())
+            .unwrap_or_default()
+    }
+
+    /// Decode event emitted by `from_address` into flattened named fields
+    /// * `from_address` - [`&FieldElement`]
+    /// * `keys` - [`&[FieldElement]`]
+    /// * `data` - [`&[FieldElement]`]
+    ///
+    pub fn decode(
+        &self,
+        from_address: &FieldElement,
+        keys: &[FieldElement],
+        data: &[FieldElement],
+    ) -> Option<HashMap<String, String>> {
+        let decoder = self.decoders.get(&from_address.to_string())?;
+        match decoder.decode(keys, data) {
+            Ok(Some((_, fields))) => Some(flatten(&fields)),
+            Ok(None) => None,
+            Err(e) => {
+                debug!("Failed to decode event of {}: {}", from_address, e);
+                None
+            }
+        }
+    }
+}
+
+/// Flatten decoded fields into `event_store.payload` string map,
+/// nested members and array items are joined with a dot e.g. `ids.0`, `data.amount`
+/// * `fields` - [`&Map<String, Value>`]
+///
+pub fn flatten(fields: &Map<String, Value>) -> HashMap<String, String> {
+    let mut flattened = HashMap::new();
+    for (name, value) in fields {
+        flatten_value(name, value, &mut flattened);
+    }
+    flattened
+}
+
+fn flatten_value(path: &str, value: &Value, flattened: &mut HashMap<String, String>) {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .for_each(|(k, v)| flatten_value(&format!("{path}.{k}"), v, flattened)),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| flatten_value(&format!("{path}.{i}"), v, flattened)),
+        Value::String(s) => {
+            flattened.insert(path.to_owned(), s.to_owned());
+        }
+        Value::Null => {}
+        v => {
+            flattened.insert(path.to_owned(), v.to_string());
+        }
+    }
+}
+
+fn event_definition(name: &str, entry: &Value) -> Result<EventDefinition, AbiError> {
+    if Some("struct") == entry.get("kind").and_then(Value::as_str) {
+        let mut keys = vec![];
+        let mut data = vec![];
+        for member in entry
+            .get("members")
+            .and_then(Value::as_array)
+            .ok_or_else(|| AbiError::InvalidAbi(format!("{name} has no members")))?
+        {
+            let member_kind = member.get("kind").and_then(Value::as_str);
+            let member = member_of(member)?;
+            match member_kind {
+                Some("key") => keys.push(member),
+                _ => data.push(member),
+            }
+        }
+        return Ok(EventDefinition {
+            name: name.to_owned(),
+            keys,
+            data,
+        });
+    }
+    if entry.get("inputs").is_some() {
+        return Ok(EventDefinition {
+            name: name.to_owned(),
+            keys: vec![],
+            data: members(entry, "inputs")?,
+        });
+    }
+    Ok(EventDefinition {
+        name: name.to_owned(),
+        keys: members(entry, "keys").unwrap_or_default(),
+        data: members(entry, "data")?,
+    })
+}
+
+/// Cairo 1 event enum variants as name, type and kind
+fn variants(entry: &Value) -> Result<Vec<(String, String, String)>, AbiError> {
+    entry
+        .get("variants")
+        .and_then(Value::as_array)
+        .ok_or_else(|| AbiError::InvalidAbi("missing variants".to_owned()))?
+        .iter()
+        .map(|variant| {
+            let (name, r#type) = member_of(variant)?;
+            let kind = variant
+                .get("kind")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            Ok((name, r#type, kind.to_owned()))
+        })
+        .collect()
+}
+
+/// Selector is computed from event name without its module path, Cairo 1 event name being
+/// its enum variant name
+fn event_selector(name: &str) -> Result<String, AbiError> {
+    let short_name = name.rsplit("::").next().unwrap_or(name);
+    let selector = get_selector_from_name(short_name)
+        .map_err(|_| AbiError::InvalidAbi(format!("invalid event name {name}")))?;
+
+    Ok(FieldElement::from_bytes(&selector.to_bytes_be()).to_string())
+}
+
+fn members(entry: &Value, field: &str) -> Result<Vec<(String, String)>, AbiError> {
+    entry
+        .get(field)
+        .and_then(Value::as_array)
+        .ok_or_else(|| AbiError::InvalidAbi(format!("missing {field}")))?
+        .iter()
+        .map(member_of)
+        .collect()
+}
+
+fn member_of(member: &Value) -> Result<(String, String), AbiError> {
+    match (
+        member.get("name").and_then(Value::as_str),
+        member.get("type").and_then(Value::as_str),
+    ) {
+        (Some(name), Some(r#type)) => Ok((name.to_owned(), r#type.to_owned())),
+        _ => Err(AbiError::InvalidAbi(format!("invalid member {member}"))),
+    }
+}
+
+fn next_felt<'a>(
+    r#type: &str,
+    felts: &'a [FieldElement],
+    position: &mut usize,
+) -> Result<&'a FieldElement, AbiError> {
+    let felt = felts
+        .get(*position)
+        .ok_or_else(|| AbiError::MissingFelts(r#type.to_owned()))?;
+    *position += 1;
+    Ok(felt)
+}
+
+fn felt_to_usize(felt: &str) -> Option<usize> {
+    usize::from_str_radix(felt.trim_start_matches("0x"), 16).ok()
+}
+
+fn u256_to_hex(low: &FieldElement, high: &FieldElement) -> String {
+    let low = crypto_bigint::U256::from_be_bytes(low.to_bytes());
+    let high = crypto_bigint::U256::from_be_bytes(high.to_bytes());
+    let value = low.wrapping_add(&high.shl_vartime(128));
+
+    format!(
+        "0x{}",
+        value
+            .to_be_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    )
+}
+
+/// Split tuple members on top level commas, `(a: felt, b: (felt, felt))` named members included
+fn split_tuple(inner: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            ',' if 0 == depth => {
+                parts.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&inner[start..]);
+
+    parts
+        .into_iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.split_once(": ") {
+            Some((_, t)) => t.trim(),
+            None => p,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use apibara_core::starknet::v1alpha2::FieldElement;
+    use serde_json::json;
+    use starknet::macros::selector;
+
+    use super::{flatten, EventDecoder};
+
+    fn selector(name: &str) -> FieldElement {
+        let selector = match name {
+            "Transfer" => selector!("Transfer"),
+            "Snapshot" => selector!("Snapshot"),
+            "ERC721Event" => selector!("ERC721Event"),
+            _ => selector!("Batch"),
+        };
+        FieldElement::from_bytes(&selector.to_bytes_be())
+    }
+
+    #[test]
+    fn test_decode_cairo_0_event() {
+        let abi = json!([
+            {"type": "struct", "name": "Uint256", "size": 2, "members": [
+                {"name": "low", "type": "felt", "offset": 0},
+                {"name": "high", "type": "felt", "offset": 1}
+            ]},
+            {"type": "event", "name": "Transfer", "keys": [], "data": [
+                {"name": "from_", "type": "felt"},
+                {"name": "to", "type": "felt"},
+                {"name": "tokenId", "type": "Uint256"}
+            ]},
+            {"type": "event", "name": "Batch", "keys": [], "data": [
+                {"name": "ids_len", "type": "felt"},
+                {"name": "ids", "type": "felt*"},
+                {"name": "pair", "type": "(a: felt, b: felt)"}
+            ]}
+        ]);
+        let decoder = EventDecoder::try_from(&abi).unwrap();
+
+        let (name, fields) = decoder
+            .decode(
+                &[selector("Transfer")],
+                &[
+                    FieldElement::from_u64(0),
+                    FieldElement::from_u64(0x123),
+                    FieldElement::from_u64(1),
+                    FieldElement::from_u64(1),
+                ],
+            )
+            .unwrap()
+            .unwrap();
+        let fields = flatten(&fields);
+        assert_eq!("Transfer", name);
+        assert_eq!(FieldElement::from_u64(0x123).to_string(), fields["to"]);
+        assert_eq!(
+            "0x0000000000000000000000000000000100000000000000000000000000000001",
+            fields["tokenId"]
+        );
+
+        let (_, fields) = decoder
+            .decode(
+                &[selector("Batch")],
+                &[
+                    FieldElement::from_u64(2),
+                    FieldElement::from_u64(7),
+                    FieldElement::from_u64(8),
+                    FieldElement::from_u64(9),
+                    FieldElement::from_u64(10),
+                ],
+            )
+            .unwrap()
+            .unwrap();
+        let fields = flatten(&fields);
+        assert_eq!(FieldElement::from_u64(8).to_string(), fields["ids.1"]);
+        assert_eq!(FieldElement::from_u64(10).to_string(), fields["pair.1"]);
+    }
+
+    #[test]
+    fn test_decode_cairo_1_event() {
+        let abi = json!([
+            {"type": "struct", "name": "core::integer::u256", "members": [
+                {"name": "low", "type": "core::integer::u128"},
+                {"name": "high", "type": "core::integer::u128"}
+            ]},
+            {"type": "struct", "name": "carbon::Absorption", "members": [
+                {"name": "time", "type": "core::integer::u64"},
+                {"name": "values", "type": "core::array::Span::<core::integer::u256>"}
+            ]},
+            {"type": "event", "name": "carbon::Snapshot", "kind": "struct", "members": [
+                {"name": "project", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "absorption", "type": "carbon::Absorption", "kind": "data"},
+                {"name": "final", "type": "core::bool", "kind": "data"}
+            ]},
+            {"type": "event", "name": "carbon::Event", "kind": "enum", "variants": [
+                {"name": "Snapshot", "type": "carbon::Snapshot", "kind": "nested"}
+            ]}
+        ]);
+        let decoder = EventDecoder::try_from(&abi).unwrap();
+
+        let (name, fields) = decoder
+            .decode(
+                &[selector("Snapshot"), FieldElement::from_u64(0x456)],
+                &[
+                    FieldElement::from_u64(1000),
+                    FieldElement::from_u64(1),
+                    FieldElement::from_u64(5),
+                    FieldElement::from_u64(0),
+                    FieldElement::from_u64(1),
+                ],
+            )
+            .unwrap()
+            .unwrap();
+        let fields = flatten(&fields);
+        assert_eq!("carbon::Snapshot", name);
+        assert_eq!(FieldElement::from_u64(0x456).to_string(), fields["project"]);
+        assert_eq!(
+            FieldElement::from_u64(1000).to_string(),
+            fields["absorption.time"]
+        );
+        assert_eq!(
+            "0x0000000000000000000000000000000000000000000000000000000000000005",
+            fields["absorption.values.0"]
+        );
+        assert_eq!("true", fields["final"]);
+    }
+
+    #[test]
+    fn test_decode_nested_component_event() {
+        let abi = json!([
+            {"type": "event", "name": "erc721::Transfer", "kind": "struct", "members": [
+                {"name": "from", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "to", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "token_id", "type": "core::integer::u256", "kind": "key"}
+            ]},
+            {"type": "event", "name": "erc721::Event", "kind": "enum", "variants": [
+                {"name": "Transfer", "type": "erc721::Transfer", "kind": "nested"}
+            ]},
+            {"type": "event", "name": "project::Event", "kind": "enum", "variants": [
+                {"name": "ERC721Event", "type": "erc721::Event", "kind": "nested"}
+            ]}
+        ]);
+        let decoder = EventDecoder::try_from(&abi).unwrap();
+        assert_eq!(
+            vec![vec![
+                selector("ERC721Event").to_string(),
+                selector("Transfer").to_string()
+            ]],
+            decoder.nested_paths()
+        );
+
+        let (name, fields) = decoder
+            .decode(
+                &[
+                    selector("ERC721Event"),
+                    selector("Transfer"),
+                    FieldElement::from_u64(0),
+                    FieldElement::from_u64(0x123),
+                    FieldElement::from_u64(7),
+                    FieldElement::from_u64(0),
+                ],
+                &[],
+            )
+            .unwrap()
+            .unwrap();
+        let fields = flatten(&fields);
+        assert_eq!("erc721::Transfer", name);
+        assert_eq!(FieldElement::from_u64(0x123).to_string(), fields["to"]);
+        assert_eq!(
+            "0x0000000000000000000000000000000000000000000000000000000000000007",
+            fields["token_id"]
+        );
+    }
+
+    #[test]
+    fn test_decode_event_named_after_its_variant() {
+        let abi = json!([
+            {"type": "event", "name": "project::TransferEvent", "kind": "struct", "members": [
+                {"name": "from", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "to", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"}
+            ]},
+            {"type": "event", "name": "project::Event", "kind": "enum", "variants": [
+                {"name": "Transfer", "type": "project::TransferEvent", "kind": "nested"}
+            ]}
+        ]);
+        let decoder = EventDecoder::try_from(&abi).unwrap();
+        assert!(decoder.nested_paths().is_empty());
+
+        let (name, fields) = decoder
+            .decode(
+                &[
+                    selector("Transfer"),
+                    FieldElement::from_u64(0),
+                    FieldElement::from_u64(0x123),
+                ],
+                &[],
+            )
+            .unwrap()
+            .unwrap();
+        let fields = flatten(&fields);
+        assert_eq!("project::TransferEvent", name);
+        assert_eq!(FieldElement::from_u64(0x123).to_string(), fields["to"]);
+
+        let type_selector = FieldElement::from_bytes(&selector!("TransferEvent").to_bytes_be());
+        assert!(decoder
+            .decode(&[type_selector, FieldElement::from_u64(0)], &[])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_unknown_or_truncated_events() {
+        let abi = json!([
+            {"type": "event", "name": "Transfer", "keys": [], "data": [
+                {"name": "from_", "type": "felt"},
+                {"name": "to", "type": "felt"}
+            ]}
+        ]);
+        let decoder = EventDecoder::try_from(&abi).unwrap();
+
+        assert!(decoder
+            .decode(&[selector("Snapshot")], &[])
+            .unwrap()
+            .is_none());
+        assert!(decoder
+            .decode(&[selector("Transfer")], &[FieldElement::from_u64(0)])
+            .is_err());
+    }
+}
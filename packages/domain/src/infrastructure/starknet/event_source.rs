@@ -3,7 +3,10 @@ use std::collections::HashMap;
 use apibara_core::starknet::v1alpha2::{BlockHeader, EventWithTransaction};
 use time::OffsetDateTime;
 
-use crate::domain::event_source::{BlockMetadata, DomainEvent, Filterable};
+use crate::domain::event_source::{
+    upcaster::{schema_version, ImplementationHistory},
+    BlockMetadata, DomainEvent, Event, Filterable,
+};
 
 impl DomainEvent {
     pub fn from_starknet_event(
@@ -41,49 +44,123 @@ impl DomainEvent {
         version = next_version;
         *last_event_idx = *version;
 
-        let mut payload: HashMap<String, String> = HashMap::new();
-
-        event.data.iter().enumerate().for_each(|(i, data)| {
-            payload.insert(i.to_string(), data.to_string());
-        });
-
         let from = event
             .from_address
             .clone()
             .expect("from address is required");
-        let key = event.keys.first().expect("key should exist");
-        if 0 == event.data.len() {
-            event.keys.iter().skip(1).enumerate().for_each(|(i, data)| {
-                payload.insert(i.to_string(), data.to_string());
-            });
-        }
+        let keys = event
+            .keys
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>();
+        let data = event
+            .data
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>();
 
-        // Will panic if event is none. Event should be extracted out of application filters
+        // Event should be extracted out of application filters
         let mut event = None;
         for filter in application_filter {
-            let found_event = filter.get_event(&from.to_string(), &key.to_string());
+            let found_event = filter.get_event(&from.to_string(), &keys);
             if found_event.is_some() {
                 event = found_event;
                 continue;
             }
         }
-        if event.is_none() {
-            tracing::error!("FROM {} KEY {}", from, key);
-        }
+        // unknown events are assumed to be keyed by their selector only
+        let path_len = event.as_ref().map_or(1, |(_, path_len)| *path_len);
+        let event = event.map(|(event, _)| event);
+        let payload = positional_payload(&keys, path_len, &data);
 
         let mut metadata = HashMap::new();
         add_tx_hash(&mut metadata, &value);
         add_contract_event_emitter(&mut metadata, &value);
 
+        // Stored anyway, projections skip and count it until it is resolved once supported,
+        // see `resolve_unknown_event`
+        let r#type = event.unwrap_or_else(|| {
+            tracing::warn!("unknown event FROM {} KEYS {:?}", from, keys);
+            Event::Unknown {
+                contract: from.to_string(),
+                selector: keys.first().cloned().unwrap_or_default(),
+            }
+        });
         DomainEvent {
             id: format!("{felt}_{version}"),
             metadata,
             payload,
-            r#type: event.expect("event should not be none at this point"),
+            keys,
+            // stamped from contract implementation at event block when stored
+            schema_version: 1,
+            r#type,
         }
     }
 }
 
+/// Positional payload of an event, members stored in keys after matched key path
+/// (Cairo 1 `#[key]` members) come first, followed by data, in declared member order
+/// * keys - [`&[String]`]
+/// * path_len - [`usize`] - Length of key path the event was matched on
+/// * data - [`&[String]`]
+///
+pub(crate) fn positional_payload(
+    keys: &[String],
+    path_len: usize,
+    data: &[String],
+) -> HashMap<String, String> {
+    keys.iter()
+        .skip(path_len)
+        .chain(data.iter())
+        .enumerate()
+        .map(|(i, value)| (i.to_string(), value.to_owned()))
+        .collect()
+}
+
+/// Resolve an event stored as unknown against current filters, `None` while its type is still
+/// not supported. Positional payload is shifted to start after the matched key path
+/// * filters - [`&mut [Box<dyn Filterable>]`] - Hydrated application filters
+/// * event - [`&DomainEvent`]
+/// * block - [`u64`] - Block the event was emitted at
+/// * history - [`&ImplementationHistory`] - Implementations of event contract
+///
+pub fn resolve_unknown_event(
+    filters: &mut [Box<dyn Filterable>],
+    event: &DomainEvent,
+    block: u64,
+    history: &ImplementationHistory,
+) -> Option<DomainEvent> {
+    let contract = match &event.r#type {
+        Event::Unknown { contract, .. } => contract,
+        _ => return None,
+    };
+    let (r#type, path_len) = filters
+        .iter_mut()
+        .find_map(|filter| filter.get_event(contract, &event.keys))?;
+
+    // unknown events positional payload starts right after their selector
+    let payload = event
+        .payload
+        .iter()
+        .filter_map(|(key, value)| match key.parse::<usize>() {
+            Ok(i) if i + 1 < path_len => None,
+            Ok(i) => Some(((i + 1 - path_len).to_string(), value.to_owned())),
+            Err(_) => Some((key.to_owned(), value.to_owned())),
+        })
+        .collect();
+
+    let mut resolved = DomainEvent {
+        id: event.id.to_owned(),
+        metadata: event.metadata.clone(),
+        payload,
+        keys: event.keys.clone(),
+        schema_version: 1,
+        r#type,
+    };
+    resolved.schema_version = schema_version(&resolved, block, history);
+    Some(resolved)
+}
+
 /// Search for transaction hash from [`EventWithTransaction`]
 /// and adds it to metadata [`HashMap`]
 fn add_tx_hash(metadata: &mut HashMap<String, String>, value: &EventWithTransaction) {
@@ -120,3 +197,95 @@ impl From<BlockHeader> for BlockMetadata {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use apibara_core::starknet::v1alpha2::FieldElement;
+    use starknet::macros::selector;
+
+    use crate::domain::{
+        crypto::U256,
+        event_source::{
+            fixtures::event,
+            payload::Transfer,
+            project::{ProjectEvents, ProjectFilters},
+            upcaster::ImplementationHistory,
+            Event, Filterable,
+        },
+        registry::{ContractType, RegisteredContract},
+    };
+
+    use super::{positional_payload, resolve_unknown_event};
+
+    fn felts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_nested_transfer_is_decoded_from_members_after_key_path() {
+        // `[component, Transfer, from, to, token_id.low, token_id.high]`, every member is a key
+        let keys = felts(&["0xc0", "0x99", "0x123", "0x789", "0x7", "0x0"]);
+        let mut transfer = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            "0x0456",
+            &[],
+        );
+        transfer.payload = positional_payload(&keys, 2, &[]);
+
+        let decoded = Transfer::try_from(&transfer).unwrap();
+        assert_eq!("0x123", decoded.from);
+        assert_eq!("0x789", decoded.to);
+        assert_eq!(U256::from(7u64), decoded.token_id);
+
+        // `#[key]` members come before data members
+        let keys = felts(&["0xc0", "0x99", "0x123", "0x789"]);
+        transfer.payload = positional_payload(&keys, 2, &felts(&["0x7", "0x0"]));
+        assert_eq!(decoded, Transfer::try_from(&transfer).unwrap());
+    }
+
+    #[test]
+    fn test_unknown_event_is_resolved_once_supported() {
+        let transfer_selector =
+            FieldElement::from_bytes(&selector!("Transfer").to_bytes_be()).to_string();
+        let keys = felts(&["0xc0", transfer_selector.as_str(), "0x123", "0x789"]);
+        let mut stored = event(
+            "0x01_0",
+            Event::Project(ProjectEvents::Transfer),
+            "0x0456",
+            &[],
+        );
+        stored.r#type = Event::Unknown {
+            contract: "0x0456".to_owned(),
+            selector: "0xc0".to_owned(),
+        };
+        stored.keys = keys.clone();
+        stored.payload = positional_payload(&keys, 1, &felts(&["0x7", "0x0"]));
+
+        let mut project_filters = ProjectFilters::new();
+        project_filters.hydrate(&[RegisteredContract {
+            address: "0x0456".to_owned(),
+            contract_type: ContractType::Project,
+            network: "testnet".to_owned(),
+            project_address: None,
+            slot: None,
+            deployment_block: 0,
+            active: true,
+        }]);
+        let mut filters: Vec<Box<dyn Filterable>> = vec![Box::new(project_filters)];
+        let history = ImplementationHistory::default();
+        assert!(resolve_unknown_event(&mut filters, &stored, 1, &history).is_none());
+
+        // component path becomes known once contract ABI exposes it
+        filters[0].add_nested_paths("0x0456", &[keys[..2].to_vec()]);
+        let resolved = resolve_unknown_event(&mut filters, &stored, 1, &history).unwrap();
+        assert!(matches!(
+            resolved.r#type,
+            Event::Project(ProjectEvents::Transfer)
+        ));
+        let decoded = Transfer::try_from(&resolved).unwrap();
+        assert_eq!("0x123", decoded.from);
+        assert_eq!("0x789", decoded.to);
+        assert_eq!(U256::from(7u64), decoded.token_id);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod badge;
 pub mod event_source;
 pub mod farming;
@@ -43,6 +44,13 @@ pub enum SequencerError {
     InvalidHeaderValueError(#[from] InvalidHeaderValue),
 }
 
+impl SequencerError {
+    /// Whether calling sequencer again later may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SequencerError::ReqwestClientError(_))
+    }
+}
+
 pub enum StarknetEnv {
     Mainnet,
     Goerli,
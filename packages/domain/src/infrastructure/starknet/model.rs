@@ -57,6 +57,18 @@ pub enum ModelError {
     NotReadyForFarming,
 }
 
+impl ModelError {
+    /// Whether calling node again later may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ModelError::RateLimited | ModelError::ReqwestError(_) => true,
+            ModelError::ProviderError(JsonRpcClientError::TransportError(_)) => true,
+            ModelError::FailedToBuildModel(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait StarknetModel<T> {
     async fn load(&self) -> Result<T, ModelError>;
@@ -11,6 +11,7 @@ use starknet::{
         Provider,
     },
 };
+use time::{OffsetDateTime, PrimitiveDateTime};
 use tracing::info;
 
 use crate::{
@@ -24,8 +25,8 @@ use crate::{
 use super::{
     get_starknet_provider_from_env, get_starknet_rpc_from_env,
     model::{
-        felt_to_u256, get_call_function, load_blockchain_data, u256_to_felt, ModelError,
-        StarknetModel, StarknetValue,
+        felt_to_u256, get_call_function, load_blockchain_data, parallelize_blockchain_rpc_calls,
+        u256_to_felt, ModelError, StarknetModel, StarknetValue,
     },
     uri::UriModel,
 };
@@ -201,6 +202,96 @@ pub(crate) fn get_slug_from_uri(external_url: &str) -> String {
         .to_string()
 }
 
+/// Fetch project uri from blockchain and metadata it points to,
+/// `slot_uri` of slot for an ERC-3525 project, `contractURI` for an ERC-721 one
+/// * provider - [`Arc<JsonRpcClient<HttpTransport>>`]
+/// * address - [`&str`]
+/// * slot - [`Option<&U256>`]
+///
+pub async fn load_project_uri_metadata(
+    provider: Arc<JsonRpcClient<HttpTransport>>,
+    address: &str,
+    slot: Option<&U256>,
+) -> Result<(String, serde_json::Value), ModelError> {
+    let call = match slot {
+        Some(slot) => (
+            address.to_owned(),
+            "slot_uri",
+            vec![u256_to_felt(slot), FieldElement::ZERO],
+        ),
+        None => (address.to_owned(), "contractURI", vec![]),
+    };
+    let data = parallelize_blockchain_rpc_calls(provider, vec![call]).await?;
+    let uri: String = StarknetValue::new(data[0].clone())
+        .resolve("string_array")
+        .into();
+
+    let metadata = match slot {
+        Some(_) => serde_json::to_value(UriModel::<Erc3525>::new(uri.clone())?.load().await?)?,
+        None => serde_json::to_value(UriModel::<Erc721>::new(uri.clone())?.load().await?)?,
+    };
+    Ok((uri, metadata))
+}
+
+/// Fetch project absorption curve at given block, `get_times` and `get_absorptions` of slot
+/// for an ERC-3525 project, `getTimes` and `getAbsorptions` for an ERC-721 one
+/// * provider - [`Arc<JsonRpcClient<HttpTransport>>`]
+/// * address - [`&str`]
+/// * slot - [`Option<&U256>`]
+/// * block_number - [`u64`]
+///
+pub async fn load_project_absorption_curve(
+    provider: Arc<JsonRpcClient<HttpTransport>>,
+    address: &str,
+    slot: Option<&U256>,
+    block_number: u64,
+) -> Result<(Vec<PrimitiveDateTime>, Vec<U256>), ModelError> {
+    let contract_address = FieldElement::from_hex_be(address)
+        .map_err(|_| ModelError::TypeConversionError(address.to_owned(), "felt".to_owned()))?;
+    let (selectors, calldata) = match slot {
+        Some(slot) => (
+            ["get_times", "get_absorptions"],
+            vec![u256_to_felt(slot), FieldElement::ZERO],
+        ),
+        None => (["getTimes", "getAbsorptions"], vec![]),
+    };
+    let block = BlockId::Number(block_number);
+    let times = provider
+        .call(
+            get_call_function(&contract_address, selectors[0], calldata.clone()),
+            &block,
+        )
+        .await?;
+    let absorptions = provider
+        .call(
+            get_call_function(&contract_address, selectors[1], calldata),
+            &block,
+        )
+        .await?;
+
+    // both are arrays, first felt is their length
+    let times = times
+        .iter()
+        .skip(1)
+        .map(|time| {
+            u64::try_from(*time)
+                .ok()
+                .and_then(|time| OffsetDateTime::from_unix_timestamp(time as i64).ok())
+                .map(|time| PrimitiveDateTime::new(time.date(), time.time()))
+                .ok_or_else(|| {
+                    ModelError::TypeConversionError(time.to_string(), "datetime".to_owned())
+                })
+        })
+        .collect::<Result<Vec<PrimitiveDateTime>, ModelError>>()?;
+    let absorptions = absorptions
+        .iter()
+        .skip(1)
+        .map(|absorption| felt_to_u256(*absorption))
+        .collect();
+
+    Ok((times, absorptions))
+}
+
 pub async fn get_slot_uri_from_feeder(
     contract_address: FieldElement,
     slot: &U256,
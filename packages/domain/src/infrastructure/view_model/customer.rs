@@ -38,3 +38,47 @@ impl From<tokio_postgres::Row> for CustomerToken {
         }
     }
 }
+
+/// Operator approved on a customer token, on whole token or up to `value`
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerTokenApproval {
+    pub project_address: String,
+    pub token_id: U256,
+    pub operator: String,
+    pub approved: bool,
+    pub value: Option<U256>,
+}
+
+impl From<tokio_postgres::Row> for CustomerTokenApproval {
+    fn from(value: tokio_postgres::Row) -> Self {
+        Self {
+            project_address: value.get(0),
+            token_id: value.get(1),
+            operator: value.get(2),
+            approved: value.get(3),
+            value: value.get(4),
+        }
+    }
+}
+
+/// Operator approved on every customer token of project
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerOperatorApproval {
+    pub project_address: String,
+    pub operator: String,
+}
+
+impl From<tokio_postgres::Row> for CustomerOperatorApproval {
+    fn from(value: tokio_postgres::Row) -> Self {
+        Self {
+            project_address: value.get(0),
+            operator: value.get(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerApprovals {
+    pub tokens: Vec<CustomerTokenApproval>,
+    pub operators: Vec<CustomerOperatorApproval>,
+}
@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, HashSet};
+
 use crate::domain::{crypto::U256, Erc20, HumanComprehensibleU256, SlotValue};
 
 use super::project::UriViewModel;
@@ -217,3 +219,177 @@ impl From<tokio_postgres::Row> for LaunchpadProject {
         }
     }
 }
+
+/// Token bought or airdropped through project minter, `amount` is only paid on buy
+#[derive(Debug)]
+pub struct MinterSale {
+    pub address: String,
+    pub quantity: U256,
+    pub amount: Option<U256>,
+    pub time: PrimitiveDateTime,
+    pub payment_address: Option<String>,
+    pub payment_decimals: Option<U256>,
+    pub payment_symbol: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for MinterSale {
+    fn from(value: tokio_postgres::Row) -> Self {
+        Self {
+            address: value.get(0),
+            quantity: value.get(1),
+            amount: value.get(2),
+            time: value.get(3),
+            payment_address: value.get(4),
+            payment_decimals: value.get(5),
+            payment_symbol: value.get(6),
+        }
+    }
+}
+
+/// Tokens minted on a given day, UTC, volume is given per payment token
+#[derive(Debug, Default, Serialize)]
+pub struct DailyMint {
+    date: String,
+    bought: U256,
+    airdropped: U256,
+    volume: Vec<HumanComprehensibleU256<Erc20>>,
+}
+
+/// Sales of project minters, as minters may not share the same payment token volume is never
+/// summed across tokens
+#[derive(Debug, Serialize)]
+pub struct LaunchpadSales {
+    volume: Vec<HumanComprehensibleU256<Erc20>>,
+    bought: U256,
+    airdropped: U256,
+    buyers: usize,
+    daily_mints: Vec<DailyMint>,
+}
+
+impl LaunchpadSales {
+    /// Aggregate project minter sales
+    /// * sales - [`&[MinterSale]`]
+    ///
+    pub fn new(sales: &[MinterSale]) -> Self {
+        let mut volume: BTreeMap<String, Erc20> = BTreeMap::new();
+        let mut bought = U256::zero();
+        let mut airdropped = U256::zero();
+        let mut buyers = HashSet::new();
+        let mut daily_mints: BTreeMap<time::Date, DailyMint> = BTreeMap::new();
+        let mut daily_volume: BTreeMap<time::Date, BTreeMap<String, Erc20>> = BTreeMap::new();
+
+        for sale in sales {
+            let day = daily_mints
+                .entry(sale.time.date())
+                .or_insert_with(|| DailyMint {
+                    date: sale.time.date().to_string(),
+                    ..Default::default()
+                });
+            match sale.amount {
+                Some(amount) => {
+                    let payment = sale.payment_address.clone().unwrap_or_default();
+                    let paid = Erc20::from_blockchain(
+                        amount,
+                        sale.payment_decimals.unwrap_or_default(),
+                        sale.payment_symbol.clone().unwrap_or_default(),
+                    );
+                    *volume.entry(payment.clone()).or_default() += paid.clone();
+                    *daily_volume
+                        .entry(sale.time.date())
+                        .or_default()
+                        .entry(payment)
+                        .or_default() += paid;
+                    bought += sale.quantity;
+                    buyers.insert(sale.address.to_lowercase());
+                    day.bought += sale.quantity;
+                }
+                None => {
+                    airdropped += sale.quantity;
+                    day.airdropped += sale.quantity;
+                }
+            }
+        }
+
+        for (date, payments) in daily_volume {
+            if let Some(day) = daily_mints.get_mut(&date) {
+                day.volume = payments.into_values().map(|v| v.into()).collect();
+            }
+        }
+
+        Self {
+            volume: volume.into_values().map(|v| v.into()).collect(),
+            bought,
+            airdropped,
+            buyers: buyers.len(),
+            daily_mints: daily_mints.into_values().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::domain::{crypto::U256, Erc20, HumanComprehensibleU256};
+
+    use super::{LaunchpadSales, MinterSale};
+
+    fn buy(
+        address: &str,
+        amount: u64,
+        payment: (&str, u64, &str),
+        time: time::PrimitiveDateTime,
+    ) -> MinterSale {
+        MinterSale {
+            address: address.to_owned(),
+            quantity: U256::from(1u64),
+            amount: Some(U256::from(amount)),
+            time,
+            payment_address: Some(payment.0.to_owned()),
+            payment_decimals: Some(U256::from(payment.1)),
+            payment_symbol: Some(payment.2.to_owned()),
+        }
+    }
+
+    fn erc20(amount: u64, decimals: u64, symbol: &str) -> HumanComprehensibleU256<Erc20> {
+        Erc20::from_blockchain(U256::from(amount), U256::from(decimals), symbol.to_owned()).into()
+    }
+
+    #[test]
+    fn test_sales_volume_is_given_per_payment_token() {
+        let usdc = ("0x0a", 6, "USDC");
+        let eth = ("0x0b", 18, "ETH");
+        let sales = LaunchpadSales::new(&[
+            buy("0x0ABC", 2_000_000, usdc, datetime!(2023-11-14 10:00)),
+            buy("0x0abc", 1_000, eth, datetime!(2023-11-14 12:00)),
+            buy("0x0def", 3_000_000, usdc, datetime!(2023-11-15 10:00)),
+            MinterSale {
+                address: "0x0fed".to_owned(),
+                quantity: U256::from(4u64),
+                amount: None,
+                time: datetime!(2023-11-15 11:00),
+                payment_address: None,
+                payment_decimals: None,
+                payment_symbol: None,
+            },
+        ]);
+
+        assert_eq!(
+            vec![erc20(5_000_000, 6, "USDC"), erc20(1_000, 18, "ETH")],
+            sales.volume
+        );
+        assert_eq!(U256::from(3u64), sales.bought);
+        assert_eq!(U256::from(4u64), sales.airdropped);
+        assert_eq!(2, sales.buyers);
+        assert_eq!(2, sales.daily_mints.len());
+        assert_eq!(
+            vec![erc20(2_000_000, 6, "USDC"), erc20(1_000, 18, "ETH")],
+            sales.daily_mints[0].volume
+        );
+        assert_eq!(
+            vec![erc20(3_000_000, 6, "USDC")],
+            sales.daily_mints[1].volume
+        );
+        assert_eq!(U256::from(4u64), sales.daily_mints[1].airdropped);
+    }
+}
@@ -21,12 +21,14 @@ pub struct DomainEventViewModel {
     pub payload: HashMap<String, String>,
     pub r#type: Event,
     pub recorded_at: PrimitiveDateTime,
+    pub keys: Vec<String>,
+    pub schema_version: u32,
 }
 
 impl From<Row> for DomainEventViewModel {
     fn from(value: Row) -> Self {
         let metadata = json_value_to_hash_map(
-            value.get(4),
+            value.get("metadata"),
             &[
                 "block_hash",
                 "block_number",
@@ -35,17 +37,30 @@ impl From<Row> for DomainEventViewModel {
                 "tx_hash",
             ],
         );
-        let payload: HashMap<String, String> = serde_json::from_value(value.get(5))
+        let payload: HashMap<String, String> = serde_json::from_value(value.get("payload"))
             .expect("failed to deserialize payload to hash_map");
+        // events stored before keys were captured have none
+        let keys: Vec<String> = value
+            .get::<&str, Option<serde_json::Value>>("keys")
+            .and_then(|keys| serde_json::from_value(keys).ok())
+            .unwrap_or_default();
+        // event types removed since they were stored are replayed as unknown events
+        let r#type: String = value.get("r_type");
+        let r#type = Event::try_from(r#type.as_str()).unwrap_or_else(|_| Event::Unknown {
+            contract: metadata.get("from_address").cloned().unwrap_or_default(),
+            selector: keys.first().cloned().unwrap_or(r#type),
+        });
         Self {
-            id: value.get(0),
-            event_id: value.get(1),
-            block_number: value.get(2),
-            block_hash: value.get(3),
+            id: value.get("id"),
+            event_id: value.get("event_id"),
+            block_number: value.get("block_number"),
+            block_hash: value.get("block_hash"),
             metadata,
             payload,
-            r#type: value.get(6),
-            recorded_at: value.get(7),
+            r#type,
+            recorded_at: value.get("recorded_at"),
+            keys,
+            schema_version: value.get::<&str, i32>("schema_version") as u32,
         }
     }
 }
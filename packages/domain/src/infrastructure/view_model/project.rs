@@ -1,7 +1,8 @@
-use crate::domain::Ulid;
+use crate::domain::{crypto::U256, Ulid};
 use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, PrimitiveDateTime};
 
-use crate::infrastructure::postgres::entity::ErcImplementation;
+use crate::infrastructure::postgres::entity::{AbsorptionCurve, ErcImplementation};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UriViewModel {
@@ -56,3 +57,29 @@ impl From<tokio_postgres::Row> for ProjectViewModel {
         }
     }
 }
+
+/// Revision of project absorption curve, `times` are unix timestamps as set on chain
+#[derive(Debug, Serialize)]
+pub struct AbsorptionCurveRevision {
+    version: i32,
+    times: Vec<i64>,
+    absorptions: Vec<U256>,
+    final_absorption: U256,
+    event_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+}
+
+impl From<AbsorptionCurve> for AbsorptionCurveRevision {
+    fn from(value: AbsorptionCurve) -> Self {
+        let unix_timestamp = |time: PrimitiveDateTime| time.assume_utc().unix_timestamp();
+        Self {
+            version: value.version,
+            times: value.times.into_iter().map(unix_timestamp).collect(),
+            final_absorption: *value.absorptions.last().unwrap_or(&U256::zero()),
+            absorptions: value.absorptions,
+            event_id: value.event_id,
+            updated_at: value.time.assume_utc(),
+        }
+    }
+}
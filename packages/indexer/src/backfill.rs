@@ -0,0 +1,193 @@
+use std::{sync::Arc, time::Duration};
+
+use apibara_core::{
+    node::v1alpha2::{Cursor, DataFinality},
+    starknet::v1alpha2::{Block, Filter},
+};
+use apibara_sdk::{Configuration, DataMessage};
+use carbonable_domain::infrastructure::{
+    postgres::{
+        backfill::{complete_backfill, get_backfills, Backfill},
+        checkpoint::get_checkpoint,
+        implementation::get_contract_abis,
+        registry::get_registered_contracts,
+    },
+    starknet::abi::AbiRegistry,
+};
+use deadpool_postgres::Pool;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+use crate::{
+    filters::{application_filters, configure_stream_filters},
+    source::BlockSource,
+    stream::{create_event_bus, PostgresEventSink, StreamIndexer},
+    IndexerError,
+};
+
+/// [`BlockSource`] wrapper ending the stream once it reached the live stream checkpoint
+pub struct CatchUpBlockSource<S: BlockSource> {
+    inner: S,
+    db_client_pool: Arc<Pool>,
+    live_stream: String,
+    caught_up: bool,
+}
+
+impl<S: BlockSource> CatchUpBlockSource<S> {
+    /// * `inner` - [`BlockSource`]
+    /// * `db_client_pool` - [`Arc<Pool>`]
+    /// * `live_stream` - [`&str`] - Checkpoint stream name of the live stream to catch up with
+    ///
+    pub fn new(inner: S, db_client_pool: Arc<Pool>, live_stream: &str) -> Self {
+        Self {
+            inner,
+            db_client_pool,
+            live_stream: live_stream.to_owned(),
+            caught_up: false,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<S: BlockSource> BlockSource for CatchUpBlockSource<S> {
+    async fn connect(
+        &mut self,
+        configuration: Configuration<Filter>,
+        starting_cursor: Cursor,
+    ) -> Result<(), IndexerError> {
+        self.inner.connect(configuration, starting_cursor).await
+    }
+
+    async fn reconfigure(
+        &mut self,
+        configuration: Configuration<Filter>,
+    ) -> Result<(), IndexerError> {
+        self.inner.reconfigure(configuration).await
+    }
+
+    async fn next_message(&mut self) -> Result<Option<DataMessage<Block>>, IndexerError> {
+        if self.caught_up {
+            return Ok(None);
+        }
+        let message = self.inner.next_message().await?;
+        if let Some(DataMessage::Data { end_cursor, .. }) = &message {
+            let client = self.db_client_pool.get().await?;
+            let live_block = get_checkpoint(&client, &self.live_stream)
+                .await?
+                .map(|c| c.order_key)
+                .unwrap_or_default();
+            self.caught_up = end_cursor.order_key >= live_block;
+        }
+
+        Ok(message)
+    }
+
+    fn is_live(&self) -> bool {
+        !self.caught_up && self.inner.is_live()
+    }
+}
+
+/// Runs pending backfills of a network, one after the other, alongside the live stream
+pub struct BackfillRunner<S: BlockSource, F: Fn() -> S> {
+    network: String,
+    db_client_pool: Arc<Pool>,
+    source_factory: F,
+    batch_size: Option<u64>,
+    interval: Duration,
+}
+
+impl<S: BlockSource, F: Fn() -> S> BackfillRunner<S, F> {
+    /// * `network` - [`&str`] - Network name, checkpoint stream name of the live stream
+    /// * `db_client_pool` - [`Arc<Pool>`]
+    /// * `source_factory` - Creates a new [`BlockSource`] per backfill
+    /// * `batch_size` - [`Option<u64>`] - Number of blocks per stream message
+    /// * `interval` - [`Duration`] - Delay between two checks for pending backfills
+    ///
+    pub fn new(
+        network: &str,
+        db_client_pool: Arc<Pool>,
+        source_factory: F,
+        batch_size: Option<u64>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            network: network.to_owned(),
+            db_client_pool,
+            source_factory,
+            batch_size,
+            interval,
+        }
+    }
+
+    /// Run pending backfills forever
+    pub async fn run(&self) -> Result<(), IndexerError> {
+        loop {
+            let backfills = {
+                let client = self.db_client_pool.get().await?;
+                get_backfills(&client, &self.network).await?
+            };
+            for backfill in backfills.iter().filter(|b| !b.is_done()) {
+                if let Err(e) = self.run_backfill(backfill).await {
+                    error!("Backfill {} failed: {}", backfill.id, e);
+                }
+            }
+            sleep(self.interval).await;
+        }
+    }
+
+    /// Stream backfill contracts history until live stream is reached
+    /// * `backfill` - [`&Backfill`]
+    ///
+    async fn run_backfill(&self, backfill: &Backfill) -> Result<(), IndexerError> {
+        let (contracts, starting_cursor, abis) = {
+            let client = self.db_client_pool.get().await?;
+            let contracts = get_registered_contracts(&client, &self.network, true)
+                .await?
+                .into_iter()
+                .filter(|c| backfill.contracts.contains(&c.address))
+                .collect::<Vec<_>>();
+            let starting_cursor = get_checkpoint(&client, &backfill.stream())
+                .await?
+                .unwrap_or(Cursor {
+                    order_key: backfill.start_block,
+                    unique_key: vec![],
+                });
+            let abis = AbiRegistry::new(get_contract_abis(&client).await?);
+            (contracts, starting_cursor, abis)
+        };
+        info!(
+            "Running backfill {} from block {}",
+            backfill.id, starting_cursor.order_key
+        );
+
+        let mut filters = application_filters();
+        // accepted blocks may still be reorganized, backfill invalidations only remove events
+        // stored by the backfill stream so that live stream data is left untouched
+        let configuration = configure_stream_filters(
+            &contracts,
+            &mut filters,
+            &abis,
+            &backfill.start_block,
+            self.batch_size,
+            DataFinality::DataStatusAccepted,
+        )?;
+        let source = CatchUpBlockSource::new(
+            (self.source_factory)(),
+            self.db_client_pool.clone(),
+            &self.network,
+        );
+        let sink = PostgresEventSink::new(
+            self.db_client_pool.clone(),
+            create_event_bus(self.db_client_pool.clone()),
+            &backfill.stream(),
+        );
+        let last_cursor = StreamIndexer::new(source, sink, filters, configuration)
+            .with_abis(abis)
+            .run(starting_cursor)
+            .await?;
+
+        let mut client = self.db_client_pool.get().await?;
+        complete_backfill(&mut client, backfill, last_cursor.order_key).await?;
+        Ok(())
+    }
+}
@@ -4,7 +4,14 @@ use apibara_core::{
 };
 use apibara_sdk::Configuration;
 use carbonable_domain::{
-    domain::event_source::Filterable, infrastructure::seed::read_data_content,
+    domain::{
+        event_source::{
+            minter::MinterFilters, offseter::OffsetFilters, project::ProjectFilters,
+            yielder::YieldFilters, Filterable,
+        },
+        registry::RegisteredContract,
+    },
+    infrastructure::starknet::abi::AbiRegistry,
 };
 
 use crate::IndexerError;
@@ -15,35 +22,68 @@ use crate::IndexerError;
 ///
 #[allow(clippy::borrowed_box)]
 fn configure_filter_item(filter: &mut Filter, application_filter: &Box<dyn Filterable>) {
-    for af in application_filter.to_filters() {
+    for (address, path) in application_filter.to_filters() {
+        // apibara matches events whose keys start with the given ones
         filter.add_event(|e| {
-            e.with_from_address(FieldElement::from_hex(&af.0).unwrap())
-                .with_keys([FieldElement::from_hex(&af.1).unwrap()].to_vec())
+            e.with_from_address(FieldElement::from_hex(&address).unwrap())
+                .with_keys(
+                    path.iter()
+                        .map(|k| FieldElement::from_hex(k).unwrap())
+                        .collect(),
+                )
         });
     }
 }
 
+/// Every application filter, not hydrated yet
+pub fn application_filters() -> Vec<Box<dyn Filterable>> {
+    vec![
+        Box::new(ProjectFilters::new()),
+        Box::new(YieldFilters::new()),
+        Box::new(OffsetFilters::new()),
+        Box::new(MinterFilters::new()),
+    ]
+}
+
+/// Hydrate application filters from contract registry
+/// * `contracts` - Contract registry entries, inactive ones are skipped
+/// * `application_filters` - The application filters to hydrate
+/// * `abis` - Contract ABI, routes events emitted through nested components
+///
+pub fn hydrate_filters(
+    contracts: &[RegisteredContract],
+    application_filters: &mut [Box<dyn Filterable>],
+    abis: &AbiRegistry,
+) {
+    for filter in application_filters.iter_mut() {
+        filter.hydrate(contracts);
+        for contract in contracts.iter().filter(|c| c.active) {
+            filter.add_nested_paths(&contract.address, &abis.nested_paths(&contract.address));
+        }
+    }
+}
+
 /// Configure stream filters for apibara
-/// * `app_config` - The application configuration
-/// * `file_path` - The path to the file containing the contract addresses
+/// * `contracts` - Contract registry entries, inactive ones are skipped
 /// * `application_filters` - The application filters to use
+/// * `abis` - Contract ABI, routes events emitted through nested components
 /// * `last_block_id` - The last block id to start from
+/// * `batch_size` - Number of blocks sent per stream message, apibara default when `None`
+/// * `finality` - Minimum finality of streamed blocks
 ///
-pub fn configure_stream_filters<P: AsRef<std::path::Path>>(
-    file_path: P,
+pub fn configure_stream_filters(
+    contracts: &[RegisteredContract],
     application_filters: &mut [Box<dyn Filterable>],
+    abis: &AbiRegistry,
     last_block_id: &u64,
+    batch_size: Option<u64>,
+    finality: DataFinality,
 ) -> Result<Configuration<Filter>, IndexerError> {
-    let content = read_data_content(file_path)?;
-
-    for filter in application_filters.iter_mut() {
-        filter.hydrate_from_file(content.clone());
-    }
+    hydrate_filters(contracts, application_filters, abis);
 
-    let config = Configuration::<Filter>::default()
+    let mut config = Configuration::<Filter>::default()
         .with_starting_block(*last_block_id)
-        // .with_batch_size(app_config.batch_size)
-        .with_finality(DataFinality::DataStatusPending)
+        .with_finality(finality)
         .with_filter(|mut filter| {
             filter.with_header(HeaderFilter::weak());
             for f in application_filters.iter() {
@@ -51,6 +91,9 @@ pub fn configure_stream_filters<P: AsRef<std::path::Path>>(
             }
             filter.build()
         });
+    if let Some(batch_size) = batch_size {
+        config = config.with_batch_size(batch_size);
+    }
 
     Ok(config)
 }
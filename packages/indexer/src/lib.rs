@@ -1,5 +1,20 @@
+pub mod backfill;
 pub mod filters;
+pub mod projection;
+pub mod record;
+pub mod registry;
+pub mod reload;
+pub mod seed;
+pub mod source;
+pub mod stream;
 
+#[cfg(test)]
+mod test_utils;
+
+use carbonable_domain::{
+    domain::{event_source::DomainError, registry::RegistryError},
+    infrastructure::{postgres::PostgresError, seed::DataSeederError},
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,4 +23,22 @@ pub enum IndexerError {
     FilterConfigurationFailed,
     #[error(transparent)]
     FailedToReadDataContent(#[from] Box<dyn std::error::Error>),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    #[error("block source failure: {0}")]
+    StreamError(String),
+    #[error(transparent)]
+    DomainError(#[from] DomainError),
+    #[error(transparent)]
+    PostgresError(#[from] PostgresError),
+    #[error(transparent)]
+    PoolError(#[from] deadpool_postgres::PoolError),
+    #[error(transparent)]
+    DataSeederError(#[from] DataSeederError),
+    #[error(transparent)]
+    RegistryError(#[from] RegistryError),
+    #[error("{0} is not a valid failed event id")]
+    InvalidFailedEventId(String),
 }
@@ -1,57 +1,53 @@
 use std::{sync::Arc, time::Duration};
 
-use apibara_core::starknet::v1alpha2::{Block, Filter};
-use apibara_sdk::{ClientBuilder, Uri};
+use apibara_core::node::v1alpha2::{Cursor, DataFinality};
+use apibara_sdk::Uri;
 use carbonable_domain::{
     domain::{
         event_source::{
-            event_bus::{Consumer, EventBus},
-            minter::{
-                MinterAirdropEventConsumer, MinterBuyEventConsumer, MinterFilters,
-                MinterMigrationEventConsumer,
-            },
-            offseter::{
-                OffsetFilters, OffseterClaimEventConsumer, OffseterDepositEventConsumer,
-                OffseterUpgradedEventConsumer, OffseterWithdrawEventConsumer,
-            },
-            project::{
-                ProjectFilters, ProjectProjectValueUpdateEventConsumer,
-                ProjectSlotChangedEventConsumer, ProjectTransferEventConsumer,
-                ProjectTransferValueEventConsumer,
-            },
-            yielder::{
-                YieldFilters, YielderClaimEventConsumer, YielderDepositEventConsumer,
-                YielderProvisionEventConsumer, YielderSnapshotEventConsumer,
-                YielderWithdrawEventConsumer,
-            },
-            BlockMetadata, DomainEvent, Filterable,
+            projection::{Projection, ProjectionStatus},
+            retry::RetryPolicy,
+            BlockMetadata, DomainEvent,
         },
-        Erc3525, Erc721,
+        registry::{ContractType, RegisteredContract},
+        Ulid,
     },
     infrastructure::{
-        app::{Cli, Commands},
+        app::{Cli, Commands, FailedEventCommands, ProjectionCommands, RegistryCommands},
         postgres::{
-            event_store::{
-                batch_events, clear_view_models, get_last_dispatched_block, get_last_handled_event,
-                store_last_handled_event,
-            },
-            get_connection, PostgresModels,
-        },
-        seed::{
-            badge::BadgeSeeder, migrator::MigratorSeeder, minter::MinterSeeder,
-            offseter::OffseterSeeder, project::ProjectSeeder, yielder::YielderSeeder, DataSeeder,
-            Seeder,
+            backfill::get_backfills,
+            checkpoint::get_checkpoint,
+            event_store::{clear_view_models, get_last_dispatched_block, get_stored_event},
+            failed_event::{delete_failed_event, get_failed_event, get_failed_events},
+            get_connection,
+            implementation::get_contract_abis,
+            projection::{get_projection_states, rebuild_projection, set_projection_status},
+            registry::{deactivate_contract, get_registered_contracts, register_contract},
         },
+        seed::DataSeeder,
+        starknet::abi::AbiRegistry,
     },
 };
 
-use carbonable_indexer::filters::configure_stream_filters;
+use carbonable_indexer::{
+    backfill::BackfillRunner,
+    filters::{application_filters, configure_stream_filters, hydrate_filters},
+    projection::{resolve_unknown_events, run_projections},
+    record::BlockRecorder,
+    registry::{import_data_file, load_contract_registry},
+    reload::RegistryWatcher,
+    seed::create_seeders,
+    source::{ApibaraBlockSource, RecordedBlockSource},
+    stream::{create_event_bus, PostgresEventSink, StreamIndexer},
+    IndexerError,
+};
 
 use clap::Parser;
-use deadpool_postgres::{Pool, Transaction};
-use futures::TryStreamExt;
-use tokio::time::sleep;
-use tracing::{debug, error, info};
+use deadpool_postgres::Pool;
+use tracing::{error, info, warn};
+
+const CONTRACT_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+const PROJECTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -67,7 +63,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             database_uri: _,
         } => {
             let file_path = format!("./data/{}.data.json", network);
-            handle_seeding(db_client_pool.clone(), file_path.as_str()).await
+            handle_seeding(db_client_pool.clone(), network.as_str(), file_path.as_str()).await
         }
         Commands::Index {
             network,
@@ -76,62 +72,108 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             apibara_uri,
             apibara_token,
             starting_block,
-            batch_size: _,
+            batch_size,
+            finality,
             force,
+            record,
         } => {
             let file_path = format!("./data/{}.data.json", network);
+            let recorder = match record {
+                Some(dir) => Some(BlockRecorder::new(dir)?),
+                None => None,
+            };
             handle_indexing(
                 db_client_pool.clone(),
+                network.as_str(),
                 file_path.as_str(),
                 apibara_uri.as_str(),
                 apibara_token.as_str(),
-                starting_block.unwrap_or(0),
+                starting_block,
+                batch_size,
+                finality.into(),
                 force,
+                recorder,
             )
             .await
         }
+        Commands::Replay {
+            network,
+            database_uri: _,
+            from,
+        } => {
+            let file_path = format!("./data/{}.data.json", network);
+            handle_replay(
+                db_client_pool.clone(),
+                network.as_str(),
+                file_path.as_str(),
+                from.as_str(),
+            )
+            .await
+        }
+        Commands::Registry {
+            network,
+            database_uri: _,
+            command,
+        } => handle_registry(db_client_pool.clone(), network.as_str(), command).await,
+        Commands::FailedEvents {
+            network: _,
+            database_uri: _,
+            command,
+        } => handle_failed_events(db_client_pool.clone(), command).await,
         Commands::EventStore {
             database_uri: _,
             gateway: _,
-            network: _,
+            network,
             flush,
+            projection,
+            retry_attempts,
+            retry_delay_ms,
+            command,
         } => {
             if flush {
                 return handle_refresh_event_store(db_client_pool.clone()).await;
             }
-            handle_event_store(db_client_pool.clone()).await
+            if let Some(command) = command {
+                return handle_projection(db_client_pool.clone(), command).await;
+            }
+            let projections = match projection.is_empty() {
+                true => Projection::all().to_vec(),
+                false => projection
+                    .iter()
+                    .map(|p| Projection::try_from(p.as_str()))
+                    .collect::<Result<Vec<Projection>, _>>()?,
+            };
+            let retry_policy = RetryPolicy {
+                max_attempts: retry_attempts,
+                base_delay: Duration::from_millis(retry_delay_ms),
+                ..Default::default()
+            };
+            handle_event_store(
+                db_client_pool.clone(),
+                network.as_str(),
+                projections,
+                retry_policy,
+            )
+            .await
         }
     }
 }
 
 /// Data seeding
 /// * db_client_pool - [`Arc<Pool>`]
-/// * configuration - [`&Args`]
-/// * file_path - [`&str`]
+/// * network - [`&str`]
+/// * file_path - [`&str`] - Legacy data file, imported when registry is empty
 ///
 async fn handle_seeding(
     db_client_pool: Arc<Pool>,
+    network: &str,
     file_path: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let db_models = Arc::new(PostgresModels::<Erc721>::new(db_client_pool.clone()));
-    let db_models_3525 = Arc::new(PostgresModels::<Erc3525>::new(db_client_pool.clone()));
+    let client = db_client_pool.get().await?;
+    let contracts = load_contract_registry(&client, network, file_path).await?;
+    let seeders = create_seeders(db_client_pool);
 
-    let seeders: Vec<Arc<dyn Seeder + Send + Sync>> = vec![
-        Arc::new(ProjectSeeder::<Erc721>::new(db_models.clone())),
-        Arc::new(ProjectSeeder::<Erc3525>::new(db_models_3525.clone())),
-        Arc::new(BadgeSeeder::<Erc721>::new(db_models.clone())),
-        Arc::new(BadgeSeeder::<Erc3525>::new(db_models_3525.clone())),
-        Arc::new(MinterSeeder::<Erc721>::new(db_models.clone())),
-        Arc::new(MinterSeeder::<Erc3525>::new(db_models_3525.clone())),
-        Arc::new(OffseterSeeder::<Erc721>::new(db_models.clone())),
-        Arc::new(OffseterSeeder::<Erc3525>::new(db_models_3525.clone())),
-        Arc::new(YielderSeeder::<Erc721>::new(db_models)),
-        Arc::new(YielderSeeder::<Erc3525>::new(db_models_3525.clone())),
-        Arc::new(MigratorSeeder::new(db_models_3525)),
-    ];
-
-    match DataSeeder::feed_from_data(&file_path, seeders)
-        .await?
+    match DataSeeder::feed_from_registry(&contracts, seeders)
         .seed()
         .await
     {
@@ -146,165 +188,367 @@ async fn handle_seeding(
     }
 }
 
+/// Contract registry management
+/// * db_client_pool - [`Arc<Pool>`]
+/// * network - [`&str`]
+/// * command - [`RegistryCommands`]
+///
+async fn handle_registry(
+    db_client_pool: Arc<Pool>,
+    network: &str,
+    command: RegistryCommands,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = db_client_pool.get().await?;
+    match command {
+        RegistryCommands::Add {
+            address,
+            contract_type,
+            project,
+            slot,
+            deployment_block,
+        } => {
+            let contract = RegisteredContract {
+                address,
+                contract_type: ContractType::try_from(contract_type.as_str())?,
+                network: network.to_owned(),
+                project_address: project,
+                slot,
+                deployment_block,
+                active: true,
+            };
+            register_contract(&client, &contract).await?;
+            info!("Registered {} {}", contract.contract_type, contract.address);
+        }
+        RegistryCommands::List { all } => {
+            for contract in get_registered_contracts(&client, network, !all).await? {
+                println!(
+                    "{}\t{}\tproject={}\tslot={}\tdeployment_block={}\tactive={}",
+                    contract.address,
+                    contract.contract_type,
+                    contract.project_address.unwrap_or_default(),
+                    contract.slot.map(|s| s.to_string()).unwrap_or_default(),
+                    contract.deployment_block,
+                    contract.active,
+                );
+            }
+        }
+        RegistryCommands::Deactivate { address } => {
+            match deactivate_contract(&client, network, &address).await? {
+                0 => warn!("Contract {} is not registered on {}", address, network),
+                // running indexers drop it from their stream at next registry check
+                _ => info!("Deactivated {}", address),
+            }
+        }
+        RegistryCommands::Import { from } => {
+            let file_path = from.unwrap_or(format!("./data/{}.data.json", network));
+            import_data_file(&client, network, file_path).await?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Runs events from blockchain to register them in local event_store
 /// * db_client_pool - [`Arc<Pool>`]
-/// * configuration - [`&Args`]
+/// * network - [`&str`] - Network name, used as checkpoint stream name
 /// * file_path - [`&str`]
+/// * starting_block - [`Option<u64>`] - Overrides stored checkpoint when provided
+/// * batch_size - [`Option<u64>`] - Number of blocks per stream message
+/// * finality - [`DataFinality`] - Minimum finality of indexed blocks
+/// * force - [`bool`] - Ignores stored checkpoint and starts from `starting_block`
+/// * recorder - [`Option<BlockRecorder>`] - Records received blocks to disk when provided
 ///
+#[allow(clippy::too_many_arguments)]
 async fn handle_indexing(
     db_client_pool: Arc<Pool>,
+    network: &str,
     file_path: &str,
     apibara_uri: &str,
     apibara_token: &str,
-    starting_block: u64,
+    starting_block: Option<u64>,
+    batch_size: Option<u64>,
+    finality: DataFinality,
     force: bool,
+    recorder: Option<BlockRecorder>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut filters: [Box<dyn Filterable>; 4] = [
-        Box::new(ProjectFilters::new()),
-        Box::new(YieldFilters::new()),
-        Box::new(OffsetFilters::new()),
-        Box::new(MinterFilters::new()),
-    ];
-    let mut last_block_id = starting_block;
-    if !force {
-        last_block_id = starting_block;
-        last_block_id = get_last_dispatched_block(&db_client_pool, &last_block_id).await?;
+    let client = db_client_pool.get().await?;
+    let contracts = load_contract_registry(&client, network, file_path).await?;
+    let backfills = get_backfills(&client, network).await?;
+    let abis = AbiRegistry::new(get_contract_abis(&client).await?);
+    // no need to scan blocks before any registered contract was deployed
+    let first_deployment_block = contracts
+        .iter()
+        .map(|c| c.deployment_block)
+        .min()
+        .unwrap_or(0);
+    let starting_cursor = match (force, starting_block) {
+        (true, _) | (_, Some(_)) => Cursor {
+            order_key: starting_block.unwrap_or(first_deployment_block),
+            unique_key: vec![],
+        },
+        (false, None) => match get_checkpoint(&client, network).await? {
+            Some(cursor) => cursor,
+            None => Cursor {
+                order_key: get_last_dispatched_block(&db_client_pool, &first_deployment_block)
+                    .await?,
+                unique_key: vec![],
+            },
+        },
+    };
+    info!("Starting stream from block : {}", starting_cursor.order_key);
+
+    let watcher = RegistryWatcher::new(
+        network,
+        db_client_pool.clone(),
+        contracts,
+        &backfills,
+        batch_size,
+        finality,
+        CONTRACT_RELOAD_INTERVAL,
+    );
+    let mut filters = application_filters();
+    let stream_config = configure_stream_filters(
+        watcher.live_contracts(),
+        &mut filters,
+        &abis,
+        &starting_cursor.order_key,
+        batch_size,
+        finality,
+    )?;
+    let apibara_uri = apibara_uri.parse::<Uri>()?;
+    let source = ApibaraBlockSource::new(apibara_uri.clone(), apibara_token);
+    let sink = PostgresEventSink::new(
+        db_client_pool.clone(),
+        create_event_bus(db_client_pool.clone()),
+        network,
+    );
+    let mut indexer = StreamIndexer::new(source, sink, filters, stream_config)
+        .with_watcher(Box::new(watcher))
+        .with_abis(abis);
+    if let Some(recorder) = recorder {
+        indexer = indexer.with_recorder(recorder);
     }
-    info!("Starting stream from block : {}", last_block_id);
+    let backfill_runner = BackfillRunner::new(
+        network,
+        db_client_pool.clone(),
+        || ApibaraBlockSource::new(apibara_uri.clone(), apibara_token),
+        batch_size,
+        CONTRACT_RELOAD_INTERVAL,
+    );
+
+    // backfills run alongside live stream
+    tokio::try_join!(indexer.run(starting_cursor), backfill_runner.run())?;
+    Ok(())
+}
 
-    let stream_config = configure_stream_filters(&file_path, &mut filters, &last_block_id)?;
+/// Replays blocks recorded with `index --record` through the indexing pipeline
+/// * db_client_pool - [`Arc<Pool>`]
+/// * network - [`&str`] - Network name, replay checkpoint is stored under `{network}-replay`
+/// * file_path - [`&str`]
+/// * from - [`&str`] - Recording directory
+///
+async fn handle_replay(
+    db_client_pool: Arc<Pool>,
+    network: &str,
+    file_path: &str,
+    from: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut filters = application_filters();
+    let client = db_client_pool.get().await?;
+    let contracts = load_contract_registry(&client, network, file_path).await?;
+    let abis = AbiRegistry::new(get_contract_abis(&client).await?);
+    let stream_config = configure_stream_filters(
+        &contracts,
+        &mut filters,
+        &abis,
+        &0,
+        None,
+        DataFinality::DataStatusAccepted,
+    )?;
+    let source = RecordedBlockSource::from_dir(from)?;
+    info!("Replaying recorded blocks from {}", from);
 
-    let (mut stream, configuration_handle) = ClientBuilder::<Filter, Block>::default()
-        .with_bearer_token(apibara_token.to_owned())
-        .connect(Uri::from_static(Box::leak(
-            apibara_uri.to_owned().into_boxed_str(),
-        )))
+    let stream = format!("{network}-replay");
+    let sink = PostgresEventSink::new(
+        db_client_pool.clone(),
+        create_event_bus(db_client_pool.clone()),
+        &stream,
+    );
+    let last_cursor = StreamIndexer::new(source, sink, filters, stream_config)
+        .with_abis(abis)
+        .run(Cursor {
+            order_key: 0,
+            unique_key: vec![],
+        })
         .await?;
 
-    configuration_handle.send(stream_config.clone()).await?;
-    let event_bus = create_event_bus(db_client_pool.clone());
+    info!("Replay done at block {}", last_cursor.order_key);
+    Ok(())
+}
 
-    loop {
-        match stream.try_next().await {
-            Ok(Some(response)) => match response {
-                apibara_sdk::DataMessage::Data {
-                    cursor,
-                    end_cursor,
-                    finality: _,
-                    batch,
-                } => {
-                    info!(
-                        "Handling data within {} and {}",
-                        cursor.expect("should have starting cursor").order_key,
-                        end_cursor.order_key
-                    );
+/// Consumes event store into projections, each one from its own checkpoint.
+/// Unknown events supported by now are resolved first.
+/// * db_client_pool - [`Arc<Pool>`]
+/// * network - [`&str`] - Network of registered contracts
+/// * projections - [`Vec<Projection>`] - Projections to run
+/// * retry_policy - [`RetryPolicy`] - Applied to consumers failing with transient errors
+///
+async fn handle_event_store(
+    db_client_pool: Arc<Pool>,
+    network: &str,
+    projections: Vec<Projection>,
+    retry_policy: RetryPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    {
+        let client = db_client_pool.get().await?;
+        let contracts = get_registered_contracts(&client, network, true).await?;
+        let abis = AbiRegistry::new(get_contract_abis(&client).await?);
+        let mut filters = application_filters();
+        hydrate_filters(&contracts, &mut filters, &abis);
+        resolve_unknown_events(&client, &mut filters).await?;
+    }
 
-                    for block in batch {
-                        let metadata =
-                            BlockMetadata::from(block.header.expect("should have blockheader"));
-                        last_block_id = metadata.get_block();
-                        debug!("Block id: {}", last_block_id);
-                        let mut last_event_idx = 0;
-                        let mut last_processed_felt = String::new();
-                        for event in block.events {
-                            let mut event = DomainEvent::from_starknet_event(
-                                event,
-                                &mut filters,
-                                &mut last_event_idx,
-                                &mut last_processed_felt,
-                            );
-                            event = event.with_metadata(&metadata.clone());
-                            event_bus.register(&event, &metadata).await?;
-                        }
-                    }
-                }
-                apibara_sdk::DataMessage::Invalidate { cursor } => match cursor {
-                    Some(c) => {
-                        error!("Received an invalidate request data at {}", &c.order_key)
-                    }
-                    None => error!("Invalidate request without cursor provided"),
-                },
-            },
-            Ok(None) => continue,
-            Err(e) => {
-                error!("Error while streaming: {}", e);
-                // info!("Restarting stream");
-                // let restarting_cfg = stream_config;
-                // let cfg = restarting_cfg.with_starting_block(last_block_id);
-                //
-                // configuration_handle.send(cfg.clone()).await?;
+    info!("Starting read of domain events...");
+    let event_bus = create_event_bus(db_client_pool.clone()).with_retry_policy(retry_policy);
+    run_projections(
+        &event_bus,
+        db_client_pool,
+        &projections,
+        PROJECTION_POLL_INTERVAL,
+    )
+    .await?;
 
-                panic!("Restarting stream");
+    Ok(())
+}
+
+/// Projections management
+/// * db_client_pool - [`Arc<Pool>`]
+/// * command - [`ProjectionCommands`]
+///
+async fn handle_projection(
+    db_client_pool: Arc<Pool>,
+    command: ProjectionCommands,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = db_client_pool.get().await?;
+    match command {
+        ProjectionCommands::Projections => {
+            for state in get_projection_states(&client).await? {
+                println!(
+                    "{}\t{}\tlast_event_id={}",
+                    state.projection,
+                    state.status,
+                    state
+                        .last_event_id
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                );
             }
         }
+        ProjectionCommands::Pause { projection } => {
+            let projection = Projection::try_from(projection.as_str())?;
+            set_projection_status(&client, projection, ProjectionStatus::Paused).await?;
+        }
+        ProjectionCommands::Resume { projection } => {
+            let projection = Projection::try_from(projection.as_str())?;
+            set_projection_status(&client, projection, ProjectionStatus::Running).await?;
+        }
+        ProjectionCommands::Rebuild { projection } => {
+            let projection = Projection::try_from(projection.as_str())?;
+            rebuild_projection(&mut client, projection).await?;
+        }
     }
+
+    Ok(())
 }
 
-async fn handle_event_store(db_client_pool: Arc<Pool>) -> Result<(), Box<dyn std::error::Error>> {
-    info!("Starting read of domain events...");
-    let event_bus = create_event_bus(db_client_pool.clone());
-    let client = db_client_pool.clone().get().await?;
-    let mut key = get_last_handled_event(&client).await;
-    loop {
-        let batch = batch_events(&client, 10, key).await?;
-        if 0 == batch.len() {
-            sleep(Duration::from_secs(1)).await;
-            continue;
+/// Parse failed event id given on command line
+/// * id - [`&str`]
+///
+fn parse_failed_event_id(id: &str) -> Result<Ulid, IndexerError> {
+    ulid::Ulid::from_string(id)
+        .map(Ulid::from)
+        .map_err(|_| IndexerError::InvalidFailedEventId(id.to_owned()))
+}
+
+/// Dead-letter queue management
+/// * db_client_pool - [`Arc<Pool>`]
+/// * command - [`FailedEventCommands`]
+///
+async fn handle_failed_events(
+    db_client_pool: Arc<Pool>,
+    command: FailedEventCommands,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = db_client_pool.get().await?;
+    match command {
+        FailedEventCommands::List { projection } => {
+            let projection = projection
+                .map(|p| Projection::try_from(p.as_str()))
+                .transpose()?;
+            for failed in get_failed_events(&client, projection).await? {
+                println!(
+                    "{}\t{}\t{}\tevent={}\tattempts={}\tlast_failed_at={}\t{}",
+                    failed.id,
+                    failed.projection,
+                    failed.consumer,
+                    failed.event_id,
+                    failed.attempts,
+                    failed.updated_at,
+                    failed.error,
+                );
+            }
+        }
+        FailedEventCommands::Retry { id, all } => {
+            let failed_events = match (id, all) {
+                (Some(id), _) => get_failed_event(&client, parse_failed_event_id(&id)?)
+                    .await?
+                    .into_iter()
+                    .collect(),
+                (None, true) => get_failed_events(&client, None).await?,
+                (None, false) => {
+                    warn!("Provide a failed event id or --all");
+                    return Ok(());
+                }
+            };
+            let event_bus = create_event_bus(db_client_pool.clone());
+            for failed in failed_events {
+                let event = match get_stored_event(&client, failed.event_store_id).await? {
+                    Some(event) => event,
+                    None => {
+                        warn!("Event {} is not in event store anymore", failed.event_id);
+                        continue;
+                    }
+                };
+                match event_bus
+                    .retry_failed_event(
+                        &failed,
+                        &DomainEvent::from(&event),
+                        &BlockMetadata::from(&event),
+                    )
+                    .await
+                {
+                    Ok(_) => info!("Failed event {} consumed", failed.id),
+                    Err(e) => error!("Failed event {} failed again: {}", failed.id, e),
+                }
+            }
         }
-        for event in batch.as_slice() {
-            let domain_event = DomainEvent::from(event);
-            let metadata = BlockMetadata::from(event);
-            match event_bus
-                .consume_event_store(&domain_event, &metadata)
-                .await
-            {
-                Ok(_) => debug!("Properly hydrated event : {}", event.event_id.to_string()),
-                Err(e) => error!(
-                    "Error while hydrating event: {}\n{}",
-                    event.event_id.to_string(),
-                    e
-                ),
+        FailedEventCommands::Discard { id } => {
+            let tx = client.transaction().await?;
+            let deleted = delete_failed_event(&tx, parse_failed_event_id(&id)?).await?;
+            tx.commit().await?;
+            match deleted {
+                0 => warn!("Failed event {} not found", id),
+                _ => info!("Discarded failed event {}", id),
             }
         }
-        key = Some(batch.last().unwrap().id.clone());
-        let _ = store_last_handled_event(&client, key).await;
     }
+
+    Ok(())
 }
 
 async fn handle_refresh_event_store(
     db_client_pool: Arc<Pool>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let client = db_client_pool.clone().get().await?;
-
-    Ok(clear_view_models(&client).await?)
-}
-
-fn create_event_bus(
-    db_client_pool: Arc<Pool>,
-) -> EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>> {
-    let mut event_bus =
-        EventBus::<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>>::new(db_client_pool.clone());
-
-    // Project
-    event_bus.add_consumer(Box::new(ProjectTransferEventConsumer::new()));
-    event_bus.add_consumer(Box::new(ProjectTransferValueEventConsumer::new()));
-    event_bus.add_consumer(Box::new(ProjectSlotChangedEventConsumer::new()));
-    event_bus.add_consumer(Box::new(ProjectProjectValueUpdateEventConsumer::new()));
-    // Yielder
-    event_bus.add_consumer(Box::new(YielderClaimEventConsumer::new()));
-    event_bus.add_consumer(Box::new(YielderDepositEventConsumer::new()));
-    event_bus.add_consumer(Box::new(YielderProvisionEventConsumer::new()));
-    event_bus.add_consumer(Box::new(YielderSnapshotEventConsumer::new()));
-    event_bus.add_consumer(Box::new(YielderWithdrawEventConsumer::new()));
-    // Offseter
-    event_bus.add_consumer(Box::new(OffseterUpgradedEventConsumer::new()));
-    event_bus.add_consumer(Box::new(OffseterDepositEventConsumer::new()));
-    event_bus.add_consumer(Box::new(OffseterClaimEventConsumer::new()));
-    event_bus.add_consumer(Box::new(OffseterWithdrawEventConsumer::new()));
-    //Minter
-    event_bus.add_consumer(Box::new(MinterMigrationEventConsumer::new()));
-    event_bus.add_consumer(Box::new(MinterAirdropEventConsumer::new()));
-    event_bus.add_consumer(Box::new(MinterBuyEventConsumer::new()));
+    let mut client = db_client_pool.clone().get().await?;
 
-    event_bus
+    Ok(clear_view_models(&mut client).await?)
 }
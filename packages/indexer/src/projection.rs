@@ -0,0 +1,181 @@
+use std::{sync::Arc, time::Duration};
+
+use carbonable_domain::{
+    domain::event_source::{
+        event_bus::{Consumer, EventBus},
+        projection::{Projection, ProjectionStatus},
+        BlockMetadata, DomainError, DomainEvent, Filterable,
+    },
+    infrastructure::{
+        postgres::{
+            event_store::{
+                batch_events, get_implementation_history, get_unknown_events, store_resolved_event,
+            },
+            projection::get_projection_state,
+        },
+        starknet::event_source::resolve_unknown_event,
+    },
+};
+use deadpool_postgres::{Object, Pool, Transaction};
+use futures::future::try_join_all;
+use tokio::time::sleep;
+use tracing::{debug, error, info};
+
+use crate::IndexerError;
+
+const PROJECTION_BATCH_SIZE: i8 = 10;
+
+/// Consumes event store into a single projection, from the projection own checkpoint.
+/// Events failing to be consumed are set aside into `failed_event`, a database failure halts
+/// the projection on that event while other projections keep running.
+pub struct ProjectionRunner<'a> {
+    event_bus: &'a EventBus<Pool, Box<dyn for<'b> Consumer<Transaction<'b>>>>,
+    db_client_pool: Arc<Pool>,
+    projection: Projection,
+    interval: Duration,
+}
+
+impl<'a> ProjectionRunner<'a> {
+    /// * `event_bus` - [`&EventBus`] - Holds projection consumers
+    /// * `db_client_pool` - [`Arc<Pool>`]
+    /// * `projection` - [`Projection`]
+    /// * `interval` - [`Duration`] - Delay before polling again when idle, paused or halted
+    ///
+    pub fn new(
+        event_bus: &'a EventBus<Pool, Box<dyn for<'b> Consumer<Transaction<'b>>>>,
+        db_client_pool: Arc<Pool>,
+        projection: Projection,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            event_bus,
+            db_client_pool,
+            projection,
+            interval,
+        }
+    }
+
+    /// Consume event store forever
+    pub async fn run(&self) -> Result<(), IndexerError> {
+        info!("Starting projection {}", self.projection);
+        loop {
+            if 0 == self.run_batch().await? {
+                sleep(self.interval).await;
+            }
+        }
+    }
+
+    /// Consume next events batch, returns number of consumed events
+    async fn run_batch(&self) -> Result<usize, IndexerError> {
+        // checkpoint and status are read back on each batch as they may be changed from cli
+        let (state, batch) = {
+            let client = self.db_client_pool.get().await?;
+            let state = get_projection_state(&client, self.projection).await?;
+            if ProjectionStatus::Paused == state.status {
+                return Ok(0);
+            }
+            let batch = batch_events(&client, PROJECTION_BATCH_SIZE, state.last_event_id).await?;
+            (state, batch)
+        };
+
+        let mut previous = state.last_event_id;
+        let mut consumed = 0;
+        for event in batch.iter() {
+            let domain_event = DomainEvent::from(event);
+            let metadata = BlockMetadata::from(event);
+            match self
+                .event_bus
+                .consume_projection(
+                    self.projection,
+                    &domain_event,
+                    &metadata,
+                    previous,
+                    event.id,
+                )
+                .await
+            {
+                Ok(_) => {
+                    debug!(
+                        "Projection {} consumed event : {}",
+                        self.projection, event.event_id
+                    );
+                    previous = Some(event.id);
+                    consumed += 1;
+                }
+                Err(DomainError::ProjectionCheckpointMoved(_)) => {
+                    info!(
+                        "Projection {} was reset, reading checkpoint again",
+                        self.projection
+                    );
+                    return Ok(consumed);
+                }
+                Err(e) => {
+                    error!(
+                        "Projection {} halted on event {}: {}",
+                        self.projection, event.event_id, e
+                    );
+                    return Ok(0);
+                }
+            }
+        }
+
+        Ok(consumed)
+    }
+}
+
+/// Run given projections side by side until one of them fails
+/// * `event_bus` - [`&EventBus`]
+/// * `db_client_pool` - [`Arc<Pool>`]
+/// * `projections` - [`&[Projection]`]
+/// * `interval` - [`Duration`]
+///
+pub async fn run_projections(
+    event_bus: &EventBus<Pool, Box<dyn for<'b> Consumer<Transaction<'b>>>>,
+    db_client_pool: Arc<Pool>,
+    projections: &[Projection],
+    interval: Duration,
+) -> Result<(), IndexerError> {
+    let runners = projections
+        .iter()
+        .map(|p| ProjectionRunner::new(event_bus, db_client_pool.clone(), *p, interval))
+        .collect::<Vec<ProjectionRunner>>();
+    try_join_all(runners.iter().map(|r| r.run())).await?;
+
+    Ok(())
+}
+
+/// Store type of events stored as unknown which are supported by now, so that projections
+/// consume them when replaying event store. Returns number of resolved events
+/// * client - [`&deadpool_postgres::Object`]
+/// * filters - [`&mut [Box<dyn Filterable>]`] - Hydrated application filters
+///
+pub async fn resolve_unknown_events(
+    client: &Object,
+    filters: &mut [Box<dyn Filterable>],
+) -> Result<usize, IndexerError> {
+    let events = get_unknown_events(client).await?;
+    let contracts: Vec<String> = events
+        .iter()
+        .filter_map(|e| e.metadata.get("from_address").cloned())
+        .collect();
+    let history = get_implementation_history(client, &contracts).await?;
+
+    let mut resolved = 0;
+    for event in events.iter() {
+        let block = BlockMetadata::from(event).get_block();
+        if let Some(domain_event) =
+            resolve_unknown_event(filters, &DomainEvent::from(event), block, &history)
+        {
+            store_resolved_event(client, event.id, &domain_event).await?;
+            resolved += 1;
+        }
+    }
+    if 0 < resolved {
+        info!(
+            "{} unknown events are now supported, rebuild projections which skipped them to consume them",
+            resolved
+        );
+    }
+
+    Ok(resolved)
+}
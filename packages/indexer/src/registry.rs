@@ -0,0 +1,57 @@
+use carbonable_domain::{
+    domain::registry::RegisteredContract,
+    infrastructure::{
+        postgres::registry::{get_registered_contracts, register_contract},
+        seed::read_data_content,
+    },
+};
+use deadpool_postgres::Object;
+use tracing::info;
+
+use crate::IndexerError;
+
+/// Import legacy data file entries into contract registry.
+/// Returns imported entries
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+/// * file_path - Legacy `data/{network}.data.json` file
+///
+pub async fn import_data_file<P: AsRef<std::path::Path>>(
+    client: &Object,
+    network: &str,
+    file_path: P,
+) -> Result<Vec<RegisteredContract>, IndexerError> {
+    let content = read_data_content(file_path)?;
+    let contracts = RegisteredContract::from_data_content(network, content)?;
+    for contract in contracts.iter() {
+        register_contract(client, contract).await?;
+    }
+    info!(
+        "Imported {} contracts into {} registry",
+        contracts.len(),
+        network
+    );
+
+    Ok(contracts)
+}
+
+/// Active contracts of network registry.
+/// Registry is initialized from legacy data file when empty
+/// * client - [`&deadpool_postgres::Object`]
+/// * network - [`&str`]
+/// * file_path - Legacy `data/{network}.data.json` file
+///
+pub async fn load_contract_registry<P: AsRef<std::path::Path>>(
+    client: &Object,
+    network: &str,
+    file_path: P,
+) -> Result<Vec<RegisteredContract>, IndexerError> {
+    if get_registered_contracts(client, network, false)
+        .await?
+        .is_empty()
+    {
+        import_data_file(client, network, file_path).await?;
+    }
+
+    Ok(get_registered_contracts(client, network, true).await?)
+}
@@ -0,0 +1,229 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use apibara_core::{
+    node::v1alpha2::{Cursor, DataFinality},
+    starknet::v1alpha2::Filter,
+};
+use apibara_sdk::Configuration;
+use carbonable_domain::{
+    domain::{event_source::Filterable, registry::RegisteredContract},
+    infrastructure::{
+        postgres::{
+            backfill::{create_backfill, delete_backfill, get_backfills, Backfill},
+            implementation::get_contract_abis,
+            registry::get_registered_contracts,
+        },
+        seed::DataSeeder,
+        starknet::abi::AbiRegistry,
+    },
+};
+use deadpool_postgres::Pool;
+use tracing::info;
+
+use crate::{filters::configure_stream_filters, seed::create_seeders, IndexerError};
+
+/// Stream configuration to apply after watched contracts changed
+pub struct StreamUpdate {
+    pub configuration: Configuration<Filter>,
+    pub starting_cursor: Cursor,
+    pub abis: AbiRegistry,
+}
+
+/// Implement this trait to add contracts to a running stream.
+#[async_trait::async_trait(?Send)]
+pub trait ContractWatcher {
+    /// Check watched contracts. When some were added or removed, hydrates `filters`
+    /// and returns the stream configuration to apply.
+    /// * `filters` - [`&mut [Box<dyn Filterable>]`] - Filters in use by the stream
+    /// * `cursor` - [`&Cursor`] - Last stored cursor of the stream
+    ///
+    async fn poll(
+        &mut self,
+        filters: &mut [Box<dyn Filterable>],
+        cursor: &Cursor,
+    ) -> Result<Option<StreamUpdate>, IndexerError>;
+}
+
+/// [`ContractWatcher`] polling the contract registry of a network.
+/// Newly registered contracts are seeded, then either added to the live stream right away
+/// or, when deployed before current block, handed over to a backfill.
+/// Backfilled contracts join the live stream once their backfill caught up.
+/// Deactivated contracts are removed from the live stream.
+pub struct RegistryWatcher {
+    network: String,
+    db_client_pool: Arc<Pool>,
+    batch_size: Option<u64>,
+    finality: DataFinality,
+    interval: Duration,
+    last_check: Instant,
+    contracts: Vec<RegisteredContract>,
+    backfilled: Vec<String>,
+}
+
+impl RegistryWatcher {
+    /// * `network` - [`&str`]
+    /// * `db_client_pool` - [`Arc<Pool>`]
+    /// * `contracts` - [`Vec<RegisteredContract>`] - Active registry contracts
+    /// * `backfills` - [`&[Backfill]`] - Backfills not merged into live stream yet
+    /// * `batch_size` - [`Option<u64>`] - Number of blocks per stream message
+    /// * `finality` - [`DataFinality`] - Minimum finality of indexed blocks
+    /// * `interval` - [`Duration`] - Minimum delay between two registry checks
+    ///
+    pub fn new(
+        network: &str,
+        db_client_pool: Arc<Pool>,
+        contracts: Vec<RegisteredContract>,
+        backfills: &[Backfill],
+        batch_size: Option<u64>,
+        finality: DataFinality,
+        interval: Duration,
+    ) -> Self {
+        let backfilled = backfills
+            .iter()
+            .flat_map(|b| b.contracts.clone())
+            .collect::<Vec<String>>();
+
+        Self {
+            network: network.to_owned(),
+            db_client_pool,
+            batch_size,
+            finality,
+            interval,
+            last_check: Instant::now(),
+            contracts: contracts
+                .into_iter()
+                .filter(|c| !backfilled.contains(&c.address))
+                .collect(),
+            backfilled,
+        }
+    }
+
+    /// Contracts indexed by the live stream
+    pub fn live_contracts(&self) -> &[RegisteredContract] {
+        &self.contracts
+    }
+
+    fn is_known(&self, address: &str) -> bool {
+        self.backfilled.iter().any(|a| a == address)
+            || self.contracts.iter().any(|c| c.address == address)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ContractWatcher for RegistryWatcher {
+    async fn poll(
+        &mut self,
+        filters: &mut [Box<dyn Filterable>],
+        cursor: &Cursor,
+    ) -> Result<Option<StreamUpdate>, IndexerError> {
+        if self.last_check.elapsed() < self.interval {
+            return Ok(None);
+        }
+        self.last_check = Instant::now();
+
+        let mut client = self.db_client_pool.get().await?;
+        let registry = get_registered_contracts(&client, &self.network, true).await?;
+        let mut changed = false;
+        let mut starting_cursor = cursor.clone();
+
+        let removed = self
+            .contracts
+            .iter()
+            .filter(|c| !registry.iter().any(|r| r.address == c.address))
+            .map(|c| c.address.to_owned())
+            .collect::<Vec<String>>();
+        if !removed.is_empty() {
+            info!(
+                "{} contracts deactivated in {} registry",
+                removed.len(),
+                self.network
+            );
+            self.contracts.retain(|c| !removed.contains(&c.address));
+            changed = true;
+        }
+
+        let added = registry
+            .iter()
+            .filter(|c| !self.is_known(&c.address))
+            .cloned()
+            .collect::<Vec<RegisteredContract>>();
+        if !added.is_empty() {
+            info!(
+                "{} contracts added to {} registry",
+                added.len(),
+                self.network
+            );
+            DataSeeder::feed_from_registry(&added, create_seeders(self.db_client_pool.clone()))
+                .seed()
+                .await?;
+
+            let (historical, live): (Vec<RegisteredContract>, Vec<RegisteredContract>) = added
+                .into_iter()
+                .partition(|c| c.deployment_block < cursor.order_key);
+            if let Some(start_block) = historical.iter().map(|c| c.deployment_block).min() {
+                let addresses = historical
+                    .into_iter()
+                    .map(|c| c.address)
+                    .collect::<Vec<String>>();
+                create_backfill(&client, &self.network, &addresses, start_block).await?;
+                self.backfilled.extend(addresses);
+            }
+            if !live.is_empty() {
+                self.contracts.extend(live);
+                changed = true;
+            }
+        }
+
+        for backfill in get_backfills(&client, &self.network).await? {
+            let end_block = match backfill.end_block {
+                Some(end_block) => end_block,
+                None => continue,
+            };
+            info!(
+                "Merging backfill {} into {} stream",
+                backfill.id, self.network
+            );
+            self.backfilled.retain(|a| !backfill.contracts.contains(a));
+            self.contracts.extend(
+                registry
+                    .iter()
+                    .filter(|c| backfill.contracts.contains(&c.address))
+                    .cloned(),
+            );
+            // restart from backfill end so merged contracts have no gap
+            if end_block < starting_cursor.order_key {
+                starting_cursor = Cursor {
+                    order_key: end_block,
+                    unique_key: vec![],
+                };
+            }
+            delete_backfill(&mut client, &backfill).await?;
+            changed = true;
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        // added contracts were seeded along with their implementation ABI
+        let abis = AbiRegistry::new(get_contract_abis(&client).await?);
+        // starting block is overridden with stream cursor
+        let configuration = configure_stream_filters(
+            &self.contracts,
+            filters,
+            &abis,
+            &0,
+            self.batch_size,
+            self.finality,
+        )?;
+
+        Ok(Some(StreamUpdate {
+            configuration,
+            starting_cursor,
+            abis,
+        }))
+    }
+}
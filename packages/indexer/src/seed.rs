@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use carbonable_domain::{
+    domain::{Erc3525, Erc721},
+    infrastructure::{
+        postgres::PostgresModels,
+        seed::{
+            badge::BadgeSeeder, migrator::MigratorSeeder, minter::MinterSeeder,
+            offseter::OffseterSeeder, project::ProjectSeeder, yielder::YielderSeeder, Seeder,
+        },
+    },
+};
+use deadpool_postgres::Pool;
+
+/// Every seeder handling a data file entry
+/// * db_client_pool - [`Arc<Pool>`]
+///
+pub fn create_seeders(db_client_pool: Arc<Pool>) -> Vec<Arc<dyn Seeder + Send + Sync>> {
+    let db_models = Arc::new(PostgresModels::<Erc721>::new(db_client_pool.clone()));
+    let db_models_3525 = Arc::new(PostgresModels::<Erc3525>::new(db_client_pool));
+
+    vec![
+        Arc::new(ProjectSeeder::<Erc721>::new(db_models.clone())),
+        Arc::new(ProjectSeeder::<Erc3525>::new(db_models_3525.clone())),
+        Arc::new(BadgeSeeder::<Erc721>::new(db_models.clone())),
+        Arc::new(BadgeSeeder::<Erc3525>::new(db_models_3525.clone())),
+        Arc::new(MinterSeeder::<Erc721>::new(db_models.clone())),
+        Arc::new(MinterSeeder::<Erc3525>::new(db_models_3525.clone())),
+        Arc::new(OffseterSeeder::<Erc721>::new(db_models.clone())),
+        Arc::new(OffseterSeeder::<Erc3525>::new(db_models_3525.clone())),
+        Arc::new(YielderSeeder::<Erc721>::new(db_models)),
+        Arc::new(YielderSeeder::<Erc3525>::new(db_models_3525.clone())),
+        Arc::new(MigratorSeeder::new(db_models_3525)),
+    ]
+}
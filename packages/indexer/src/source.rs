@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+
+use apibara_core::{
+    node::v1alpha2::{Cursor, DataFinality},
+    starknet::v1alpha2::{Block, Filter},
+};
+use apibara_sdk::{ClientBuilder, Configuration, DataMessage, DataStream, DataStreamClient, Uri};
+use futures::TryStreamExt;
+
+use crate::{record::read_recorded_blocks, IndexerError};
+
+/// Implement this trait to feed the indexer with [`DataMessage`].
+#[async_trait::async_trait(?Send)]
+pub trait BlockSource {
+    /// (Re)connect source, streaming data matching `configuration` from `starting_cursor`
+    async fn connect(
+        &mut self,
+        configuration: Configuration<Filter>,
+        starting_cursor: Cursor,
+    ) -> Result<(), IndexerError>;
+
+    /// Update configuration of a connected source without interrupting it
+    async fn reconfigure(
+        &mut self,
+        configuration: Configuration<Filter>,
+    ) -> Result<(), IndexerError>;
+
+    /// Next message of the stream. `Ok(None)` means the stream ended.
+    async fn next_message(&mut self) -> Result<Option<DataMessage<Block>>, IndexerError>;
+
+    /// Live sources are reconnected once they end, finite ones are not.
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
+/// [`BlockSource`] streaming blocks from an apibara DNA server
+pub struct ApibaraBlockSource {
+    uri: Uri,
+    token: String,
+    stream: Option<DataStream<Filter, Block>>,
+    configuration_handle: Option<DataStreamClient<Filter>>,
+}
+
+impl ApibaraBlockSource {
+    pub fn new(uri: Uri, token: &str) -> Self {
+        Self {
+            uri,
+            token: token.to_owned(),
+            stream: None,
+            configuration_handle: None,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BlockSource for ApibaraBlockSource {
+    async fn connect(
+        &mut self,
+        configuration: Configuration<Filter>,
+        starting_cursor: Cursor,
+    ) -> Result<(), IndexerError> {
+        self.stream = None;
+        self.configuration_handle = None;
+        let (stream, configuration_handle) = ClientBuilder::<Filter, Block>::default()
+            .with_bearer_token(self.token.clone())
+            .connect(self.uri.clone())
+            .await
+            .map_err(|e| IndexerError::StreamError(e.to_string()))?;
+
+        configuration_handle
+            .send(configuration.with_starting_cursor(starting_cursor))
+            .await
+            .map_err(|e| IndexerError::StreamError(e.to_string()))?;
+
+        self.stream = Some(stream);
+        self.configuration_handle = Some(configuration_handle);
+        Ok(())
+    }
+
+    async fn reconfigure(
+        &mut self,
+        configuration: Configuration<Filter>,
+    ) -> Result<(), IndexerError> {
+        match &self.configuration_handle {
+            Some(handle) => handle
+                .send(configuration)
+                .await
+                .map_err(|e| IndexerError::StreamError(e.to_string())),
+            None => Err(IndexerError::StreamError(
+                "apibara stream is not connected".to_owned(),
+            )),
+        }
+    }
+
+    async fn next_message(&mut self) -> Result<Option<DataMessage<Block>>, IndexerError> {
+        match &mut self.stream {
+            Some(stream) => stream
+                .try_next()
+                .await
+                .map_err(|e| IndexerError::StreamError(e.to_string())),
+            None => Err(IndexerError::StreamError(
+                "apibara stream is not connected".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Wraps a single block into a [`DataMessage::Data`]
+/// * `block` - [`Block`]
+/// * `finality` - [`DataFinality`]
+///
+pub fn block_to_message(block: Block, finality: DataFinality) -> DataMessage<Block> {
+    let header = block.header.clone().unwrap_or_default();
+    let end_cursor = Cursor {
+        order_key: header.block_number,
+        unique_key: header
+            .block_hash
+            .map(|h| h.to_bytes().to_vec())
+            .unwrap_or_default(),
+    };
+    DataMessage::Data {
+        cursor: Some(Cursor {
+            order_key: header.block_number.saturating_sub(1),
+            unique_key: vec![],
+        }),
+        end_cursor,
+        finality,
+        batch: vec![block],
+    }
+}
+
+/// [`BlockSource`] serving in memory messages, mostly built with `test_utils::BlockBuilder`.
+/// `Err` items are returned as stream errors, source stays live until every message was served
+/// so that reconnection can be exercised.
+#[derive(Default)]
+pub struct InMemoryBlockSource {
+    messages: VecDeque<Result<DataMessage<Block>, IndexerError>>,
+    connections: Vec<Cursor>,
+}
+
+impl InMemoryBlockSource {
+    pub fn new(messages: Vec<Result<DataMessage<Block>, IndexerError>>) -> Self {
+        Self {
+            messages: messages.into(),
+            connections: vec![],
+        }
+    }
+
+    /// Starting cursors source was connected with, in order
+    pub fn connections(&self) -> &[Cursor] {
+        &self.connections
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BlockSource for InMemoryBlockSource {
+    async fn connect(
+        &mut self,
+        _configuration: Configuration<Filter>,
+        starting_cursor: Cursor,
+    ) -> Result<(), IndexerError> {
+        self.connections.push(starting_cursor);
+        Ok(())
+    }
+
+    async fn reconfigure(
+        &mut self,
+        _configuration: Configuration<Filter>,
+    ) -> Result<(), IndexerError> {
+        Ok(())
+    }
+
+    async fn next_message(&mut self) -> Result<Option<DataMessage<Block>>, IndexerError> {
+        match self.messages.pop_front() {
+            Some(Ok(message)) => Ok(Some(message)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        !self.messages.is_empty()
+    }
+}
+
+/// [`BlockSource`] serving blocks recorded with [`crate::record::BlockRecorder`]
+pub struct RecordedBlockSource {
+    inner: InMemoryBlockSource,
+    blocks: Vec<Block>,
+}
+
+impl RecordedBlockSource {
+    /// Load every recorded block from `dir`
+    /// * `dir` - Recording directory
+    ///
+    pub fn from_dir<P: AsRef<std::path::Path>>(dir: P) -> Result<Self, IndexerError> {
+        Ok(Self {
+            inner: InMemoryBlockSource::default(),
+            blocks: read_recorded_blocks(dir)?,
+        })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BlockSource for RecordedBlockSource {
+    async fn connect(
+        &mut self,
+        configuration: Configuration<Filter>,
+        starting_cursor: Cursor,
+    ) -> Result<(), IndexerError> {
+        let messages = self
+            .blocks
+            .iter()
+            .filter(|b| {
+                b.header
+                    .as_ref()
+                    .map_or(true, |h| h.block_number >= starting_cursor.order_key)
+            })
+            .map(|b| {
+                Ok(block_to_message(
+                    b.clone(),
+                    DataFinality::DataStatusAccepted,
+                ))
+            })
+            .collect();
+        self.inner = InMemoryBlockSource::new(messages);
+        self.inner.connect(configuration, starting_cursor).await
+    }
+
+    async fn reconfigure(
+        &mut self,
+        configuration: Configuration<Filter>,
+    ) -> Result<(), IndexerError> {
+        self.inner.reconfigure(configuration).await
+    }
+
+    async fn next_message(&mut self) -> Result<Option<DataMessage<Block>>, IndexerError> {
+        self.inner.next_message().await
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,681 @@
+use std::{sync::Arc, time::Duration};
+
+use apibara_core::{
+    node::v1alpha2::Cursor,
+    starknet::v1alpha2::{Block, Filter},
+};
+use apibara_sdk::{Configuration, DataMessage};
+use carbonable_domain::{
+    domain::event_source::{
+        event_bus::{Consumer, EventBus},
+        minter::{
+            MinterAirdropEventConsumer, MinterBuyEventConsumer, MinterMigrationEventConsumer,
+            MinterSaleStatusEventConsumer,
+        },
+        offseter::{
+            OffseterClaimEventConsumer, OffseterDepositEventConsumer,
+            OffseterUpgradedEventConsumer, OffseterWithdrawEventConsumer,
+        },
+        project::{
+            ProjectAbsorptionUpdateEventConsumer, ProjectApprovalEventConsumer,
+            ProjectApprovalForAllEventConsumer, ProjectApprovalValueEventConsumer,
+            ProjectMetadataUpdateEventConsumer, ProjectProjectValueUpdateEventConsumer,
+            ProjectSlotChangedEventConsumer, ProjectTransferEventConsumer,
+            ProjectTransferValueEventConsumer,
+        },
+        projection::Projection,
+        yielder::{
+            YielderClaimEventConsumer, YielderDepositEventConsumer, YielderProvisionEventConsumer,
+            YielderSnapshotEventConsumer, YielderWithdrawEventConsumer,
+        },
+        BlockMetadata, DomainEvent, Filterable,
+    },
+    infrastructure::{
+        postgres::event_store::invalidate_events_after_cursor, starknet::abi::AbiRegistry,
+    },
+};
+use deadpool_postgres::{Pool, Transaction};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::{record::BlockRecorder, reload::ContractWatcher, source::BlockSource, IndexerError};
+
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Implement this trait to persist what the indexer extracts from a [`BlockSource`].
+#[async_trait::async_trait(?Send)]
+pub trait EventSink {
+    /// Store events of a whole stream batch along with the cursor it ends at
+    async fn store_batch(
+        &self,
+        events: &[(DomainEvent, BlockMetadata)],
+        cursor: &Cursor,
+    ) -> Result<(), IndexerError>;
+
+    /// Drop every stored event after `cursor` following a chain reorg
+    async fn invalidate(&self, cursor: &Cursor) -> Result<(), IndexerError>;
+}
+
+/// [`EventSink`] storing events in postgres event_store
+pub struct PostgresEventSink {
+    db_client_pool: Arc<Pool>,
+    event_bus: EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>>,
+    stream: String,
+}
+
+impl PostgresEventSink {
+    /// * `db_client_pool` - [`Arc<Pool>`]
+    /// * `event_bus` - [`EventBus`] - Used to register stream batches
+    /// * `stream` - [`&str`] - Checkpoint stream name
+    ///
+    pub fn new(
+        db_client_pool: Arc<Pool>,
+        event_bus: EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>>,
+        stream: &str,
+    ) -> Self {
+        Self {
+            db_client_pool,
+            event_bus,
+            stream: stream.to_owned(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl EventSink for PostgresEventSink {
+    async fn store_batch(
+        &self,
+        events: &[(DomainEvent, BlockMetadata)],
+        cursor: &Cursor,
+    ) -> Result<(), IndexerError> {
+        Ok(self
+            .event_bus
+            .register_batch(events, &self.stream, cursor)
+            .await?)
+    }
+
+    async fn invalidate(&self, cursor: &Cursor) -> Result<(), IndexerError> {
+        let mut client = self.db_client_pool.get().await?;
+        invalidate_events_after_cursor(&mut client, &self.stream, cursor).await?;
+        Ok(())
+    }
+}
+
+/// Event bus registering stream batches, with every projection consumer
+/// * db_client_pool - [`Arc<Pool>`]
+///
+pub fn create_event_bus(
+    db_client_pool: Arc<Pool>,
+) -> EventBus<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>> {
+    let mut event_bus =
+        EventBus::<Pool, Box<dyn for<'a> Consumer<Transaction<'a>>>>::new(db_client_pool.clone());
+
+    // Customer tokens
+    event_bus.add_consumer(
+        Projection::CustomerToken,
+        Box::new(ProjectTransferEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerToken,
+        Box::new(ProjectTransferValueEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerToken,
+        Box::new(ProjectSlotChangedEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerToken,
+        Box::new(MinterMigrationEventConsumer::new()),
+    );
+    // Customer approvals
+    event_bus.add_consumer(
+        Projection::CustomerApproval,
+        Box::new(ProjectApprovalEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerApproval,
+        Box::new(ProjectApprovalValueEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerApproval,
+        Box::new(ProjectApprovalForAllEventConsumer::new()),
+    );
+    // Customer farms
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(YielderClaimEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(YielderDepositEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(YielderWithdrawEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(OffseterUpgradedEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(OffseterDepositEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(OffseterClaimEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::CustomerFarm,
+        Box::new(OffseterWithdrawEventConsumer::new()),
+    );
+    // Yielder snapshots and provisions
+    event_bus.add_consumer(
+        Projection::YielderSnapshot,
+        Box::new(YielderSnapshotEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::YielderProvision,
+        Box::new(YielderProvisionEventConsumer::new()),
+    );
+    // Project values
+    event_bus.add_consumer(
+        Projection::ProjectValue,
+        Box::new(ProjectProjectValueUpdateEventConsumer::new()),
+    );
+    // Project uri and metadata
+    event_bus.add_consumer(
+        Projection::ProjectMetadata,
+        Box::new(ProjectMetadataUpdateEventConsumer::new()),
+    );
+    // Project absorption curves
+    event_bus.add_consumer(
+        Projection::ProjectAbsorption,
+        Box::new(ProjectAbsorptionUpdateEventConsumer::new()),
+    );
+    // Minter sales
+    event_bus.add_consumer(
+        Projection::MinterSale,
+        Box::new(MinterAirdropEventConsumer::new()),
+    );
+    event_bus.add_consumer(
+        Projection::MinterSale,
+        Box::new(MinterBuyEventConsumer::new()),
+    );
+    // Minter sale status
+    event_bus.add_consumer(
+        Projection::MinterStatus,
+        Box::new(MinterSaleStatusEventConsumer::new()),
+    );
+
+    event_bus
+}
+
+/// Streaming loop reading messages from a [`BlockSource`] into an [`EventSink`].
+/// Live sources are reconnected with exponential backoff from last stored cursor
+/// whenever they or the sink fail, or when they end.
+pub struct StreamIndexer<S: BlockSource, K: EventSink> {
+    source: S,
+    sink: K,
+    filters: Vec<Box<dyn Filterable>>,
+    configuration: Configuration<Filter>,
+    recorder: Option<BlockRecorder>,
+    watcher: Option<Box<dyn ContractWatcher>>,
+    abis: AbiRegistry,
+    reconnect_base_delay: Duration,
+    reconnect_max_delay: Duration,
+}
+
+impl<S: BlockSource, K: EventSink> StreamIndexer<S, K> {
+    /// * `source` - [`BlockSource`]
+    /// * `sink` - [`EventSink`]
+    /// * `filters` - [`Vec<Box<dyn Filterable>>`] - Hydrated application filters
+    /// * `configuration` - [`Configuration<Filter>`] - Stream configuration, without starting cursor
+    ///
+    pub fn new(
+        source: S,
+        sink: K,
+        filters: Vec<Box<dyn Filterable>>,
+        configuration: Configuration<Filter>,
+    ) -> Self {
+        Self {
+            source,
+            sink,
+            filters,
+            configuration,
+            recorder: None,
+            watcher: None,
+            abis: AbiRegistry::default(),
+            reconnect_base_delay: RECONNECT_BASE_DELAY,
+            reconnect_max_delay: RECONNECT_MAX_DELAY,
+        }
+    }
+
+    /// Record every received block to disk
+    pub fn with_recorder(mut self, recorder: BlockRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Add contracts to the running stream whenever `watcher` reports some
+    pub fn with_watcher(mut self, watcher: Box<dyn ContractWatcher>) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+
+    /// Store ABI decoded named fields along with positional event payload
+    pub fn with_abis(mut self, abis: AbiRegistry) -> Self {
+        self.abis = abis;
+        self
+    }
+
+    /// Override reconnection backoff delays
+    pub fn with_reconnect_delay(mut self, base: Duration, max: Duration) -> Self {
+        self.reconnect_base_delay = base;
+        self.reconnect_max_delay = max;
+        self
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn sink(&self) -> &K {
+        &self.sink
+    }
+
+    /// Run stream from `starting_cursor` until a finite source is exhausted.
+    /// Returns last stored cursor, or the sink error which stopped a finite source.
+    /// * `starting_cursor` - [`Cursor`]
+    ///
+    pub async fn run(&mut self, mut starting_cursor: Cursor) -> Result<Cursor, IndexerError> {
+        let mut reconnect_delay = self.reconnect_base_delay;
+
+        loop {
+            let mut sink_failure = None;
+            // Resume from last cursor which batch was fully registered.
+            if let Err(e) = self
+                .source
+                .connect(self.configuration.clone(), starting_cursor.clone())
+                .await
+            {
+                error!("Failed to connect block source: {}", e);
+                reconnect_delay = self.wait_before_reconnect(reconnect_delay).await;
+                continue;
+            }
+
+            loop {
+                match self.source.next_message().await {
+                    Ok(Some(message)) => {
+                        match self.handle_message(message).await {
+                            Ok(Some(cursor)) => starting_cursor = cursor,
+                            Ok(None) => {}
+                            Err(e) => {
+                                // e.g. database outage, message is streamed again on reconnect
+                                error!("Failed to handle stream message: {}", e);
+                                sink_failure = Some(e);
+                                break;
+                            }
+                        }
+                        reconnect_delay = self.reconnect_base_delay;
+
+                        if let Err(e) = self.reload_contracts(&mut starting_cursor).await {
+                            error!("Failed to reconfigure stream: {}", e);
+                            break;
+                        }
+                    }
+                    Ok(None) => {
+                        warn!("Block source stream closed");
+                        break;
+                    }
+                    Err(e) => {
+                        error!("Error while streaming: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            if !self.source.is_live() {
+                return match sink_failure {
+                    Some(e) => Err(e),
+                    None => Ok(starting_cursor),
+                };
+            }
+
+            info!(
+                "Restarting stream from block : {}",
+                starting_cursor.order_key
+            );
+            reconnect_delay = self.wait_before_reconnect(reconnect_delay).await;
+        }
+    }
+
+    /// Handle a single stream message, returns cursor to restart from if it moved
+    async fn handle_message(
+        &mut self,
+        message: DataMessage<Block>,
+    ) -> Result<Option<Cursor>, IndexerError> {
+        match message {
+            DataMessage::Data {
+                cursor,
+                end_cursor,
+                finality: _,
+                batch,
+            } => {
+                info!(
+                    "Handling data within {} and {}",
+                    cursor.map(|c| c.order_key).unwrap_or_default(),
+                    end_cursor.order_key
+                );
+
+                if let Some(recorder) = &self.recorder {
+                    recorder.record(&batch)?;
+                }
+
+                let mut events = Vec::new();
+                for block in batch {
+                    events.append(&mut block_to_events(block, &mut self.filters, &self.abis));
+                }
+
+                self.sink.store_batch(&events, &end_cursor).await?;
+                Ok(Some(end_cursor))
+            }
+            DataMessage::Invalidate { cursor } => match cursor {
+                Some(c) => {
+                    warn!("Received an invalidate request data at {}", &c.order_key);
+                    self.sink.invalidate(&c).await?;
+                    Ok(Some(c))
+                }
+                None => {
+                    error!("Invalidate request without cursor provided");
+                    Ok(None)
+                }
+            },
+        }
+    }
+
+    /// Rebuild stream filter when watched contracts changed.
+    /// Stream is reconfigured from `cursor`, or from the cursor required by the watcher
+    /// so that contracts merged from a backfill have no gap.
+    async fn reload_contracts(&mut self, cursor: &mut Cursor) -> Result<(), IndexerError> {
+        let watcher = match self.watcher.as_mut() {
+            Some(watcher) => watcher,
+            None => return Ok(()),
+        };
+        let update = match watcher.poll(&mut self.filters, cursor).await {
+            Ok(Some(update)) => update,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                error!("Failed to reload watched contracts: {}", e);
+                return Ok(());
+            }
+        };
+
+        *cursor = update.starting_cursor;
+        info!("Reconfiguring stream from block : {}", cursor.order_key);
+        self.configuration = update.configuration.clone();
+        self.abis = update.abis;
+        self.source
+            .reconfigure(update.configuration.with_starting_cursor(cursor.clone()))
+            .await
+    }
+
+    /// Waits before reconnecting and returns next delay using exponential backoff
+    async fn wait_before_reconnect(&self, delay: Duration) -> Duration {
+        info!("Reconnecting block source in {}s", delay.as_secs());
+        sleep(delay).await;
+
+        std::cmp::min(delay * 2, self.reconnect_max_delay)
+    }
+}
+
+/// Extract domain events from an apibara block
+/// * block - [`Block`]
+/// * filters - [`&mut [Box<dyn Filterable>]`]
+/// * abis - [`&AbiRegistry`] - Decodes payload of contracts with a known ABI
+///
+pub fn block_to_events(
+    block: Block,
+    filters: &mut [Box<dyn Filterable>],
+    abis: &AbiRegistry,
+) -> Vec<(DomainEvent, BlockMetadata)> {
+    let metadata = BlockMetadata::from(block.header.expect("should have blockheader"));
+    debug!("Block id: {}", metadata.get_block());
+    let mut last_event_idx = 0;
+    let mut last_processed_felt = String::new();
+    let mut events = Vec::new();
+    for event in block.events {
+        let decoded = event
+            .event
+            .as_ref()
+            .and_then(|e| abis.decode(e.from_address.as_ref()?, &e.keys, &e.data));
+        let mut event = DomainEvent::from_starknet_event(
+            event,
+            filters,
+            &mut last_event_idx,
+            &mut last_processed_felt,
+        );
+        event = event.with_metadata(&metadata.clone());
+        if let Some(fields) = decoded {
+            event = event.with_decoded_payload(fields);
+        }
+        events.push((event, metadata.clone()));
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, time::Duration};
+
+    use apibara_core::{
+        node::v1alpha2::{Cursor, DataFinality},
+        starknet::v1alpha2::{FieldElement, Filter},
+    };
+    use apibara_sdk::{Configuration, DataMessage};
+    use carbonable_domain::domain::{
+        event_source::{project::ProjectFilters, BlockMetadata, DomainEvent, Filterable},
+        registry::{ContractType, RegisteredContract},
+    };
+    use starknet::macros::selector;
+
+    use super::{EventSink, StreamIndexer};
+    use crate::{
+        source::{block_to_message, InMemoryBlockSource},
+        test_utils::BlockBuilder,
+        IndexerError,
+    };
+
+    const PROJECT: &str = "0x0000000000000000000000000000000000000000000000000000000000000456";
+
+    #[derive(Default)]
+    struct InMemoryEventSink {
+        events: RefCell<Vec<(u64, String)>>,
+        checkpoint: RefCell<Option<u64>>,
+        failures: RefCell<usize>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl EventSink for InMemoryEventSink {
+        async fn store_batch(
+            &self,
+            events: &[(DomainEvent, BlockMetadata)],
+            cursor: &Cursor,
+        ) -> Result<(), IndexerError> {
+            if *self.failures.borrow() > 0 {
+                *self.failures.borrow_mut() -= 1;
+                return Err(IndexerError::StreamError("database is down".to_owned()));
+            }
+            self.events.borrow_mut().extend(
+                events
+                    .iter()
+                    .map(|(e, m)| (m.get_block(), e.id().to_owned())),
+            );
+            *self.checkpoint.borrow_mut() = Some(cursor.order_key);
+            Ok(())
+        }
+
+        async fn invalidate(&self, cursor: &Cursor) -> Result<(), IndexerError> {
+            self.events
+                .borrow_mut()
+                .retain(|(block, _)| *block <= cursor.order_key);
+            *self.checkpoint.borrow_mut() = Some(cursor.order_key);
+            Ok(())
+        }
+    }
+
+    fn transfer_at(
+        block_number: u64,
+        tx_hash: &str,
+    ) -> DataMessage<apibara_core::starknet::v1alpha2::Block> {
+        let block = BlockBuilder::init()
+            .with_block_number(block_number)
+            .add_event(
+                tx_hash,
+                PROJECT,
+                &selector!("Transfer").to_bytes_be(),
+                vec![
+                    FieldElement::from_u64(0),
+                    FieldElement::from_u64(1),
+                    FieldElement::from_u64(block_number),
+                    FieldElement::from_u64(0),
+                ],
+            )
+            .build();
+        block_to_message(block, DataFinality::DataStatusAccepted)
+    }
+
+    fn indexer(
+        messages: Vec<Result<DataMessage<apibara_core::starknet::v1alpha2::Block>, IndexerError>>,
+    ) -> StreamIndexer<InMemoryBlockSource, InMemoryEventSink> {
+        let mut project_filters = ProjectFilters::new();
+        project_filters.hydrate(&[RegisteredContract {
+            address: PROJECT.to_owned(),
+            contract_type: ContractType::Project,
+            network: "testnet".to_owned(),
+            project_address: None,
+            slot: None,
+            deployment_block: 0,
+            active: true,
+        }]);
+        let filters: Vec<Box<dyn Filterable>> = vec![Box::new(project_filters)];
+
+        StreamIndexer::new(
+            InMemoryBlockSource::new(messages),
+            InMemoryEventSink::default(),
+            filters,
+            Configuration::<Filter>::default(),
+        )
+        .with_reconnect_delay(Duration::ZERO, Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn test_data_messages_are_stored_with_checkpoint() {
+        let mut indexer = indexer(vec![
+            Ok(transfer_at(10, "0x01")),
+            Ok(transfer_at(11, "0x02")),
+        ]);
+
+        let last_cursor = indexer
+            .run(Cursor {
+                order_key: 10,
+                unique_key: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(11, last_cursor.order_key);
+        assert_eq!(2, indexer.sink().events.borrow().len());
+        assert_eq!(Some(11), *indexer.sink().checkpoint.borrow());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_drops_events_after_cursor() {
+        let mut indexer = indexer(vec![
+            Ok(transfer_at(10, "0x01")),
+            Ok(transfer_at(11, "0x02")),
+            Ok(DataMessage::Invalidate {
+                cursor: Some(Cursor {
+                    order_key: 10,
+                    unique_key: vec![],
+                }),
+            }),
+            Ok(transfer_at(11, "0x03")),
+        ]);
+
+        indexer
+            .run(Cursor {
+                order_key: 10,
+                unique_key: vec![],
+            })
+            .await
+            .unwrap();
+
+        let reorged_transfer = format!("{}_0", FieldElement::from_hex("0x03").unwrap());
+        let events = indexer.sink().events.borrow().clone();
+        assert_eq!(2, events.len());
+        assert_eq!((11, reorged_transfer), events[1]);
+        assert_eq!(Some(11), *indexer.sink().checkpoint.borrow());
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_restarts_from_last_stored_cursor() {
+        let mut indexer = indexer(vec![
+            Ok(transfer_at(10, "0x01")),
+            Err(IndexerError::StreamError("connection reset".to_owned())),
+            Ok(transfer_at(11, "0x02")),
+        ]);
+
+        let last_cursor = indexer
+            .run(Cursor {
+                order_key: 5,
+                unique_key: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(11, last_cursor.order_key);
+        assert_eq!(
+            vec![5, 10],
+            indexer
+                .source()
+                .connections()
+                .iter()
+                .map(|c| c.order_key)
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(2, indexer.sink().events.borrow().len());
+    }
+
+    #[tokio::test]
+    async fn test_sink_error_restarts_from_last_stored_cursor() {
+        let mut indexer = indexer(vec![
+            Ok(transfer_at(10, "0x01")),
+            Ok(transfer_at(11, "0x02")),
+            Ok(transfer_at(12, "0x03")),
+        ]);
+        *indexer.sink().failures.borrow_mut() = 1;
+
+        let last_cursor = indexer
+            .run(Cursor {
+                order_key: 5,
+                unique_key: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(12, last_cursor.order_key);
+        assert_eq!(
+            vec![5, 5],
+            indexer
+                .source()
+                .connections()
+                .iter()
+                .map(|c| c.order_key)
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(2, indexer.sink().events.borrow().len());
+    }
+}
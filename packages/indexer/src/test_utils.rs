@@ -1,6 +1,6 @@
 use apibara_core::starknet::v1alpha2::{
     transaction::Transaction::InvokeV1, Block, Event, EventWithTransaction, FieldElement,
-    InvokeTransactionV1, Transaction,
+    InvokeTransactionV1, Transaction, TransactionReceipt,
 };
 use pbjson_types::Timestamp;
 
@@ -37,6 +37,13 @@ impl BlockBuilder {
         }
     }
 
+    pub fn with_block_number(&mut self, block_number: u64) -> &mut Self {
+        if let Some(header) = self.block.header.as_mut() {
+            header.block_number = block_number;
+        }
+        self
+    }
+
     pub fn with_events(&mut self, events: Vec<EventWithTransaction>) -> &mut Self {
         self.block.events = events;
         self
@@ -73,6 +80,43 @@ impl BlockBuilder {
         self
     }
 
+    /// Add an event emitted by `contract`, with its receipt, so it can be extracted with application filters
+    pub fn add_event(
+        &mut self,
+        tx_hash: &str,
+        contract: &str,
+        event_key: &[u8; 32],
+        data: Vec<FieldElement>,
+    ) -> &mut Self {
+        let event = Event {
+            from_address: Some(FieldElement::from_hex(contract).unwrap()),
+            keys: vec![FieldElement::from_bytes(event_key)],
+            data,
+        };
+        self.block.events.push(EventWithTransaction {
+            transaction: Some(Transaction {
+                meta: Some(apibara_core::starknet::v1alpha2::TransactionMeta {
+                    hash: Some(FieldElement::from_hex(tx_hash).unwrap()),
+                    max_fee: None,
+                    signature: vec![],
+                    nonce: None,
+                    version: 100u64,
+                }),
+                transaction: Some(InvokeV1(InvokeTransactionV1 {
+                    sender_address: Some(FieldElement::from_hex(contract).unwrap()),
+                    calldata: vec![],
+                })),
+            }),
+            receipt: Some(TransactionReceipt {
+                transaction_hash: Some(FieldElement::from_hex(tx_hash).unwrap()),
+                events: vec![event.clone()],
+                ..Default::default()
+            }),
+            event: Some(event),
+        });
+        self
+    }
+
     pub fn build(&mut self) -> Block {
         self.block.clone()
     }
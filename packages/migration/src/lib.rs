@@ -9,6 +9,21 @@ mod m20230601_135331_add_project_value;
 mod m20230927_115912_add_customer_actions;
 mod m20231019_074938_add_project_asset_computations;
 mod m20240223_093348_add_project_metadata;
+mod m20240305_090000_add_indexer_checkpoint;
+mod m20240306_090000_add_contract_registry;
+mod m20240307_090000_add_indexer_backfill;
+mod m20240308_090000_add_projection_checkpoint;
+mod m20240309_090000_add_failed_event;
+mod m20240310_090000_add_event_store_keys;
+mod m20240311_090000_add_event_store_schema_version;
+mod m20240312_090000_add_minter_sale_projection;
+mod m20240313_090000_add_minter_status_projection;
+mod m20240314_090000_add_customer_approval;
+mod m20240315_090000_add_project_metadata_projection;
+mod m20240316_090000_add_absorption_curve;
+mod m20240317_090000_add_skipped_event;
+mod m20240318_090000_add_event_store_stream;
+mod m20240319_090000_add_sale_event_id;
 
 pub struct Migrator;
 
@@ -25,6 +40,21 @@ impl MigratorTrait for Migrator {
             Box::new(m20230927_115912_add_customer_actions::Migration),
             Box::new(m20231019_074938_add_project_asset_computations::Migration),
             Box::new(m20240223_093348_add_project_metadata::Migration),
+            Box::new(m20240305_090000_add_indexer_checkpoint::Migration),
+            Box::new(m20240306_090000_add_contract_registry::Migration),
+            Box::new(m20240307_090000_add_indexer_backfill::Migration),
+            Box::new(m20240308_090000_add_projection_checkpoint::Migration),
+            Box::new(m20240309_090000_add_failed_event::Migration),
+            Box::new(m20240310_090000_add_event_store_keys::Migration),
+            Box::new(m20240311_090000_add_event_store_schema_version::Migration),
+            Box::new(m20240312_090000_add_minter_sale_projection::Migration),
+            Box::new(m20240313_090000_add_minter_status_projection::Migration),
+            Box::new(m20240314_090000_add_customer_approval::Migration),
+            Box::new(m20240315_090000_add_project_metadata_projection::Migration),
+            Box::new(m20240316_090000_add_absorption_curve::Migration),
+            Box::new(m20240317_090000_add_skipped_event::Migration),
+            Box::new(m20240318_090000_add_event_store_stream::Migration),
+            Box::new(m20240319_090000_add_sale_event_id::Migration),
         ]
     }
 }
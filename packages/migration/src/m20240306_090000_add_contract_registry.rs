@@ -0,0 +1,63 @@
+use carbonable_domain::infrastructure::postgres::entity::ContractRegistryIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContractRegistryIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::Network)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::Address)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::ContractType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ContractRegistryIden::ProjectAddress).string())
+                    .col(ColumnDef::new(ContractRegistryIden::Slot).binary())
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::DeploymentBlock)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(ContractRegistryIden::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ContractRegistryIden::Network)
+                            .col(ContractRegistryIden::Address),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContractRegistryIden::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,52 @@
+use carbonable_domain::infrastructure::postgres::entity::IndexerBackfillIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IndexerBackfillIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IndexerBackfillIden::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(IndexerBackfillIden::Network)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IndexerBackfillIden::Contracts)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IndexerBackfillIden::StartBlock)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(IndexerBackfillIden::EndBlock).binary())
+                    .col(
+                        ColumnDef::new(IndexerBackfillIden::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IndexerBackfillIden::Table).to_owned())
+            .await
+    }
+}
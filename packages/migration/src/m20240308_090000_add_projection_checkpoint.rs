@@ -0,0 +1,63 @@
+use carbonable_domain::infrastructure::postgres::entity::ProjectionCheckpointIden;
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectionCheckpointIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectionCheckpointIden::Name)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectionCheckpointIden::LastEventId)
+                            .string()
+                            .string_len(26)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectionCheckpointIden::Status)
+                            .string()
+                            .not_null()
+                            .default("running"),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectionCheckpointIden::UpdatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // every projection resumes from the former global cursor
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            SELECT p.name, NULLIF(lse.id, ''), 'running', NOW()
+            FROM (VALUES ('customer_token'), ('customer_farm'), ('yielder_snapshot'), ('yielder_provision'), ('project_value')) AS p(name)
+            LEFT JOIN last_stored_event lse ON true"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ProjectionCheckpointIden::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
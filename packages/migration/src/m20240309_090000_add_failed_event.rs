@@ -0,0 +1,77 @@
+use carbonable_domain::infrastructure::postgres::entity::FailedEventIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FailedEventIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FailedEventIden::Id)
+                            .string()
+                            .string_len(26)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(FailedEventIden::EventStoreId)
+                            .string()
+                            .string_len(26)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(FailedEventIden::EventId).string().not_null())
+                    .col(
+                        ColumnDef::new(FailedEventIden::Projection)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FailedEventIden::Consumer)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(FailedEventIden::Error).text().not_null())
+                    .col(
+                        ColumnDef::new(FailedEventIden::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(FailedEventIden::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FailedEventIden::UpdatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("failed_event_event_store_id_projection_idx")
+                    .table(FailedEventIden::Table)
+                    .col(FailedEventIden::EventStoreId)
+                    .col(FailedEventIden::Projection)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FailedEventIden::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,35 @@
+use carbonable_domain::infrastructure::postgres::entity::EventStoreIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EventStoreIden::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(EventStoreIden::SchemaVersion)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EventStoreIden::Table)
+                    .drop_column(EventStoreIden::SchemaVersion)
+                    .to_owned(),
+            )
+            .await
+    }
+}
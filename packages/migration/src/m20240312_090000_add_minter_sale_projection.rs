@@ -0,0 +1,43 @@
+use carbonable_domain::infrastructure::postgres::entity::BuyIden;
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BuyIden::Table)
+                    .add_column_if_not_exists(ColumnDef::new(BuyIden::Amount).binary().null())
+                    .to_owned(),
+            )
+            .await?;
+        // buy and airdrop were never written, projection is built from first event
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            VALUES ('minter_sale', NULL, 'running', NOW())
+            ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DELETE FROM projection_checkpoint WHERE name = 'minter_sale'"#)
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BuyIden::Table)
+                    .drop_column(BuyIden::Amount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,28 @@
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // seeded sale status is overwritten by replaying every minter event
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            VALUES ('minter_status', NULL, 'running', NOW())
+            ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DELETE FROM projection_checkpoint WHERE name = 'minter_status'"#)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use carbonable_domain::infrastructure::postgres::entity::{
+    OperatorApprovalIden, TokenApprovalIden,
+};
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        manager
+            .create_table(
+                Table::create()
+                    .table(TokenApprovalIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TokenApprovalIden::Id)
+                            .string()
+                            .string_len(26)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenApprovalIden::ProjectAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenApprovalIden::TokenId)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenApprovalIden::OperatorAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenApprovalIden::Approved)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(TokenApprovalIden::Value).binary().null())
+                    .index(
+                        Index::create()
+                            .name("token_approval_unique_idx")
+                            .table(TokenApprovalIden::Table)
+                            .col(TokenApprovalIden::ProjectAddress)
+                            .col(TokenApprovalIden::TokenId)
+                            .col(TokenApprovalIden::OperatorAddress)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(OperatorApprovalIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OperatorApprovalIden::Id)
+                            .string()
+                            .string_len(26)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OperatorApprovalIden::ProjectAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OperatorApprovalIden::OwnerAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OperatorApprovalIden::OperatorAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("operator_approval_unique_idx")
+                            .table(OperatorApprovalIden::Table)
+                            .col(OperatorApprovalIden::ProjectAddress)
+                            .col(OperatorApprovalIden::OwnerAddress)
+                            .col(OperatorApprovalIden::OperatorAddress)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            VALUES ('customer_approval', NULL, 'running', NOW())
+            ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"DELETE FROM projection_checkpoint WHERE name = 'customer_approval'"#,
+        )
+        .await?;
+        manager
+            .drop_table(Table::drop().table(OperatorApprovalIden::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TokenApprovalIden::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,30 @@
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // seeded uri and metadata are fetched again on each project metadata update
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            VALUES ('project_metadata', NULL, 'running', NOW())
+            ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"DELETE FROM projection_checkpoint WHERE name = 'project_metadata'"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
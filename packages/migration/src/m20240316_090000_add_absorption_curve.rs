@@ -0,0 +1,101 @@
+use carbonable_domain::infrastructure::postgres::entity::{
+    AbsorptionCurveIden, SEED_ABSORPTION_CURVE_EVENT_ID,
+};
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        manager
+            .create_table(
+                Table::create()
+                    .table(AbsorptionCurveIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::Id)
+                            .string()
+                            .string_len(26)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::ProjectAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AbsorptionCurveIden::Slot).binary().null())
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::Version)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::Times)
+                            .array(ColumnType::DateTime)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::Absorptions)
+                            .array(ColumnType::Binary(BlobSize::Medium))
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::EventId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AbsorptionCurveIden::Time)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // NULL slots of ERC-721 projects would be distinct from each other in a plain unique index
+        db.execute_unprepared(
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS absorption_curve_project_idx
+            ON absorption_curve (project_address, COALESCE(slot, '\x'::bytea), version)"#,
+        )
+        .await?;
+        // curves seeded on existing projects are recorded as version 0, projects seeded later get
+        // theirs on first update. Project ids are ulids unique per address and slot, revision
+        // time is project creation time read from the 48 bits timestamp of its ulid
+        db.execute_unprepared(&format!(
+            r#"INSERT INTO absorption_curve (id, project_address, slot, version, times, absorptions, event_id, time)
+            SELECT p.id, p.address, p.slot, 0, p.times, p.absorptions, '{SEED_ABSORPTION_CURVE_EVENT_ID}',
+                to_timestamp((
+                    SELECT SUM((strpos('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(UPPER(p.id), i, 1)) - 1)::numeric * power(32::numeric, 10 - i))
+                    FROM generate_series(1, 10) AS i
+                )::double precision / 1000) AT TIME ZONE 'UTC'
+            FROM project p
+            WHERE p.times IS NOT NULL AND p.absorptions IS NOT NULL
+            ON CONFLICT DO NOTHING"#
+        ))
+        .await?;
+        db.execute_unprepared(
+            r#"INSERT INTO projection_checkpoint (name, last_event_id, status, updated_at)
+            VALUES ('project_absorption', NULL, 'running', NOW())
+            ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"DELETE FROM projection_checkpoint WHERE name = 'project_absorption'"#,
+        )
+        .await?;
+        manager
+            .drop_table(Table::drop().table(AbsorptionCurveIden::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,76 @@
+use carbonable_domain::infrastructure::postgres::entity::SkippedEventIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SkippedEventIden::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SkippedEventIden::Id)
+                            .string()
+                            .string_len(26)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::Projection)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::ContractAddress)
+                            .string()
+                            .string_len(66)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::Selector)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::Count)
+                            .big_integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::LastEventId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkippedEventIden::UpdatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("skipped_event_projection_contract_selector_idx")
+                    .table(SkippedEventIden::Table)
+                    .col(SkippedEventIden::Projection)
+                    .col(SkippedEventIden::ContractAddress)
+                    .col(SkippedEventIden::Selector)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SkippedEventIden::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,49 @@
+use carbonable_domain::infrastructure::postgres::entity::EventStoreIden;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // events stored before have no stream, they were stored by the live stream
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EventStoreIden::Table)
+                    .add_column_if_not_exists(ColumnDef::new(EventStoreIden::Stream).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("event_store_stream_block_number_idx")
+                    .table(EventStoreIden::Table)
+                    .col(EventStoreIden::Stream)
+                    .col(EventStoreIden::BlockNumber)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("event_store_stream_block_number_idx")
+                    .table(EventStoreIden::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EventStoreIden::Table)
+                    .drop_column(EventStoreIden::Stream)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,76 @@
+use carbonable_domain::infrastructure::postgres::entity::{AirdropIden, BuyIden};
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BuyIden::Table)
+                    .add_column_if_not_exists(ColumnDef::new(BuyIden::EventId).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AirdropIden::Table)
+                    .add_column_if_not_exists(ColumnDef::new(AirdropIden::EventId).string())
+                    .to_owned(),
+            )
+            .await?;
+        // `hash` held the id of the event rows were projected from, identical sales of a
+        // transaction are told apart by their event id only
+        db.execute_unprepared(
+            r#"UPDATE buy SET event_id = hash WHERE event_id IS NULL;
+            UPDATE airdrop SET event_id = hash WHERE event_id IS NULL;
+            ALTER TABLE buy DROP CONSTRAINT IF EXISTS buy_unique_idx;
+            ALTER TABLE airdrop DROP CONSTRAINT IF EXISTS airdrop_unique_idx;"#,
+        )
+        .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("buy_event_id_idx")
+                    .table(BuyIden::Table)
+                    .col(BuyIden::EventId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("airdrop_event_id_idx")
+                    .table(AirdropIden::Table)
+                    .col(AirdropIden::EventId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BuyIden::Table)
+                    .drop_column(BuyIden::EventId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AirdropIden::Table)
+                    .drop_column(AirdropIden::EventId)
+                    .to_owned(),
+            )
+            .await
+    }
+}